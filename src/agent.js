@@ -61,6 +61,190 @@ function filtered(arr, filter) {
     return Array.from(finalResults);
 }
 
+// Hook event schema version; bump when the shape of hook:* events changes
+const HOOK_EVENT_VERSION = 1;
+
+// Short description of what a pointer-sized value points to: a symbol for
+// code, a printable C string, or the first bytes. Null when unreadable.
+function previewPointer(p) {
+    try {
+        if (p.isNull()) return null;
+        const range = Process.findRangeByAddress(p);
+        if (!range || !range.protection.includes('r')) return null;
+        if (range.protection.includes('x')) {
+            const frame = describeFrame(p);
+            if (frame.symbol) return frame.module ? frame.module + '!' + frame.symbol : frame.symbol;
+        }
+        try {
+            const s = p.readCString(64);
+            if (s !== null && s.length >= 2 && /^[\x20-\x7e\t\r\n]+$/.test(s)) {
+                return JSON.stringify(s);
+            }
+        } catch (e) {
+            // Not a string; fall through to bytes
+        }
+        const bytes = new Uint8Array(p.readByteArray(8));
+        return Array.from(bytes).map(b => b.toString(16).padStart(2, '0')).join(' ');
+    } catch (e) {
+        return null;
+    }
+}
+
+function describeValue(v) {
+    const value = { value: v.toString() };
+    const pointsTo = previewPointer(v);
+    if (pointsTo !== null) value.pointsTo = pointsTo;
+    return value;
+}
+
+// Readers for values behind a pointer, keyed by VzValueType name
+const MEMORY_READERS = {
+    Byte: p => p.readS8(),
+    UByte: p => p.readU8(),
+    Short: p => p.readS16(),
+    UShort: p => p.readU16(),
+    Int: p => p.readS32(),
+    UInt: p => p.readU32(),
+    Long: p => p.readS64(),
+    ULong: p => p.readU64(),
+    Float: p => p.readFloat(),
+    Double: p => p.readDouble(),
+    Bool: p => p.readU8() !== 0,
+    Pointer: p => p.readPointer()
+};
+
+// Interpret a register-sized argument slot as a VzValueType. Floats are
+// reinterpreted from the slot bits, so they only decode on ABIs that pass
+// them in general-purpose registers or on the stack.
+function registerValue(v, type) {
+    const bits = BigInt(v.toString(10));
+    switch (type) {
+        case 'Byte': return BigInt.asIntN(8, bits).toString();
+        case 'UByte': return BigInt.asUintN(8, bits).toString();
+        case 'Short': return BigInt.asIntN(16, bits).toString();
+        case 'UShort': return BigInt.asUintN(16, bits).toString();
+        case 'Int': return BigInt.asIntN(32, bits).toString();
+        case 'UInt': return BigInt.asUintN(32, bits).toString();
+        case 'Long': return BigInt.asIntN(64, bits).toString();
+        case 'ULong': return BigInt.asUintN(64, bits).toString();
+        case 'Bool': return String(BigInt.asUintN(8, bits) !== 0n);
+        case 'Float': {
+            const view = new DataView(new ArrayBuffer(4));
+            view.setUint32(0, Number(BigInt.asUintN(32, bits)), true);
+            return String(view.getFloat32(0, true));
+        }
+        case 'Double': {
+            const view = new DataView(new ArrayBuffer(8));
+            view.setBigUint64(0, BigInt.asUintN(64, bits), true);
+            return String(view.getFloat64(0, true));
+        }
+        default: return v.toString();
+    }
+}
+
+// Decode an argument or return value as described by a signature ArgSpec
+// ({ name, type, indirect?, size?, sizeFromRet?, out? }, see
+// src/gum/signature.rs). Out-parameters are decoded again with the function's
+// `retval`, which gives the length of `[ret]` buffers.
+function decodeArg(v, spec, retval) {
+    if (spec.type === 'Pointer' && !spec.indirect) {
+        return Object.assign({ name: spec.name }, describeValue(v));
+    }
+    const value = { value: v.toString(), name: spec.name };
+    // A [ret] buffer holds nothing meaningful until the function returns
+    if (spec.sizeFromRet && retval === undefined) {
+        return value;
+    }
+    const length = spec.sizeFromRet ? retval.toInt32() : null;
+    try {
+        if (v.isNull() && (spec.indirect || ['String', 'Utf16', 'Bytes'].includes(spec.type))) {
+            value.decoded = 'NULL';
+        } else if (length !== null && length < 0) {
+            value.decoded = '(failed)';
+        } else if (spec.type === 'String') {
+            value.decoded = JSON.stringify(length === null ? v.readCString(256) : v.readUtf8String(length));
+        } else if (spec.type === 'Utf16') {
+            value.decoded = JSON.stringify(v.readUtf16String(length === null ? 256 : length));
+        } else if (spec.type === 'Bytes') {
+            const size = length === null ? (spec.size || 16) : Math.min(length, 256);
+            value.decoded = size > 0 ? hexdump(v, { length: size, header: false, ansi: false }) : '';
+        } else if (spec.indirect) {
+            value.decoded = String(MEMORY_READERS[spec.type](v));
+        } else {
+            value.decoded = registerValue(v, spec.type);
+        }
+    } catch (e) {
+        value.decoded = '(unreadable)';
+    }
+    return value;
+}
+
+// Millisecond clock for hook timings; sub-millisecond where the runtime has one
+const hookClock = (typeof performance !== 'undefined' && performance.now)
+    ? () => performance.now()
+    : () => Date.now();
+
+// Values a `hook add --if` condition can test, computed only for the keys it
+// uses since conditions run on every call of the hooked function
+function hookFacts(keys, ctx, args, signature) {
+    const facts = {};
+    for (const key of keys) {
+        let m;
+        if (key === 'tid') {
+            facts.tid = ctx.threadId;
+        } else if (key === 'depth') {
+            facts.depth = ctx.depth;
+        } else if (key === 'caller') {
+            const frame = describeFrame(ctx.returnAddress);
+            facts.caller = [frame.module, frame.symbol].filter(Boolean).join('!') || frame.address;
+        } else if ((m = /^arg(\d+)$/.exec(key))) {
+            // Signed scalars compare by value when a signature gives their type
+            const spec = signature && signature.args[+m[1]];
+            const typed = spec && !spec.indirect && spec.type in MEMORY_READERS && spec.type !== 'Pointer';
+            facts[key] = typed ? registerValue(args[+m[1]], spec.type) : args[+m[1]].toString();
+        } else if ((m = /^(w?str)\(arg(\d+)\)$/.exec(key))) {
+            try {
+                const p = args[+m[2]];
+                facts[key] = (m[1] === 'str' ? p.readCString(256) : p.readUtf16String(256)) || '';
+            } catch (e) {
+                facts[key] = '';
+            }
+        }
+    }
+    return facts;
+}
+
+// Address where a DebugSymbol starts, or null. DebugSymbol.fromAddress reports
+// the address it was asked about, so the name is looked up again, first as an
+// export of the symbol's module.
+function symbolStart(symbol) {
+    const md = symbol.moduleName ? Process.findModuleByName(symbol.moduleName) : null;
+    const exp = md ? md.findExportByName(symbol.name) : null;
+    if (exp) {
+        return exp;
+    }
+    const named = DebugSymbol.fromName(symbol.name);
+    if (named.address.isNull()) {
+        return null;
+    }
+    if (md && (named.address.compare(md.base) < 0 || named.address.compare(md.base.add(md.size)) >= 0)) {
+        return null;
+    }
+    return named.address;
+}
+
+// Backtrace frame; offset is relative to the module base
+function describeFrame(addr) {
+    const module = Process.findModuleByAddress(addr);
+    const symbol = DebugSymbol.fromAddress(addr);
+    return {
+        address: addr.toString(),
+        module: module ? module.name : null,
+        symbol: symbol && symbol.name ? symbol.name : null,
+        offset: module ? addr.sub(module.base).toUInt32() : null
+    };
+}
+
 // ============================================================================
 // Hook Management State
 // ============================================================================
@@ -68,6 +252,451 @@ function filtered(arr, filter) {
 const activeHooks = new Map();  // id -> { listener, config, address, enabled }
 let hookIdCounter = 0;
 
+// Undo a hook; replacements (config.replace) are reverted rather than detached
+function detachHook(hook) {
+    if (hook.config.replace) {
+        Interceptor.revert(ptr(hook.target));
+    } else if (hook.listener) {
+        hook.listener.detach();
+    }
+}
+
+// ============================================================================
+// Breakpoint State
+// ============================================================================
+
+// Threads blocked in a break hook's onEnter, in the order they stopped.
+// context and args are the live InvocationContext values, so writes to them
+// take effect when the thread resumes.
+const stoppedThreads = new Map();  // threadId -> { hookId, address, context, args, config, timestamp }
+
+// Block the calling thread until the host posts 'break:resume:<threadId>'
+function breakHere(id, target, invocation, args, hookConfig) {
+    const threadId = invocation.threadId;
+    const timestamp = Date.now();
+    stoppedThreads.set(threadId, {
+        hookId: id,
+        address: target.toString(),
+        context: invocation.context,
+        args,
+        config: hookConfig,
+        timestamp
+    });
+    send({
+        type: 'break:hit',
+        v: HOOK_EVENT_VERSION,
+        id: id,
+        address: target.toString(),
+        threadId,
+        timestamp
+    });
+    try {
+        recv('break:resume:' + threadId, () => {}).wait();
+    } finally {
+        stoppedThreads.delete(threadId);
+    }
+}
+
+function stoppedThread(threadId) {
+    const stopped = stoppedThreads.get(threadId);
+    if (!stopped) {
+        throw new Error('Thread ' + threadId + ' is not stopped at a break hook');
+    }
+    return stopped;
+}
+
+// Register name -> value string for a CpuContext
+function contextRegisters(ctx) {
+    const regs = {};
+
+    // Architecture-specific register extraction
+    if (Process.arch === 'x64') {
+        regs.rax = ctx.rax.toString();
+        regs.rbx = ctx.rbx.toString();
+        regs.rcx = ctx.rcx.toString();
+        regs.rdx = ctx.rdx.toString();
+        regs.rsi = ctx.rsi.toString();
+        regs.rdi = ctx.rdi.toString();
+        regs.rbp = ctx.rbp.toString();
+        regs.rsp = ctx.rsp.toString();
+        regs.r8 = ctx.r8.toString();
+        regs.r9 = ctx.r9.toString();
+        regs.r10 = ctx.r10.toString();
+        regs.r11 = ctx.r11.toString();
+        regs.r12 = ctx.r12.toString();
+        regs.r13 = ctx.r13.toString();
+        regs.r14 = ctx.r14.toString();
+        regs.r15 = ctx.r15.toString();
+        regs.rip = ctx.pc.toString();
+        regs.rflags = ctx.rflags ? ctx.rflags.toString() : null;
+    } else if (Process.arch === 'ia32') {
+        regs.eax = ctx.eax.toString();
+        regs.ebx = ctx.ebx.toString();
+        regs.ecx = ctx.ecx.toString();
+        regs.edx = ctx.edx.toString();
+        regs.esi = ctx.esi.toString();
+        regs.edi = ctx.edi.toString();
+        regs.ebp = ctx.ebp.toString();
+        regs.esp = ctx.esp.toString();
+        regs.eip = ctx.pc.toString();
+    } else if (Process.arch === 'arm64') {
+        for (let i = 0; i <= 28; i++) {
+            regs['x' + i] = ctx['x' + i].toString();
+        }
+        regs.fp = ctx.fp.toString();
+        regs.lr = ctx.lr.toString();
+        regs.sp = ctx.sp.toString();
+        regs.pc = ctx.pc.toString();
+    } else if (Process.arch === 'arm') {
+        for (let i = 0; i <= 12; i++) {
+            regs['r' + i] = ctx['r' + i].toString();
+        }
+        regs.sp = ctx.sp.toString();
+        regs.lr = ctx.lr.toString();
+        regs.pc = ctx.pc.toString();
+        regs.cpsr = ctx.cpsr ? ctx.cpsr.toString() : null;
+    }
+
+    return regs;
+}
+
+// Registers of a thread; one stopped at a break hook reports its live hook
+// context
+function threadRegisters(threadId) {
+    const stopped = stoppedThreads.get(threadId);
+    if (stopped) {
+        return contextRegisters(stopped.context);
+    }
+
+    const thread = Process.enumerateThreads().find(t => t.id === threadId);
+    return thread && thread.context ? contextRegisters(thread.context) : null;
+}
+
+// ============================================================================
+// Pending Hook State
+// ============================================================================
+
+// Hooks on `module!symbol` targets whose module is not loaded yet. They keep
+// their ID and are attached by armPendingHooks once the module shows up.
+const pendingHooks = new Map();  // id -> { module, symbol, config, enabled, error }
+const MODULE_POLL_MS = 250;
+let moduleWatch = null;
+
+// Attach every enabled pending hook whose module is now loaded
+function armPendingHooks() {
+    for (const [id, pending] of pendingHooks) {
+        if (!pending.enabled || pending.error) continue;
+        const md = Process.findModuleByName(pending.module);
+        if (!md) continue;
+        
+        const addr = md.findExportByName(pending.symbol)
+            || (md.findSymbolByName ? md.findSymbolByName(pending.symbol) : null);
+        if (!addr) {
+            pending.error = 'Symbol not found: ' + pending.symbol;
+            send({ type: 'hook:error', id, error: pending.error });
+            continue;
+        }
+        const result = rpc.exports.hook_attach(addr.toString(), pending.config, null, id);
+        if (result.success) {
+            pendingHooks.delete(id);
+            send({ type: 'hook:attached', id, address: addr.toString() });
+        } else {
+            pending.error = result.error;
+            send({ type: 'hook:error', id, error: result.error });
+        }
+    }
+    // Not from inside the observer or dlopen callback that called us
+    if (pendingHooks.size === 0) setTimeout(unwatchModules, 0);
+}
+
+// Start noticing module loads: through a module observer where the runtime
+// has one, else after each dlopen, else by polling the module list
+function watchModules() {
+    if (moduleWatch) return;
+    if (typeof Process.attachModuleObserver === 'function') {
+        moduleWatch = Process.attachModuleObserver({ onAdded: armPendingHooks });
+        return;
+    }
+    const loaders = ['dlopen', 'android_dlopen_ext']
+        .map(name => Module.findGlobalExportByName
+            ? Module.findGlobalExportByName(name)
+            : Module.findExportByName(null, name))
+        .filter(addr => addr !== null);
+    if (loaders.length > 0) {
+        moduleWatch = loaders.map(addr => Interceptor.attach(addr, { onLeave: armPendingHooks }));
+    } else {
+        moduleWatch = setInterval(armPendingHooks, MODULE_POLL_MS);
+    }
+}
+
+// Stop noticing module loads once no pending hook is left
+function unwatchModules() {
+    if (!moduleWatch || pendingHooks.size > 0) return;
+    if (Array.isArray(moduleWatch)) {
+        moduleWatch.forEach(listener => listener.detach());
+    } else if (typeof moduleWatch.detach === 'function') {
+        moduleWatch.detach();
+    } else {
+        clearInterval(moduleWatch);
+    }
+    moduleWatch = null;
+}
+
+// ============================================================================
+// Trace State
+// ============================================================================
+
+// Trace calls are sent in batches to keep per-call overhead low
+const TRACE_BATCH_SIZE = 256;
+const TRACE_FLUSH_MS = 50;
+
+const traceListeners = [];
+let traceBuffer = [];
+let traceTimer = null;
+
+function traceFlush() {
+    if (traceTimer !== null) {
+        clearTimeout(traceTimer);
+        traceTimer = null;
+    }
+    if (traceBuffer.length === 0) return;
+    send({ type: 'trace:events', v: HOOK_EVENT_VERSION, events: traceBuffer });
+    traceBuffer = [];
+}
+
+function tracePush(event) {
+    traceBuffer.push(event);
+    if (traceBuffer.length >= TRACE_BATCH_SIZE) {
+        traceFlush();
+    } else if (traceTimer === null) {
+        traceTimer = setTimeout(traceFlush, TRACE_FLUSH_MS);
+    }
+}
+
+// ============================================================================
+// Stalker State
+// ============================================================================
+
+// Followed threads: threadId -> { mode, module }. Stalker hands each
+// thread's events over in batches (every Stalker.queueDrainInterval ms);
+// stalkSend packs them into records of STALK_RECORD_SIZE bytes, the layout
+// src/gum/stalk.rs reads.
+const STALK_RECORD_SIZE = 24;
+const STALK_KINDS = { call: 0, ret: 1, exec: 2, block: 3 };
+const STALK_EVENTS = {
+    calls: { call: true, ret: true },
+    blocks: { block: true },
+    insns: { exec: true }
+};
+
+const stalkedThreads = new Map();
+// Addresses already symbolized for the host, which keeps what it is sent
+const stalkSymbols = new Set();
+
+function stalkWriteAddress(view, offset, address) {
+    view.setUint32(offset, address.and(0xffffffff).toUInt32(), true);
+    view.setUint32(offset + 4, address.shr(32).and(0xffffffff).toUInt32(), true);
+}
+
+function stalkInRange(range, address) {
+    return address.compare(range.base) >= 0 && address.compare(range.end) < 0;
+}
+
+// Filter, symbolize and pack a drained Stalker batch, then send it. With a
+// range, only events with an address inside it are kept.
+function stalkSend(threadId, range, events) {
+    const parsed = Stalker.parse(events, { annotate: true, stringify: false });
+    const records = [];
+    const symbols = {};
+    for (const event of parsed) {
+        const kind = STALK_KINDS[event[0]];
+        if (kind === undefined) continue;
+        const location = event[1];
+        const target = (kind === STALK_KINDS.exec) ? ptr(0) : event[2];
+        if (range !== null && !stalkInRange(range, location)
+            && !(kind !== STALK_KINDS.block && stalkInRange(range, target))) {
+            continue;
+        }
+        const depth = (kind === STALK_KINDS.call || kind === STALK_KINDS.ret) ? event[3] : 0;
+        records.push([kind, depth, location, target]);
+        // Block ends and exec targets are never shown by name
+        const named = (kind === STALK_KINDS.call || kind === STALK_KINDS.ret)
+            ? [location, target] : [location];
+        for (const address of named) {
+            const key = address.toString();
+            if (stalkSymbols.has(key)) continue;
+            stalkSymbols.add(key);
+            symbols[key] = describeFrame(address);
+        }
+    }
+    if (records.length === 0) return;
+    const buffer = new ArrayBuffer(records.length * STALK_RECORD_SIZE);
+    const view = new DataView(buffer);
+    records.forEach(([kind, depth, location, target], i) => {
+        const offset = i * STALK_RECORD_SIZE;
+        view.setUint32(offset, kind, true);
+        view.setInt32(offset + 4, depth, true);
+        stalkWriteAddress(view, offset + 8, location);
+        stalkWriteAddress(view, offset + 16, target);
+    });
+    send({ type: 'stalk:events', v: HOOK_EVENT_VERSION, threadId, symbols }, buffer);
+}
+
+// ============================================================================
+// Coverage State
+// ============================================================================
+
+// Basic blocks compiled by the threads `coverage start` follows, start ->
+// size. Stalker compiles a block once per thread, and only the first time
+// is kept, so this is what ran rather than how often.
+const coverageBlocks = new Map();
+let coverageThreads = [];
+let coverageRange = null;
+
+function coverageReceive(events) {
+    for (const event of Stalker.parse(events, { annotate: true, stringify: false })) {
+        if (event[0] !== 'compile') continue;
+        const start = event[1];
+        if (coverageRange !== null && !stalkInRange(coverageRange, start)) continue;
+        const key = start.toString();
+        if (!coverageBlocks.has(key)) {
+            coverageBlocks.set(key, event[2].sub(start).toUInt32());
+        }
+    }
+}
+
+// ============================================================================
+// Watchpoint State
+// ============================================================================
+
+// MemoryAccessMonitor watches whole pages and forgets a page once it is
+// touched, so every watched page is re-protected WATCH_REARM_MS after a hit,
+// once the faulting instruction has gone through. Accesses to that page in
+// between are missed. Freezes kept on write share the monitor.
+const WATCH_REARM_MS = 5;
+
+const watchpoints = new Map();  // id -> { address, size, operations, hits }
+let watchIdCounter = 0;
+let watchRearmTimer = null;
+
+function watchInfo(id, watch) {
+    return {
+        id,
+        address: watch.address.toString(),
+        size: watch.size,
+        operations: watch.operations,
+        hits: watch.hits
+    };
+}
+
+function watchOnAccess(details) {
+    for (const [id, watch] of watchpoints) {
+        const offset = details.address.sub(watch.address);
+        if (details.address.compare(watch.address) < 0 || offset.compare(ptr(watch.size)) >= 0
+            || !watch.operations.includes(details.operation)) {
+            continue;
+        }
+        watch.hits++;
+        send({
+            type: 'watch:hit',
+            v: HOOK_EVENT_VERSION,
+            id,
+            operation: details.operation,
+            address: details.address.toString(),
+            from: describeFrame(details.from),
+            threadId: details.threadId !== undefined ? details.threadId : null,
+            backtrace: details.context
+                ? Thread.backtrace(details.context, Backtracer.ACCURATE).map(describeFrame)
+                : [],
+            timestamp: Date.now()
+        });
+    }
+    if (watchRearmTimer === null) {
+        watchRearmTimer = setTimeout(() => {
+            watchRearmTimer = null;
+            watchArm();
+        }, WATCH_REARM_MS);
+    }
+}
+
+// Monitor the pages of every watchpoint and write-triggered freeze,
+// replacing the previous monitor. Those freezes are rewritten while the
+// monitor is off, since their page may have been written since the last
+// arming and the rewrite must not be reported as an access.
+function watchArm() {
+    try {
+        MemoryAccessMonitor.disable();
+    } catch (e) {
+        // Not enabled
+    }
+    freezeOnWrite();
+    const pageSize = Process.pageSize;
+    const pages = new Map();
+    const watched = [...watchpoints.values()]
+        .concat([...freezes.values()].filter(freeze => freeze.onWrite));
+    for (const watch of watched) {
+        const first = watch.address.and(ptr(pageSize - 1).not());
+        const end = watch.address.add(watch.size);
+        for (let page = first; page.compare(end) < 0; page = page.add(pageSize)) {
+            pages.set(page.toString(), page);
+        }
+    }
+    if (pages.size === 0) return 0;
+    const ranges = [...pages.values()].map(base => ({ base, size: pageSize }));
+    MemoryAccessMonitor.enable(ranges, { onAccess: watchOnAccess });
+    return ranges.length;
+}
+
+// ============================================================================
+// Freeze State
+// ============================================================================
+
+// Values `freeze` keeps rewriting: id -> { address, size, writer, value,
+// type, text, interval, onWrite, timer, writes }. writer names one of the
+// writer_* exports, so a freeze writes exactly what `write` would.
+const FREEZE_INTERVAL_MS = 100;
+
+const freezes = new Map();
+let freezeIdCounter = 0;
+
+function freezeInfo(id, freeze) {
+    return {
+        id,
+        address: freeze.address.toString(),
+        type: freeze.type,
+        value: freeze.text,
+        interval: freeze.onWrite ? null : freeze.interval,
+        onWrite: freeze.onWrite,
+        writes: freeze.writes
+    };
+}
+
+// A freeze whose write fails (say, the memory was unmapped) is dropped
+function freezeApply(id, freeze) {
+    try {
+        rpc.exports[freeze.writer](freeze.address, freeze.value);
+        freeze.writes++;
+    } catch (e) {
+        freezeStop(id);
+        send({ type: 'freeze:error', v: HOOK_EVENT_VERSION, id, error: e.message });
+    }
+}
+
+// Rewrite every freeze kept on write
+function freezeOnWrite() {
+    for (const [id, freeze] of freezes) {
+        if (freeze.onWrite) freezeApply(id, freeze);
+    }
+}
+
+function freezeStop(id) {
+    const freeze = freezes.get(id);
+    if (!freeze) return false;
+    if (freeze.timer !== null) clearInterval(freeze.timer);
+    freezes.delete(id);
+    return true;
+}
+
 // ============================================================================
 // Scan State
 // ============================================================================
@@ -75,6 +704,32 @@ let hookIdCounter = 0;
 let scanResults = [];
 let scanSnapshots = new Map();  // address -> { original, current }
 
+// ============================================================================
+// Allocation State
+// ============================================================================
+
+// Buffers from the alloc commands. Frida frees a Memory.alloc buffer once the
+// NativePointer is garbage collected, so each one stays here until freed.
+const allocations = new Map();  // id -> { buffer, size, protection, kind }
+let allocIdCounter = 0;
+
+function allocationInfo(id, entry) {
+    return {
+        id,
+        address: entry.buffer.toString(),
+        size: entry.size,
+        protection: entry.protection,
+        kind: entry.kind
+    };
+}
+
+function registerAllocation(buffer, size, protection, kind) {
+    const id = 'alloc_' + (allocIdCounter++);
+    const entry = { buffer, size, protection, kind };
+    allocations.set(id, entry);
+    return Object.assign({ success: true }, allocationInfo(id, entry));
+}
+
 // ============================================================================
 // RPC Exports
 // ============================================================================
@@ -116,6 +771,7 @@ rpc.exports = {
     reader_float: a => ptr(a).readFloat(),
     reader_double: a => ptr(a).readDouble(),
     reader_string: (a, l = 256) => ptr(a).readCString(l),
+    reader_utf16: (a, l = 256) => ptr(a).readUtf16String(l),
     reader_bytes: (a, l = 8) => Array.from(new Uint8Array(ptr(a).readByteArray(l))),
     reader_pointer: a => ptr(a).readPointer().toString(),
     
@@ -134,6 +790,7 @@ rpc.exports = {
     writer_float: (a, v) => ptr(a).writeFloat(v),
     writer_double: (a, v) => ptr(a).writeDouble(v),
     writer_string: (a, v) => ptr(a).writeUtf8String(v),
+    writer_utf16: (a, v) => ptr(a).writeUtf16String(v),
     writer_bytes: (a, v) => ptr(a).writeByteArray(v),
     writer_pointer: (a, v) => ptr(a).writePointer(ptr(v)),
 
@@ -177,6 +834,149 @@ rpc.exports = {
         }
     },
 
+    // ========================================================================
+    // Memory Allocation
+    // ========================================================================
+
+    // Allocate size bytes. With a protection other than the default
+    // rw-, whole pages are allocated so protecting them leaves the heap alone.
+    mem_alloc: (size, protection = null) => {
+        try {
+            if (protection === null || protection === 'rw-') {
+                return registerAllocation(Memory.alloc(size), size, 'rw-', 'bytes');
+            }
+            const pages = Math.ceil(size / Process.pageSize) * Process.pageSize;
+            const buffer = Memory.alloc(pages);
+            Memory.protect(buffer, pages, protection);
+            return registerAllocation(buffer, pages, protection, 'bytes');
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    // Allocate a NUL-terminated copy of text, as UTF-8 or (utf16) UTF-16
+    mem_alloc_string: (text, encoding = 'utf8') => {
+        try {
+            if (encoding === 'utf16') {
+                const buffer = Memory.allocUtf16String(text);
+                return registerAllocation(buffer, (text.length + 1) * 2, 'rw-', 'utf16');
+            }
+            const buffer = Memory.allocUtf8String(text);
+            const size = unescape(encodeURIComponent(text)).length + 1;
+            return registerAllocation(buffer, size, 'rw-', 'string');
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    // Drop an allocation; Frida frees it once nothing else references it
+    mem_free: (id) => {
+        if (!allocations.delete(id)) {
+            return { success: false, error: 'Allocation not found' };
+        }
+        return { success: true };
+    },
+
+    mem_allocations: () => Array.from(allocations, ([id, entry]) => allocationInfo(id, entry)),
+
+    // ========================================================================
+    // Watchpoints
+    // ========================================================================
+
+    // Report accesses to size bytes at address; operations are any of
+    // 'read', 'write' and 'execute'
+    watch_add: (a, size, operations) => {
+        const id = 'watch_' + (watchIdCounter++);
+        watchpoints.set(id, { address: ptr(a), size, operations, hits: 0 });
+        try {
+            const pages = watchArm();
+            return Object.assign({ success: true, pages }, watchInfo(id, watchpoints.get(id)));
+        } catch (e) {
+            watchpoints.delete(id);
+            try {
+                watchArm();
+            } catch (ignored) {
+                // Leave the remaining watchpoints unarmed
+            }
+            return { success: false, error: e.message };
+        }
+    },
+
+    watch_remove: (id) => {
+        if (!watchpoints.delete(id)) {
+            return { success: false, error: 'Watchpoint not found: ' + id };
+        }
+        try {
+            watchArm();
+            return { success: true };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    watch_list: () => Array.from(watchpoints, ([id, watch]) => watchInfo(id, watch)),
+
+    // ========================================================================
+    // Freezes
+    // ========================================================================
+
+    // Keep writing value at address with a writer_* export: every
+    // config.interval ms, or with config.onWrite whenever the page is
+    // written. config.type and config.text describe the value for listing.
+    freeze_add: (a, writer, value, config = {}) => {
+        if (!/^writer_/.test(writer) || typeof rpc.exports[writer] !== 'function') {
+            return { success: false, error: 'Unknown writer: ' + writer };
+        }
+        const id = 'freeze_' + (freezeIdCounter++);
+        const freeze = {
+            address: ptr(a),
+            size: Math.max(1, config.size || 1),
+            writer,
+            value,
+            type: config.type || null,
+            text: config.text || String(value),
+            interval: config.interval || FREEZE_INTERVAL_MS,
+            onWrite: !!config.onWrite,
+            timer: null,
+            writes: 0
+        };
+        try {
+            rpc.exports[writer](freeze.address, value);
+            freeze.writes++;
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+        freezes.set(id, freeze);
+        try {
+            if (freeze.onWrite) {
+                watchArm();
+            } else {
+                freeze.timer = setInterval(() => freezeApply(id, freeze), freeze.interval);
+            }
+        } catch (e) {
+            freezeStop(id);
+            return { success: false, error: e.message };
+        }
+        return Object.assign({ success: true }, freezeInfo(id, freeze));
+    },
+
+    freeze_remove: (id) => {
+        const freeze = freezes.get(id);
+        if (!freezeStop(id)) {
+            return { success: false, error: 'Freeze not found: ' + id };
+        }
+        if (freeze.onWrite) {
+            try {
+                watchArm();
+            } catch (e) {
+                return { success: false, error: e.message };
+            }
+        }
+        return { success: true };
+    },
+
+    freeze_list: () => Array.from(freezes, ([id, freeze]) => freezeInfo(id, freeze)),
+
     // ========================================================================
     // Disassembly
     // ========================================================================
@@ -409,11 +1209,72 @@ rpc.exports = {
         return null;
     },
     
+    // Symbol containing each address, with the address it starts at
+    symbol_starts: (addresses) => {
+        const starts = new Map();
+        return addresses.map(a => {
+            const p = ptr(a);
+            const symbol = DebugSymbol.fromAddress(p);
+            if (!symbol || !symbol.name) {
+                return null;
+            }
+            const key = symbol.moduleName + '!' + symbol.name;
+            if (!starts.has(key)) {
+                starts.set(key, symbolStart(symbol));
+            }
+            const start = starts.get(key);
+            if (start === null || start.compare(p) > 0) {
+                return null;
+            }
+            return {
+                name: symbol.name,
+                moduleName: symbol.moduleName,
+                address: start.toString()
+            };
+        });
+    },
+
     resolve_export: (moduleName, exportName) => {
-        const addr = Module.findExportByName(moduleName, exportName);
+        const md = Process.findModuleByName(moduleName);
+        const addr = md ? md.findExportByName(exportName) : null;
         return addr ? addr.toString() : null;
     },
 
+    // A bare name in an address expression: a module, then a debug symbol,
+    // then a register of the given thread or, without one, of the thread
+    // stopped longest at a break hook
+    resolve_name: (name, threadId) => {
+        const md = Process.findModuleByName(name);
+        if (md) {
+            return md.base.toString();
+        }
+        const symbol = DebugSymbol.fromName(name);
+        if (symbol && !symbol.address.isNull()) {
+            return symbol.address.toString();
+        }
+        if (threadId === null) {
+            const oldest = stoppedThreads.keys().next();
+            if (oldest.done) {
+                return null;
+            }
+            threadId = oldest.value;
+        }
+        const regs = threadRegisters(threadId);
+        return regs && typeof regs[name] === 'string' ? regs[name] : null;
+    },
+
+    find_module: (name) => {
+        const md = Process.findModuleByName(name);
+        if (!md) {
+            return null;
+        }
+        return {
+            name: md.name,
+            address: md.base.toString(),
+            size: md.size
+        };
+    },
+
     // ========================================================================
     // Thread Operations
     // ========================================================================
@@ -430,65 +1291,7 @@ rpc.exports = {
     },
     
     get_thread_context: (threadId) => {
-        const threads = Process.enumerateThreads();
-        const thread = threads.find(t => t.id === threadId);
-        
-        if (!thread || !thread.context) {
-            return null;
-        }
-        
-        const ctx = thread.context;
-        const regs = {};
-        
-        // Architecture-specific register extraction
-        if (Process.arch === 'x64') {
-            regs.rax = ctx.rax.toString();
-            regs.rbx = ctx.rbx.toString();
-            regs.rcx = ctx.rcx.toString();
-            regs.rdx = ctx.rdx.toString();
-            regs.rsi = ctx.rsi.toString();
-            regs.rdi = ctx.rdi.toString();
-            regs.rbp = ctx.rbp.toString();
-            regs.rsp = ctx.rsp.toString();
-            regs.r8 = ctx.r8.toString();
-            regs.r9 = ctx.r9.toString();
-            regs.r10 = ctx.r10.toString();
-            regs.r11 = ctx.r11.toString();
-            regs.r12 = ctx.r12.toString();
-            regs.r13 = ctx.r13.toString();
-            regs.r14 = ctx.r14.toString();
-            regs.r15 = ctx.r15.toString();
-            regs.rip = ctx.pc.toString();
-            regs.rflags = ctx.rflags ? ctx.rflags.toString() : null;
-        } else if (Process.arch === 'ia32') {
-            regs.eax = ctx.eax.toString();
-            regs.ebx = ctx.ebx.toString();
-            regs.ecx = ctx.ecx.toString();
-            regs.edx = ctx.edx.toString();
-            regs.esi = ctx.esi.toString();
-            regs.edi = ctx.edi.toString();
-            regs.ebp = ctx.ebp.toString();
-            regs.esp = ctx.esp.toString();
-            regs.eip = ctx.pc.toString();
-        } else if (Process.arch === 'arm64') {
-            for (let i = 0; i <= 28; i++) {
-                regs['x' + i] = ctx['x' + i].toString();
-            }
-            regs.fp = ctx.fp.toString();
-            regs.lr = ctx.lr.toString();
-            regs.sp = ctx.sp.toString();
-            regs.pc = ctx.pc.toString();
-        } else if (Process.arch === 'arm') {
-            for (let i = 0; i <= 12; i++) {
-                regs['r' + i] = ctx['r' + i].toString();
-            }
-            regs.sp = ctx.sp.toString();
-            regs.lr = ctx.lr.toString();
-            regs.pc = ctx.pc.toString();
-            regs.cpsr = ctx.cpsr ? ctx.cpsr.toString() : null;
-        }
-        
-        return regs;
+        return threadRegisters(threadId);
     },
     
     read_stack: (address, depth = 32) => {
@@ -519,34 +1322,27 @@ rpc.exports = {
         return stack;
     },
     
-    backtrace: (contextPtr = null) => {
+    // Backtrace of a thread, from its hook context when stopped at a break
+    // hook; without a thread ID, of the agent's own thread
+    backtrace: (threadId = null) => {
         let context = null;
-        if (contextPtr) {
-            // This would need to be a CpuContext, typically from a hook
-            context = contextPtr;
+        if (threadId !== null) {
+            const stopped = stoppedThreads.get(threadId);
+            const thread = stopped ? null : Process.enumerateThreads().find(t => t.id === threadId);
+            context = stopped ? stopped.context : (thread ? thread.context : null);
         }
         
-        const bt = Thread.backtrace(context, Backtracer.ACCURATE);
-        return bt.map(addr => {
-            const module = Process.findModuleByAddress(addr);
-            const symbol = DebugSymbol.fromAddress(addr);
-            
-            return {
-                address: addr.toString(),
-                module: module ? module.name : null,
-                symbol: symbol ? symbol.name : null,
-                offset: module ? addr.sub(module.base).toInt32() : null
-            };
-        });
+        return Thread.backtrace(context, Backtracer.ACCURATE).map(describeFrame);
     },
 
     // ========================================================================
     // Function Hooking (Interceptor)
     // ========================================================================
     
-    hook_attach: (address, config = {}) => {
+    // reuseId keeps the ID of a pending hook when it is armed
+    hook_attach: (address, config = {}, prevStats = null, reuseId = null) => {
         try {
-            const id = 'hook_' + (hookIdCounter++);
+            const id = reuseId || 'hook_' + (hookIdCounter++);
             const target = ptr(address);
             
             // Validate address
@@ -563,40 +1359,60 @@ rpc.exports = {
                 argCount: config.argCount || 4,
                 modifyArgs: config.modifyArgs || null,
                 modifyRetval: config.modifyRetval || null,
-                backtrace: config.backtrace === true
+                backtrace: config.backtrace === true,
+                breakpoint: config.breakpoint === true,
+                signature: config.signature || null,
+                condition: config.condition || null,
+                conditionText: config.conditionText || null
             };
+            const signature = hookConfig.signature;
+            if (signature) {
+                hookConfig.argCount = signature.args.length;
+            }
+            const condition = hookConfig.condition;
+            const conditionKeys = condition ? condition.filter(Array.isArray).map(c => c[0]) : [];
+            // Counted for every matching call, whether or not it is logged;
+            // kept across disable/enable, which re-attaches under a new ID
+            const stats = prevStats || { calls: 0, threads: new Map(), timed: 0, totalTime: 0, maxTime: 0 };
             
             const listener = Interceptor.attach(target, {
                 onEnter: function(args) {
+                    // Calls failing the condition are neither reported nor modified
+                    this.matched = !condition ||
+                        filtered([hookFacts(conditionKeys, this, args, signature)], condition).length > 0;
+                    if (!this.matched) return;
+                    
+                    stats.calls++;
+                    stats.threads.set(this.threadId, (stats.threads.get(this.threadId) || 0) + 1);
+                    this.enterTime = hookClock();
+                    
                     if (hookConfig.onEnter) {
                         const data = {
-                            type: 'hook_enter',
+                            type: 'hook:enter',
+                            v: HOOK_EVENT_VERSION,
                             id: id,
-                            address: address,
+                            address: target.toString(),
                             threadId: this.threadId,
-                            depth: this.depth
+                            depth: this.depth,
+                            timestamp: Date.now()
                         };
                         
                         if (hookConfig.logArgs) {
                             data.args = [];
                             for (let i = 0; i < hookConfig.argCount; i++) {
                                 try {
-                                    data.args.push(args[i].toString());
+                                    data.args.push(signature
+                                        ? decodeArg(args[i], signature.args[i])
+                                        : describeValue(args[i]));
                                 } catch (e) {
-                                    data.args.push('(error)');
+                                    data.args.push({ value: '(error)' });
                                 }
                             }
                         }
                         
                         if (hookConfig.backtrace) {
                             data.backtrace = Thread.backtrace(this.context, Backtracer.ACCURATE)
-                                .map(addr => {
-                                    const sym = DebugSymbol.fromAddress(addr);
-                                    return {
-                                        address: addr.toString(),
-                                        symbol: sym ? sym.name : null
-                                    };
-                                });
+                                .map(describeFrame);
                         }
                         
                         send(data);
@@ -616,28 +1432,55 @@ rpc.exports = {
                     }
                     
                     // Store context for onLeave
-                    this.hookContext = { args: [], config: hookConfig };
+                    this.hookContext = { args: [], config: hookConfig, outArgs: [] };
+                    if (signature) {
+                        signature.args.forEach((spec, i) => {
+                            if (spec.out) this.hookContext.outArgs.push([args[i], spec]);
+                        });
+                    }
                     if (hookConfig.logArgs) {
                         for (let i = 0; i < hookConfig.argCount; i++) {
                             try {
-                                this.hookContext.args.push(args[i].toString());
+                                this.hookContext.args.push({ value: args[i].toString() });
                             } catch (e) {
-                                this.hookContext.args.push('(error)');
+                                this.hookContext.args.push({ value: '(error)' });
                             }
                         }
                     }
+                    
+                    if (hookConfig.breakpoint) {
+                        breakHere(id, target, this, args, hookConfig);
+                        // Time spent stopped is not the function's
+                        this.enterTime = hookClock();
+                    }
                 },
                 onLeave: function(retval) {
+                    if (!this.matched) return;
+                    
+                    const elapsed = (hookClock() - this.enterTime) * 1000;
+                    stats.timed++;
+                    stats.totalTime += elapsed;
+                    stats.maxTime = Math.max(stats.maxTime, elapsed);
+                    
                     if (hookConfig.onLeave) {
                         const data = {
-                            type: 'hook_leave',
+                            type: 'hook:leave',
+                            v: HOOK_EVENT_VERSION,
                             id: id,
-                            address: address,
-                            threadId: this.threadId
+                            address: target.toString(),
+                            threadId: this.threadId,
+                            depth: this.depth,
+                            timestamp: Date.now()
                         };
                         
                         if (hookConfig.logRetval) {
-                            data.retval = retval.toString();
+                            data.retval = signature && signature.ret
+                                ? decodeArg(retval, signature.ret)
+                                : describeValue(retval);
+                        }
+                        
+                        if (this.hookContext && this.hookContext.outArgs.length > 0) {
+                            data.out = this.hookContext.outArgs.map(([p, spec]) => decodeArg(p, spec, retval));
                         }
                         
                         if (hookConfig.logArgs && this.hookContext) {
@@ -661,6 +1504,7 @@ rpc.exports = {
             activeHooks.set(id, { 
                 listener, 
                 config: hookConfig, 
+                stats,
                 address: address,
                 enabled: true,
                 target: target.toString()
@@ -672,11 +1516,79 @@ rpc.exports = {
         }
     },
     
+    // Replace a function with a stub that returns config.retval, or with
+    // config.skip does nothing. retType, argTypes and abi describe the
+    // NativeCallback; argTypes only matter for callee-cleanup ABIs (stdcall).
+    hook_replace: (address, config = {}) => {
+        try {
+            const target = ptr(address);
+            const range = Process.findRangeByAddress(target);
+            if (!range || !range.protection.includes('x')) {
+                return { success: false, error: 'Invalid or non-executable address' };
+            }
+            
+            const replaceConfig = {
+                replace: true,
+                skip: config.skip === true,
+                retval: config.skip ? null : config.retval,
+                retType: config.skip ? 'void' : (config.retType || 'pointer'),
+                argTypes: config.argTypes || [],
+                abi: config.abi || 'default'
+            };
+            const value = replaceConfig.retType === 'pointer'
+                ? ptr(replaceConfig.retval)
+                : replaceConfig.retval;
+            
+            const stats = { calls: 0, threads: new Map(), timed: 0, totalTime: 0, maxTime: 0 };
+            const callback = new NativeCallback(function() {
+                stats.calls++;
+                stats.threads.set(this.threadId, (stats.threads.get(this.threadId) || 0) + 1);
+                return value;
+            }, replaceConfig.retType, replaceConfig.argTypes, replaceConfig.abi);
+            Interceptor.replace(target, callback);
+            
+            const id = 'hook_' + (hookIdCounter++);
+            activeHooks.set(id, {
+                listener: null,
+                callback,
+                config: replaceConfig,
+                stats,
+                address: address,
+                enabled: true,
+                target: target.toString()
+            });
+            return { success: true, id: id, address: address };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+    
+    // Hook `moduleName!symbol` once moduleName is loaded; config is that of
+    // hook_attach
+    hook_defer: (moduleName, symbol, config = {}) => {
+        try {
+            const id = 'hook_' + (hookIdCounter++);
+            pendingHooks.set(id, { module: moduleName, symbol, config, enabled: true, error: null });
+            watchModules();
+            // The module may have been loaded since the host looked
+            armPendingHooks();
+            return { success: true, id: id };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+    
     hook_detach: (id) => {
+        if (pendingHooks.delete(id)) {
+            unwatchModules();
+            return { success: true };
+        }
         const hook = activeHooks.get(id);
         if (hook) {
             try {
-                hook.listener.detach();
+                if (hook.enabled) {
+                    detachHook(hook);
+                }
                 activeHooks.delete(id);
                 return { success: true };
             } catch (e) {
@@ -687,6 +1599,24 @@ rpc.exports = {
     },
     
     hook_list: () => {
+        const pending = Array.from(pendingHooks, ([id, p]) => ({
+            id: id,
+            address: null,
+            target: p.module + '!' + p.symbol,
+            enabled: p.enabled,
+            pending: p.error ? 'failed' : 'waiting',
+            error: p.error,
+            config: {
+                onEnter: p.config.onEnter !== false,
+                onLeave: p.config.onLeave === true,
+                logArgs: p.config.logArgs === true,
+                logRetval: p.config.logRetval === true,
+                signature: p.config.signature ? { name: p.config.signature.name } : null,
+                conditionText: p.config.conditionText || null,
+                breakpoint: p.config.breakpoint === true,
+                replace: null
+            }
+        }));
         return Array.from(activeHooks.entries()).map(([id, h]) => ({
             id: id,
             address: h.address,
@@ -696,12 +1626,39 @@ rpc.exports = {
                 onEnter: h.config.onEnter,
                 onLeave: h.config.onLeave,
                 logArgs: h.config.logArgs,
-                logRetval: h.config.logRetval
+                logRetval: h.config.logRetval,
+                signature: h.config.signature ? { name: h.config.signature.name } : null,
+                conditionText: h.config.conditionText,
+                breakpoint: h.config.breakpoint === true,
+                replace: h.config.replace
+                    ? { skip: h.config.skip, retval: h.config.retval }
+                    : null
             }
-        }));
+        })).concat(pending);
+    },
+    
+    // Call counts and enter-to-leave times (microseconds) of one or all hooks
+    hook_stats: (id = null) => {
+        return Array.from(activeHooks.entries())
+            .filter(([hookId]) => id === null || hookId === id)
+            .map(([hookId, h]) => ({
+                id: hookId,
+                address: h.target,
+                calls: h.stats.calls,
+                threads: Array.from(h.stats.threads, ([tid, calls]) => ({ id: tid, calls })),
+                timed: h.stats.timed,
+                totalTime: h.stats.totalTime,
+                maxTime: h.stats.maxTime
+            }));
     },
     
     hook_enable: (id) => {
+        const pending = pendingHooks.get(id);
+        if (pending) {
+            pending.enabled = true;
+            armPendingHooks();
+            return { success: true };
+        }
         const hook = activeHooks.get(id);
         if (!hook) {
             return { success: false, error: 'Hook not found' };
@@ -710,9 +1667,19 @@ rpc.exports = {
             return { success: true, message: 'Already enabled' };
         }
         
+        if (hook.config.replace) {
+            try {
+                Interceptor.replace(ptr(hook.target), hook.callback);
+                hook.enabled = true;
+                return { success: true };
+            } catch (e) {
+                return { success: false, error: e.message };
+            }
+        }
+        
         // Re-attach the hook
         try {
-            const result = rpc.exports.hook_attach(hook.address, hook.config);
+            const result = rpc.exports.hook_attach(hook.address, hook.config, hook.stats);
             if (result.success) {
                 activeHooks.delete(id);
                 return { success: true, newId: result.id };
@@ -724,6 +1691,11 @@ rpc.exports = {
     },
     
     hook_disable: (id) => {
+        const pending = pendingHooks.get(id);
+        if (pending) {
+            pending.enabled = false;
+            return { success: true };
+        }
         const hook = activeHooks.get(id);
         if (!hook) {
             return { success: false, error: 'Hook not found' };
@@ -733,7 +1705,7 @@ rpc.exports = {
         }
         
         try {
-            hook.listener.detach();
+            detachHook(hook);
             hook.enabled = false;
             hook.listener = null;
             return { success: true };
@@ -746,19 +1718,305 @@ rpc.exports = {
         let count = 0;
         for (const [id, hook] of activeHooks) {
             try {
-                if (hook.listener) {
-                    hook.listener.detach();
+                if (hook.enabled) {
+                    detachHook(hook);
                 }
                 count++;
             } catch (e) {
                 // Ignore errors during cleanup
             }
         }
+        count += pendingHooks.size;
         activeHooks.clear();
+        pendingHooks.clear();
+        unwatchModules();
         hookIdCounter = 0;
         return { success: true, count: count };
     },
 
+    // Threads stopped at break hooks, oldest first
+    break_list: () => {
+        return Array.from(stoppedThreads, ([threadId, s]) => ({
+            threadId,
+            id: s.hookId,
+            address: s.address,
+            timestamp: s.timestamp
+        }));
+    },
+    
+    // Arguments of a stopped thread, decoded by the hook's signature if any
+    break_args: (threadId) => {
+        const stopped = stoppedThread(threadId);
+        const signature = stopped.config.signature;
+        const values = [];
+        for (let i = 0; i < stopped.config.argCount; i++) {
+            try {
+                values.push(signature
+                    ? decodeArg(stopped.args[i], signature.args[i])
+                    : describeValue(stopped.args[i]));
+            } catch (e) {
+                values.push({ value: '(error)' });
+            }
+        }
+        return values;
+    },
+    
+    break_set_arg: (threadId, index, value) => {
+        try {
+            stoppedThread(threadId).args[index] = ptr(value);
+            return { success: true };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+    
+    // Registers use the names of get_thread_context; rip/eip alias pc
+    break_set_reg: (threadId, name, value) => {
+        try {
+            const context = stoppedThread(threadId).context;
+            const key = (name === 'rip' || name === 'eip') ? 'pc' : name;
+            if (!(key in context)) {
+                return { success: false, error: 'Unknown register: ' + name };
+            }
+            context[key] = ptr(value);
+            return { success: true };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    // Attach enter (and with withRetval, leave) hooks to each { name, address }
+    trace_start: (targets, withRetval = false) => {
+        let count = 0;
+        const failed = [];
+        for (const t of targets) {
+            const name = t.name;
+            const callbacks = {
+                onEnter: function() {
+                    tracePush({
+                        kind: 'enter', name, threadId: this.threadId,
+                        depth: this.depth, timestamp: Date.now()
+                    });
+                }
+            };
+            if (withRetval) {
+                callbacks.onLeave = function(retval) {
+                    tracePush({
+                        kind: 'leave', name, threadId: this.threadId,
+                        depth: this.depth, timestamp: Date.now(), retval: retval.toString()
+                    });
+                };
+            }
+            try {
+                traceListeners.push(Interceptor.attach(ptr(t.address), callbacks));
+                count++;
+            } catch (e) {
+                failed.push(name);
+            }
+        }
+        Interceptor.flush();
+        return { success: true, count, failed };
+    },
+    
+    trace_stop: () => {
+        const count = traceListeners.length;
+        traceListeners.forEach(l => {
+            try {
+                l.detach();
+            } catch (e) {
+                // Ignore errors during cleanup
+            }
+        });
+        traceListeners.length = 0;
+        traceFlush();
+        return { success: true, count };
+    },
+
+    // ========================================================================
+    // Stalker
+    // ========================================================================
+
+    // Follow a thread, sending its calls and returns (mode 'calls'), basic
+    // blocks ('blocks') or instructions ('insns'), optionally only those
+    // touching one module
+    stalk_follow: (threadId, mode = 'calls', moduleName = null) => {
+        try {
+            const events = STALK_EVENTS[mode];
+            if (!events) {
+                return { success: false, error: 'Unknown mode: ' + mode };
+            }
+            if (threadId === Process.getCurrentThreadId()) {
+                return { success: false, error: 'Cannot stalk the agent\'s own thread' };
+            }
+            if (stalkedThreads.has(threadId)) {
+                return { success: false, error: 'Thread ' + threadId + ' is already stalked' };
+            }
+            if (coverageThreads.includes(threadId)) {
+                return { success: false, error: 'Thread ' + threadId + ' is collecting coverage' };
+            }
+            if (!Process.enumerateThreads().some(t => t.id === threadId)) {
+                return { success: false, error: 'Thread not found: ' + threadId };
+            }
+            let range = null;
+            if (moduleName !== null) {
+                const module = Process.findModuleByName(moduleName);
+                if (!module) {
+                    return { success: false, error: 'Module not found: ' + moduleName };
+                }
+                range = { base: module.base, end: module.base.add(module.size) };
+            }
+            Stalker.follow(threadId, {
+                events,
+                onReceive: batch => stalkSend(threadId, range, batch)
+            });
+            stalkedThreads.set(threadId, { mode, module: moduleName });
+            return { success: true, threadId, mode, module: moduleName };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    // Stop following one thread, or every stalked thread when threadId is null
+    stalk_unfollow: (threadId = null) => {
+        try {
+            if (threadId !== null && !stalkedThreads.has(threadId)) {
+                return { success: false, error: 'Thread ' + threadId + ' is not stalked' };
+            }
+            const threads = threadId === null ? [...stalkedThreads.keys()] : [threadId];
+            // Deliver what is still queued before the threads are let go
+            Stalker.flush();
+            for (const id of threads) {
+                Stalker.unfollow(id);
+                stalkedThreads.delete(id);
+            }
+            Stalker.garbageCollect();
+            return { success: true, threads };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    stalk_list: () => {
+        return [...stalkedThreads].map(([threadId, s]) => ({
+            threadId, mode: s.mode, module: s.module
+        }));
+    },
+
+    // ========================================================================
+    // Coverage
+    // ========================================================================
+
+    // Follow every thread but this one and those already stalked, recording
+    // the blocks they compile (only those in moduleName, if given). Threads
+    // created later are not followed.
+    coverage_start: (moduleName = null) => {
+        try {
+            if (coverageThreads.length > 0) {
+                return { success: false, error: 'Coverage is already being collected' };
+            }
+            let range = null;
+            if (moduleName !== null) {
+                const module = Process.findModuleByName(moduleName);
+                if (!module) {
+                    return { success: false, error: 'Module not found: ' + moduleName };
+                }
+                range = { base: module.base, end: module.base.add(module.size) };
+            }
+            coverageBlocks.clear();
+            coverageRange = range;
+            const self = Process.getCurrentThreadId();
+            for (const thread of Process.enumerateThreads()) {
+                if (thread.id === self || stalkedThreads.has(thread.id)) continue;
+                try {
+                    Stalker.follow(thread.id, {
+                        events: { compile: true },
+                        onReceive: coverageReceive
+                    });
+                    coverageThreads.push(thread.id);
+                } catch (e) {
+                    // The thread may have exited meanwhile
+                }
+            }
+            return { success: true, threads: coverageThreads.length, module: moduleName };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    // Stop following; the blocks stay available to coverage_blocks
+    coverage_stop: () => {
+        try {
+            if (coverageThreads.length === 0) {
+                return { success: false, error: 'Coverage is not being collected' };
+            }
+            Stalker.flush();
+            const threads = coverageThreads.length;
+            for (const id of coverageThreads) {
+                try {
+                    Stalker.unfollow(id);
+                } catch (e) {
+                    // Ignore threads that exited
+                }
+            }
+            coverageThreads = [];
+            Stalker.garbageCollect();
+            return { success: true, threads, blocks: coverageBlocks.size };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
+    // [start, size] of each block collected by the last coverage_start
+    coverage_blocks: () => {
+        return [...coverageBlocks].map(([start, size]) => [start, size]);
+    },
+
+    // ========================================================================
+    // Native Calls
+    // ========================================================================
+
+    // Call the function at address. config.args are { type, value } with type
+    // pointer, float, double, string or utf16; strings are copied into the
+    // process for the duration of the call. retType and abi describe the
+    // NativeFunction, and config.ret (an ArgSpec) decodes a pointer-sized
+    // return value.
+    call_function: (address, config = {}) => {
+        try {
+            const target = ptr(address);
+            const range = Process.findRangeByAddress(target);
+            if (!range || !range.protection.includes('x')) {
+                return { success: false, error: 'Invalid or non-executable address' };
+            }
+
+            const retType = config.retType || 'pointer';
+            const args = config.args || [];
+            const values = args.map(arg => {
+                switch (arg.type) {
+                    case 'string': return Memory.allocUtf8String(arg.value);
+                    case 'utf16': return Memory.allocUtf16String(arg.value);
+                    case 'float':
+                    case 'double': return Number(arg.value);
+                    default: return ptr(arg.value);
+                }
+            });
+            const argTypes = args.map(arg =>
+                (arg.type === 'float' || arg.type === 'double') ? arg.type : 'pointer');
+            const fn = new NativeFunction(target, retType, argTypes, config.abi || 'default');
+            // values keeps the strings alive until the call returns
+            const result = fn(...values);
+
+            let retval = null;
+            if (retType === 'pointer') {
+                retval = config.ret ? decodeArg(result, config.ret) : describeValue(result);
+            } else if (retType !== 'void') {
+                retval = { value: String(result) };
+            }
+            return { success: true, retval };
+        } catch (e) {
+            return { success: false, error: e.message };
+        }
+    },
+
     // ========================================================================
     // Memory Scanning
     // ========================================================================
@@ -102,6 +102,38 @@ pub struct TargetArgs {
     pub target: Option<String>,
 }
 
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    #[clap(
+        short,
+        long,
+        value_name = "FILE",
+        help = "run commands from FILE and exit"
+    )]
+    pub script: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        value_name = "COMMANDS",
+        help = "run ';'-separated COMMANDS and exit"
+    )]
+    pub eval: Option<String>,
+}
+
+impl BatchArgs {
+    pub fn is_batch(&self) -> bool {
+        self.script.is_some() || self.eval.is_some()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
 #[derive(Args, Debug)]
 pub struct AttachArgs {
     #[clap(flatten)]
@@ -109,6 +141,18 @@ pub struct AttachArgs {
 
     #[clap(flatten)]
     pub target: TargetArgs,
+
+    #[clap(flatten)]
+    pub batch: BatchArgs,
+
+    #[clap(
+        short,
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        help = "print command results as TEXT or JSON lines"
+    )]
+    pub output: OutputFormat,
 }
 
 #[derive(Clone, Debug, ValueEnum)]
@@ -28,8 +28,8 @@ fn handle_completions(shell: clap_complete::Shell) {
 fn handle_attach(manager: &Manager, args: &cli::AttachArgs) {
     let device_opt = get_device(manager, &args.connection);
     if let Some(mut device) = device_opt {
-        attach(&mut device, &args.target);
-        exit(0);
+        let status = attach(&mut device, args);
+        exit(status);
     } else {
         println!("{}", VlitzError::DeviceNotFound);
         exit(1);
@@ -0,0 +1,251 @@
+// src/gum/bookmark.rs
+//
+// Lib export/import. Each entry is stored alongside the module it lies in
+// and its offset from that module's base, so a bookmark file written in one
+// run can be rebased onto the module layout of the next.
+
+use super::vzdata::{VzData, VzModule};
+use serde::{Deserialize, Serialize};
+
+/// Current bookmark file format version
+pub const BOOKMARK_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookmarkFile {
+    pub version: u32,
+    pub entries: Vec<Bookmark>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Module containing the entry's address; `None` for addresses outside
+    /// any module (heap, stack) and for entries without an address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    pub data: VzData,
+}
+
+/// Outcome of rebasing a bookmark file onto the current modules
+#[derive(Debug, Default)]
+pub struct ImportResult {
+    pub datas: Vec<VzData>,
+    /// Entries that had no module and were imported at their saved address
+    pub absolute: usize,
+    /// Modules referenced by the file that are not loaded now
+    pub missing: Vec<String>,
+}
+
+fn primary_address(data: &VzData) -> Option<u64> {
+    match data {
+        VzData::Pointer(p) => Some(p.address),
+        VzData::Module(m) => Some(m.address),
+        VzData::Range(r) => Some(r.address),
+        VzData::Function(f) => Some(f.address),
+        VzData::Variable(v) => Some(v.address),
+        VzData::Hook(h) => Some(h.address),
+        VzData::Instruction(i) => Some(i.address),
+        VzData::ScanResult(s) => Some(s.address),
+        VzData::Import(i) => i.address,
+        VzData::Symbol(s) => Some(s.address),
+        VzData::HookEvent(e) => Some(e.address),
+        VzData::JavaClass(_)
+        | VzData::JavaMethod(_)
+        | VzData::ObjCClass(_)
+        | VzData::ObjCMethod(_)
+        | VzData::Thread(_) => None,
+    }
+}
+
+/// Shift the address of `data` by `delta`. An import's slot lives in the
+/// importing module rather than the target's, so it is cleared instead.
+fn shift_addresses(data: &mut VzData, delta: u64) {
+    let shift = |a: &mut u64| *a = a.wrapping_add(delta);
+    match data {
+        VzData::Pointer(p) => shift(&mut p.address),
+        VzData::Module(m) => shift(&mut m.address),
+        VzData::Range(r) => shift(&mut r.address),
+        VzData::Function(f) => shift(&mut f.address),
+        VzData::Variable(v) => shift(&mut v.address),
+        VzData::Hook(h) => shift(&mut h.address),
+        VzData::Instruction(i) => shift(&mut i.address),
+        VzData::ScanResult(s) => shift(&mut s.address),
+        VzData::Import(i) => {
+            if let Some(a) = i.address.as_mut() {
+                shift(a);
+            }
+            i.slot = None;
+        }
+        VzData::Symbol(s) => shift(&mut s.address),
+        VzData::HookEvent(e) => shift(&mut e.address),
+        VzData::JavaClass(_)
+        | VzData::JavaMethod(_)
+        | VzData::ObjCClass(_)
+        | VzData::ObjCMethod(_)
+        | VzData::Thread(_) => {}
+    }
+}
+
+fn module_containing(modules: &[VzModule], address: u64) -> Option<&VzModule> {
+    modules
+        .iter()
+        .find(|m| address >= m.address && address - m.address < m.size as u64)
+}
+
+/// Build a bookmark file from `datas`, locating each address in `modules`
+pub fn export(datas: &[VzData], modules: &[VzModule]) -> BookmarkFile {
+    let entries = datas
+        .iter()
+        .map(|data| {
+            let location = primary_address(data).and_then(|address| {
+                module_containing(modules, address).map(|m| (m.name.clone(), address - m.address))
+            });
+            let (module, offset) = location.unzip();
+            Bookmark {
+                module,
+                offset,
+                data: data.clone(),
+            }
+        })
+        .collect();
+    BookmarkFile {
+        version: BOOKMARK_VERSION,
+        entries,
+    }
+}
+
+/// Rebase the entries of `file` onto the currently loaded `modules`.
+/// Entries whose module is not loaded are dropped and reported in `missing`.
+pub fn import(file: BookmarkFile, modules: &[VzModule]) -> Result<ImportResult, String> {
+    if file.version > BOOKMARK_VERSION {
+        return Err(format!(
+            "Unsupported bookmark version {} (expected at most {})",
+            file.version, BOOKMARK_VERSION
+        ));
+    }
+    let mut result = ImportResult::default();
+    for entry in file.entries {
+        let mut data = entry.data;
+        match (entry.module, entry.offset, primary_address(&data)) {
+            (Some(name), Some(offset), Some(saved)) => {
+                let Some(module) = modules.iter().find(|m| m.name == name) else {
+                    if !result.missing.contains(&name) {
+                        result.missing.push(name);
+                    }
+                    continue;
+                };
+                let target = module.address.wrapping_add(offset);
+                shift_addresses(&mut data, target.wrapping_sub(saved));
+                if let VzData::Module(m) = &mut data {
+                    m.size = module.size;
+                }
+            }
+            (None, _, Some(_)) => result.absolute += 1,
+            _ => {}
+        }
+        result.datas.push(data);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gum::vzdata::{new_base, VzDataType, VzImport, VzPointer, VzThread, VzValueType};
+
+    fn module(name: &str, address: u64, size: usize) -> VzModule {
+        VzModule {
+            base: new_base(VzDataType::Module),
+            name: name.to_string(),
+            address,
+            size,
+        }
+    }
+
+    fn pointer(address: u64) -> VzData {
+        VzData::Pointer(VzPointer {
+            base: new_base(VzDataType::Pointer),
+            address,
+            size: 4,
+            value_type: VzValueType::Int,
+        })
+    }
+
+    #[test]
+    fn test_export_records_module_offset() {
+        let modules = vec![module("libc.so.6", 0x7f00_0000, 0x1000)];
+        let file = export(&[pointer(0x7f00_0123), pointer(0x5000)], &modules);
+        assert_eq!(file.version, BOOKMARK_VERSION);
+        assert_eq!(file.entries[0].module.as_deref(), Some("libc.so.6"));
+        assert_eq!(file.entries[0].offset, Some(0x123));
+        assert_eq!(file.entries[1].module, None);
+        assert_eq!(file.entries[1].offset, None);
+    }
+
+    #[test]
+    fn test_import_rebases_addresses() {
+        let old = vec![module("libc.so.6", 0x7f00_0000, 0x1000)];
+        let file = export(&[pointer(0x7f00_0123)], &old);
+        let new = vec![module("libc.so.6", 0x7e55_0000, 0x1000)];
+        let result = import(file, &new).unwrap();
+        assert_eq!(result.datas, vec![pointer(0x7e55_0123)]);
+        assert!(result.missing.is_empty());
+    }
+
+    #[test]
+    fn test_import_clears_import_slot() {
+        let import_data = VzData::Import(VzImport {
+            base: new_base(VzDataType::Import),
+            name: "open".to_string(),
+            address: Some(0x7f00_0010),
+            import_type: "function".to_string(),
+            module: "libc.so.6".to_string(),
+            slot: Some(0x1800),
+        });
+        let file = export(&[import_data], &[module("libc.so.6", 0x7f00_0000, 0x1000)]);
+        let result = import(file, &[module("libc.so.6", 0x7e00_0000, 0x1000)]).unwrap();
+        match &result.datas[0] {
+            VzData::Import(i) => {
+                assert_eq!(i.address, Some(0x7e00_0010));
+                assert_eq!(i.slot, None);
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_import_reports_missing_modules() {
+        let old = vec![module("libfoo.so", 0x1000, 0x1000)];
+        let thread = VzData::Thread(VzThread {
+            base: new_base(VzDataType::Thread),
+            id: 7,
+        });
+        let file = export(&[pointer(0x1004), pointer(0x9000), thread.clone()], &old);
+        let result = import(file, &[]).unwrap();
+        assert_eq!(result.datas, vec![pointer(0x9000), thread]);
+        assert_eq!(result.absolute, 1);
+        assert_eq!(result.missing, vec!["libfoo.so".to_string()]);
+    }
+
+    #[test]
+    fn test_file_roundtrip_json() {
+        let modules = vec![module("libc.so.6", 0x7f00_0000, 0x1000)];
+        let file = export(
+            &[pointer(0x7f00_0010), VzData::Module(modules[0].clone())],
+            &modules,
+        );
+        let json = serde_json::to_string(&file).unwrap();
+        let parsed: BookmarkFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, file);
+    }
+
+    #[test]
+    fn test_import_rejects_newer_version() {
+        let file = BookmarkFile {
+            version: BOOKMARK_VERSION + 1,
+            entries: vec![],
+        };
+        assert!(import(file, &[]).is_err());
+    }
+}
@@ -1,28 +1,48 @@
 // src/gum/commander.rs
+use crate::core::cli::OutputFormat;
 use crate::gum::{
-    filter::parse_filter_string,
+    filter::{parse_filter_string, parse_hook_condition, FilterSegment},
     list::{list_functions, list_ranges, list_variables},
     memory::{
-        get_address_from_data, parse_value_type, read_memory_by_type, view_memory,
-        write_memory_by_type,
+        get_address_from_data, parse_value_type, read_memory_by_type, readpointer, typed_writer,
+        view_memory, write_memory_by_type, written_size,
     },
 };
 use crate::util::logger;
 use crossterm::{style::Stylize, terminal, ExecutableCommand};
 
 use super::{
+    bookmark,
+    completer::{CommandShape, CompletionData, SubCommandShape},
+    coverage::{self, Coverage, DiffSide},
+    event::{EventValue, Frame, HookEvent},
+    eventlog,
+    expr::{self, Resolver},
+    handler,
+    hookstats::{self, HookStats},
     list::list_modules,
     navigator::Navigator,
+    session::{parse_command, split_commands, Word},
+    signature::{self, ArgSpec, Signature, SignatureDb},
     store::Store,
+    trace,
+    vars::Variables,
     vzdata::{
-        new_base, VzBase, VzData, VzDataType, VzHook, VzInstruction, VzScanResult, VzThread,
-        VzValueType,
+        new_base, string_to_u64, VzBase, VzData, VzDataType, VzFunction, VzHook, VzInstruction,
+        VzPointer, VzScanResult, VzThread, VzValueType,
     },
+    watch,
 };
+use clap::ValueEnum;
 use frida::Script;
 use regex::Regex;
-use serde_json::json;
-use std::{collections::HashMap, fmt, io::stdout, vec};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    io::stdout,
+    vec,
+};
 
 #[derive(Debug)]
 pub(crate) struct CommandArg {
@@ -120,6 +140,93 @@ impl Command {
     }
 }
 
+/// Result of running a sequence of commands from a script or `--eval`
+pub(crate) enum BatchOutcome {
+    Completed,
+    /// A command asked to leave the session (e.g. `exit`)
+    Exited,
+    /// Index of the first command that reported an error
+    Failed(usize),
+}
+
+/// Guards against scripts that source themselves
+const MAX_SOURCE_DEPTH: usize = 8;
+
+/// Events `hook capture` adds to Field when no limit is given
+const DEFAULT_CAPTURE_MAX: usize = 1000;
+
+/// Commands that print their result as JSON in JSON mode; the others refuse
+/// to run there. Subcommands are named with their command, e.g. `hook list`.
+const JSON_COMMANDS: &[&str] = &[
+    // Navigation
+    "select",
+    "deselect",
+    "add",
+    "sub",
+    "goto",
+    // Stores
+    "field list",
+    "field next",
+    "field prev",
+    "field sort",
+    "field move",
+    "field remove",
+    "field clear",
+    "field filter",
+    "lib list",
+    "lib next",
+    "lib prev",
+    "lib sort",
+    "lib save",
+    "lib move",
+    "lib remove",
+    "lib clear",
+    "lib filter",
+    "lib label",
+    "lib note",
+    "lib tag",
+    "lib export",
+    "lib import",
+    // Memory
+    "list modules",
+    "list ranges",
+    "list functions",
+    "list variables",
+    "read",
+    "watch list",
+    "freeze list",
+    // Hooking
+    "hook",
+    "hook list",
+    "hook stats",
+    "hook sigs",
+    "events",
+    "events list",
+    "events filter",
+    "args",
+    // Scanning
+    "scan",
+    "scan results",
+    "scan list",
+    "thread",
+    "thread list",
+    "thread regs",
+    // Tracing
+    "stalk list",
+    "coverage diff",
+    // Native
+    "call",
+    "alloc",
+    "alloc string",
+    "alloc utf16",
+    "alloc list",
+    // Session
+    "exit",
+    "source",
+    "output",
+    "set",
+];
+
 pub struct Commander<'a, 'b> {
     script: &'a mut Script<'b>,
     pub env: String,
@@ -127,6 +234,21 @@ pub struct Commander<'a, 'b> {
     lib: Store,
     pub navigator: Navigator,
     commands: Vec<Command>,
+    module_names: Vec<String>,
+    source_depth: usize,
+    output: OutputFormat,
+    vars: Variables,
+    signatures: SignatureDb,
+    /// Hook events loaded by `events load`
+    events: Vec<HookEvent>,
+    /// Set by `fail` while a command runs, so batch mode can stop there
+    failed: bool,
+    /// Which arguments of the running command were quoted on the command line
+    quoted: Vec<bool>,
+    /// Hook IDs offered by tab completion; `None` after a `hook` command
+    completion_hooks: Option<Vec<String>>,
+    /// Threads stopped at break hooks when the prompt last looked
+    prompt_stopped: Vec<(u64, String)>,
 }
 
 impl<'a, 'b> Commander<'a, 'b> {
@@ -139,6 +261,12 @@ impl<'a, 'b> Commander<'a, 'b> {
         let env_arr = env_value.as_array().cloned().unwrap_or_default();
         let os = env_arr.get(0).and_then(|v| v.as_str()).unwrap_or("");
         let arch = env_arr.get(1).and_then(|v| v.as_str()).unwrap_or("");
+        let mut signatures = SignatureDb::new();
+        if let Some(path) = SignatureDb::user_file().filter(|p| p.exists()) {
+            if let Err(e) = signatures.load_file(&path) {
+                logger::error(&format!("Failed to load signatures: {}", e));
+            }
+        }
         Commander {
             script,
             env: format!("{} {}", os, arch),
@@ -146,15 +274,130 @@ impl<'a, 'b> Commander<'a, 'b> {
             lib: Store::new("Lib".to_string()),
             navigator: Navigator::new(),
             commands: crate::gum::commands::build_all(),
+            module_names: Vec::new(),
+            source_depth: 0,
+            output: OutputFormat::Text,
+            vars: Variables::new(),
+            signatures,
+            events: Vec::new(),
+            failed: false,
+            quoted: Vec::new(),
+            completion_hooks: None,
+            prompt_stopped: Vec::new(),
+        }
+    }
+
+    /// Switch result formatting; JSON mode also turns off ANSI styling
+    pub fn set_output(&mut self, output: OutputFormat) {
+        self.output = output;
+        let colors = output == OutputFormat::Text && std::env::var_os("NO_COLOR").is_none();
+        crossterm::style::force_color_output(colors);
+    }
+
+    /// REPL prompt for the navigator, labelled when its address has a Lib label
+    /// and prefixed with the thread stopped at a break hook, if any
+    pub(crate) fn prompt(&mut self) -> String {
+        let label = self
+            .navigator
+            .get_data()
+            .and_then(get_address_from_data)
+            .and_then(|addr| self.labels().remove(&addr));
+        let prompt = self.navigator.prompt(label.as_deref());
+        // Threads only stop on a break:hit message; once stopped, keep looking
+        // until they are all resumed
+        if handler::take_break_hit() || !self.prompt_stopped.is_empty() {
+            self.prompt_stopped = self.stopped_threads();
+        }
+        match self.prompt_stopped.first() {
+            Some((tid, _)) => format!("{} {}", format!("(stopped {})", tid).red(), prompt),
+            None => prompt,
+        }
+    }
+
+    /// Collect command shapes and live session state for tab completion
+    pub(crate) fn completion_data(&mut self) -> CompletionData {
+        let commands = self
+            .commands
+            .iter()
+            .map(|c| CommandShape {
+                names: std::iter::once(c.command.clone())
+                    .chain(c.aliases.iter().cloned())
+                    .collect(),
+                args: c.args.iter().map(|a| a.name.clone()).collect(),
+                subcommands: c
+                    .subcommands
+                    .iter()
+                    .map(|s| SubCommandShape {
+                        names: std::iter::once(s.name.clone())
+                            .chain(s.aliases.iter().cloned())
+                            .collect(),
+                        args: s.args.iter().map(|a| a.name.clone()).collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        if self.module_names.is_empty() {
+            if let Ok(modules) = list_modules(self.script, None) {
+                self.module_names = modules.into_iter().map(|m| m.name).collect();
+            }
+        }
+
+        if self.completion_hooks.is_none() {
+            self.completion_hooks = Some(self.hook_ids());
+        }
+
+        CompletionData {
+            commands,
+            field_len: self.field.data.len(),
+            lib_len: self.lib.data.len(),
+            hook_ids: self.completion_hooks.clone().unwrap_or_default(),
+            modules: self.module_names.clone(),
+            variables: self.vars.names(),
+        }
+    }
+
+    /// Execute a command line. `$variables` in unquoted words are expanded
+    /// first, and a trailing `--json` switches to JSON output for this command
+    /// only.
+    pub(crate) fn execute_command(&mut self, command: &str, words: &[Word]) -> bool {
+        self.collect_captured();
+        // `set` names the variable it assigns, so its first argument stays as typed
+        let skip = usize::from(command == "set");
+        let expanded: Vec<String> = words
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                if i < skip || a.quoted {
+                    a.text.clone()
+                } else {
+                    self.vars.expand(&a.text)
+                }
+            })
+            .collect();
+        let mut args: Vec<&str> = expanded.iter().map(|s| s.as_str()).collect();
+        self.quoted = words.iter().map(|w| w.quoted).collect();
+        if args.last() != Some(&"--json") {
+            return self.dispatch(command, &args);
         }
+        args.pop();
+        self.quoted.pop();
+        let previous = self.output;
+        self.set_output(OutputFormat::Json);
+        let keep_running = self.dispatch(command, &args);
+        self.set_output(previous);
+        keep_running
     }
 
-    pub fn execute_command(&mut self, command: &str, args: &[&str]) -> bool {
+    fn dispatch(&mut self, command: &str, args: &[&str]) -> bool {
         if let Some(cmd) = self
             .commands
             .iter()
             .find(|c| c.command == command || c.aliases.contains(&command.to_string()))
         {
+            if cmd.command == "hook" {
+                self.completion_hooks = None;
+            }
             if !cmd.subcommands.is_empty() {
                 if let Some((subcommand, sub_args)) = args.split_first() {
                     if let Some(sub_cmd) = cmd.subcommands.iter().find(|s| {
@@ -163,36 +406,115 @@ impl<'a, 'b> Commander<'a, 'b> {
                         // Check required arguments for the subcommand
                         let required_args = sub_cmd.args.iter().filter(|a| a.required).count();
                         if sub_args.len() < required_args {
-                            println!(
-                                "{} Expected at least {} arguments, got {}",
-                                "Error:".red(),
+                            self.fail(&format!(
+                                "Expected at least {} arguments, got {}",
                                 required_args,
                                 sub_args.len()
-                            );
+                            ));
                             return true;
                         }
-                        return (sub_cmd.execute)(self, sub_args);
+                        let name = format!("{} {}", cmd.command, sub_cmd.name);
+                        let execute = sub_cmd.execute;
+                        if !self.quoted.is_empty() {
+                            self.quoted.remove(0);
+                        }
+                        return self.run_handler(&name, execute, sub_args);
                     }
                 }
                 // If we reached here, no valid subcommand was found
-                if let Some(default_exec) = &cmd.default_execute {
-                    return default_exec(self, args);
+                if let Some(default_exec) = cmd.default_execute {
+                    let name = cmd.command.clone();
+                    return self.run_handler(&name, default_exec, args);
                 }
-                println!(
-                    "{} {}",
-                    "No subcommand specified.".red(),
+                self.fail(&format!(
+                    "No subcommand specified. {}",
                     format!("Use 'help {}' for more information.", command).dark_grey()
-                );
+                ));
                 return true;
-            } else if let Some(exec) = &cmd.default_execute {
-                return exec(self, args);
+            } else if let Some(exec) = cmd.default_execute {
+                let name = cmd.command.clone();
+                return self.run_handler(&name, exec, args);
             }
         } else {
-            println!("{} {}", "Unknown command:".red(), command);
+            self.fail(&format!("Unknown command: {}", command));
         }
         true
     }
 
+    /// Run a handler, refusing ones without a JSON form while JSON output is on
+    fn run_handler(&mut self, name: &str, handler: CommandHandler, args: &[&str]) -> bool {
+        if self.json_output() && !JSON_COMMANDS.contains(&name) {
+            self.fail(&format!("'{}' has no JSON output", name));
+            return true;
+        }
+        handler(self, args)
+    }
+
+    /// Run commands in order, echoing each one after the prompt. Stops at
+    /// the first command that reports an error or ends the session.
+    pub(crate) fn run_commands(&mut self, commands: &[String]) -> BatchOutcome {
+        for (index, line) in commands.iter().enumerate() {
+            let mut args = parse_command(line);
+            if args.is_empty() {
+                continue;
+            }
+            if !self.json_output() {
+                println!("{}> {}", self.navigator, line);
+            }
+            let command = args.remove(0);
+            self.failed = false;
+            let keep_running = self.execute_command(&command.text, &args);
+            if self.failed {
+                return BatchOutcome::Failed(index);
+            }
+            if !keep_running {
+                return BatchOutcome::Exited;
+            }
+        }
+        BatchOutcome::Completed
+    }
+
+    pub(crate) fn source(&mut self, args: &[&str]) -> bool {
+        let path = match args.first() {
+            Some(path) => *path,
+            None => {
+                self.fail("Usage: source <file>");
+                return true;
+            }
+        };
+        if self.source_depth >= MAX_SOURCE_DEPTH {
+            self.fail(&format!(
+                "Nested source depth exceeds {}: {}",
+                MAX_SOURCE_DEPTH, path
+            ));
+            return true;
+        }
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.fail(&format!("Failed to read script {}: {}", path, e));
+                return true;
+            }
+        };
+        let commands = split_commands(&text);
+        self.source_depth += 1;
+        let outcome = self.run_commands(&commands);
+        self.source_depth -= 1;
+        match outcome {
+            BatchOutcome::Completed => true,
+            BatchOutcome::Exited => false,
+            BatchOutcome::Failed(index) => {
+                self.fail(&format!(
+                    "{} aborted at command {}: {}",
+                    path,
+                    index + 1,
+                    commands[index]
+                ));
+                true
+            }
+        }
+    }
+
     pub(crate) fn help(&mut self, args: &[&str]) -> bool {
         if !args.is_empty() {
             let command = self
@@ -380,8 +702,84 @@ impl<'a, 'b> Commander<'a, 'b> {
         }
     }
 
+    pub(crate) fn output_format(&mut self, args: &[&str]) -> bool {
+        if let Some(name) = args.first() {
+            match OutputFormat::from_str(name, true) {
+                Ok(output) => self.set_output(output),
+                Err(_) => {
+                    self.fail(&format!("Unknown output format: {} (text, json)", name));
+                    return true;
+                }
+            }
+        }
+        let name = match self.output {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+        };
+        if self.json_output() {
+            self.emit_json(json!({ "output": name }));
+        } else {
+            println!("{} {}", "Output:".green(), name);
+        }
+        true
+    }
+
+    pub(crate) fn set_variable(&mut self, args: &[&str]) -> bool {
+        if args.is_empty() {
+            for (name, value) in self.vars.entries() {
+                if self.json_output() {
+                    self.emit_json(json!({ "type": "Variable", "name": name, "value": value }));
+                } else {
+                    println!("  {} = {}", name.cyan(), value.yellow());
+                }
+            }
+            return true;
+        }
+
+        // Accept `set $a = expr`, `set $a expr` and `set $a=expr`
+        let (name, first) = match args[0].split_once('=') {
+            Some((name, rest)) => (name, Some(rest).filter(|r| !r.is_empty())),
+            None => (args[0], None),
+        };
+        let rest = args[1..]
+            .iter()
+            .copied()
+            .skip_while(|a| first.is_none() && *a == "=");
+        let expr = first.into_iter().chain(rest).collect::<Vec<_>>().join(" ");
+        if expr.is_empty() {
+            self.fail("Usage: set $name = <expr>");
+            return true;
+        }
+        let expr = self.vars.expand(&expr);
+
+        let value = match self.resolve_target_address(&expr) {
+            Ok(addr) => {
+                self.vars.push_address(addr);
+                format!("{:#x}", addr)
+            }
+            Err(e) => {
+                self.fail(&format!("Invalid expression: {}", e));
+                return true;
+            }
+        };
+        match self.vars.set(name, value.clone()) {
+            Ok(()) => {
+                let name = format!("${}", name.trim_start_matches('$'));
+                if self.json_output() {
+                    self.emit_json(json!({ "type": "Variable", "name": name, "value": value }));
+                } else {
+                    println!("{} {} = {}", "[SET]".green(), name.cyan(), value.yellow());
+                }
+            }
+            Err(e) => self.fail(&e),
+        }
+        true
+    }
+
     pub(crate) fn exit(&mut self, _args: &[&str]) -> bool {
-        println!("{}", "Exiting...".yellow());
+        if !self.json_output() {
+            println!("{}", "Exiting...".yellow());
+        }
         false
     }
 
@@ -455,23 +853,16 @@ impl<'a, 'b> Commander<'a, 'b> {
 
     pub(crate) fn select(&mut self, args: &[&str]) -> bool {
         let selector = args.get(0).unwrap_or(&"");
-        let result = self.selector(selector).map_err(|e| {
-            println!("Failed to select data: {}", e);
-            e
-        });
+        let result = match self.selector(selector) {
+            Ok(data) if data.len() == 1 => Ok(data[0].clone()),
+            Ok(_) => Err(format!("Multiple data found for selector: {}", selector)),
+            Err(e) => Err(format!("Failed to select data: {}", e)),
+        };
         match result {
-            Ok(data) => {
-                if data.len() == 1 {
-                    let item_to_select = data[0].clone();
-                    self.navigator.select(&item_to_select);
-                    true
-                } else {
-                    println!("Multiple data found for selector: {}", selector);
-                    true
-                }
-            }
-            Err(_) => true,
+            Ok(data) => self.navigator.select(&data),
+            Err(e) => self.fail(&e),
         }
+        true
     }
 
     pub(crate) fn deselect(&mut self, _args: &[&str]) -> bool {
@@ -490,8 +881,8 @@ impl<'a, 'b> Commander<'a, 'b> {
     pub(crate) fn add(&mut self, args: &[&str]) -> bool {
         match args.get(0).map(|s| Self::parse_number(s)) {
             Some(Ok(offset)) => self.navigator.add(offset),
-            Some(Err(e)) => logger::error(&format!("Invalid offset: {}", e)),
-            None => logger::error("Offset argument required"),
+            Some(Err(e)) => self.fail(&format!("Invalid offset: {}", e)),
+            None => self.fail("Offset argument required"),
         }
         true
     }
@@ -499,17 +890,20 @@ impl<'a, 'b> Commander<'a, 'b> {
     pub(crate) fn sub(&mut self, args: &[&str]) -> bool {
         match args.get(0).map(|s| Self::parse_number(s)) {
             Some(Ok(offset)) => self.navigator.sub(offset),
-            Some(Err(e)) => logger::error(&format!("Invalid offset: {}", e)),
-            None => logger::error("Offset argument required"),
+            Some(Err(e)) => self.fail(&format!("Invalid offset: {}", e)),
+            None => self.fail("Offset argument required"),
         }
         true
     }
 
     pub(crate) fn goto(&mut self, args: &[&str]) -> bool {
-        match args.get(0).map(|s| Self::parse_number(s)) {
-            Some(Ok(addr)) => self.navigator.goto(addr),
-            Some(Err(e)) => logger::error(&format!("Invalid address: {}", e)),
-            None => logger::error("Address argument required"),
+        match args.get(0).map(|s| self.resolve_target_address(s)) {
+            Some(Ok(addr)) => {
+                self.vars.push_address(addr);
+                self.navigator.goto(addr);
+            }
+            Some(Err(e)) => self.fail(&format!("Invalid address: {}", e)),
+            None => self.fail("Address argument required"),
         }
         true
     }
@@ -517,10 +911,10 @@ impl<'a, 'b> Commander<'a, 'b> {
     pub(crate) fn field_list(&mut self, args: &[&str]) -> bool {
         match args.get(0) {
             Some(v) => match Self::parse_usize(v) {
-                Ok(p) => println!("{}", self.field.to_string(Some(p.saturating_sub(1)))),
-                Err(e) => logger::error(&e),
+                Ok(p) => self.show_store(&self.field, Some(p.saturating_sub(1))),
+                Err(e) => self.fail(&e),
             },
-            None => println!("{}", self.field.to_string(None)),
+            None => self.show_store(&self.field, None),
         }
         true
     }
@@ -531,12 +925,12 @@ impl<'a, 'b> Commander<'a, 'b> {
             match args.get(0) {
                 Some(v) => match Self::parse_usize(v) {
                     Ok(p) => self.field.next_page(p.max(1)),
-                    Err(e) => logger::error(&e),
+                    Err(e) => self.fail(&e),
                 },
                 None => self.field.next_page(1),
             }
         }
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
@@ -546,12 +940,12 @@ impl<'a, 'b> Commander<'a, 'b> {
             match args.get(0) {
                 Some(v) => match Self::parse_usize(v) {
                     Ok(p) => self.field.prev_page(p.max(1)),
-                    Err(e) => logger::error(&e),
+                    Err(e) => self.fail(&e),
                 },
                 None => self.field.prev_page(1),
             }
         }
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
@@ -559,7 +953,7 @@ impl<'a, 'b> Commander<'a, 'b> {
         if let Some(sort_by) = args.get(0) {
             self.field.sort(Some(sort_by));
         }
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
@@ -575,12 +969,12 @@ impl<'a, 'b> Commander<'a, 'b> {
         match (from_res, to_res) {
             (Ok(from), Ok(to)) => {
                 if let Err(e) = self.field.move_data(from, to) {
-                    logger::error(&format!("Field move error: {}", e));
+                    self.fail(&format!("Field move error: {}", e));
                 }
             }
-            (Err(e), _) | (_, Err(e)) => logger::error(&format!("Field move error: {}", e)),
+            (Err(e), _) | (_, Err(e)) => self.fail(&format!("Field move error: {}", e)),
         }
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
@@ -597,39 +991,39 @@ impl<'a, 'b> Commander<'a, 'b> {
         match (index_res, count_res) {
             (Ok(idx), Ok(count)) => {
                 if let Err(e) = self.field.remove_data(idx, count) {
-                    logger::error(&format!("Field remove error: {}", e));
+                    self.fail(&format!("Field remove error: {}", e));
                 }
             }
-            (Err(e), _) | (_, Err(e)) => logger::error(&format!("Field remove error: {}", e)),
+            (Err(e), _) | (_, Err(e)) => self.fail(&format!("Field remove error: {}", e)),
         }
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
     pub(crate) fn field_clear(&mut self, _args: &[&str]) -> bool {
         self.field.clear_data();
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
     pub(crate) fn field_filter(&mut self, args: &[&str]) -> bool {
         let filter_arg = args.get(0).map_or("", |v| v);
         let filter = parse_filter_string(filter_arg).unwrap_or_else(|_| {
-            logger::error(&format!("Failed to parse filter string: {}", filter_arg));
+            self.fail(&format!("Failed to parse filter string: {}", filter_arg));
             Vec::new()
         });
         self.field.filter(filter);
-        println!("{}", self.field.to_string(None));
+        self.show_store(&self.field, None);
         true
     }
 
     pub(crate) fn lib_list(&mut self, args: &[&str]) -> bool {
         match args.get(0) {
             Some(v) => match Self::parse_usize(v) {
-                Ok(p) => println!("{}", self.lib.to_string(Some(p.saturating_sub(1)))),
-                Err(e) => logger::error(&e),
+                Ok(p) => self.show_store(&self.lib, Some(p.saturating_sub(1))),
+                Err(e) => self.fail(&e),
             },
-            None => println!("{}", self.lib.to_string(None)),
+            None => self.show_store(&self.lib, None),
         }
         true
     }
@@ -640,12 +1034,12 @@ impl<'a, 'b> Commander<'a, 'b> {
             match args.get(0) {
                 Some(v) => match Self::parse_usize(v) {
                     Ok(p) => self.lib.next_page(p.max(1)),
-                    Err(e) => logger::error(&e),
+                    Err(e) => self.fail(&e),
                 },
                 None => self.lib.next_page(1),
             }
         }
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
@@ -655,12 +1049,12 @@ impl<'a, 'b> Commander<'a, 'b> {
             match args.get(0) {
                 Some(v) => match Self::parse_usize(v) {
                     Ok(p) => self.lib.prev_page(p.max(1)),
-                    Err(e) => logger::error(&e),
+                    Err(e) => self.fail(&e),
                 },
                 None => self.lib.prev_page(1),
             }
         }
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
@@ -668,7 +1062,7 @@ impl<'a, 'b> Commander<'a, 'b> {
         if let Some(sort_by) = args.get(0) {
             self.lib.sort(Some(sort_by));
         }
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
@@ -734,16 +1128,19 @@ impl<'a, 'b> Commander<'a, 'b> {
                                 VzData::Symbol(s) => {
                                     s.base.is_saved = true;
                                 }
+                                VzData::HookEvent(e) => {
+                                    e.base.is_saved = true;
+                                }
                             }
                             d
                         })
                         .collect(),
                 );
             }
-            Ok(_) => logger::error("No data selected"),
-            Err(e) => logger::error(&format!("Selection error: {}", e)),
+            Ok(_) => self.fail("No data selected"),
+            Err(e) => self.fail(&format!("Selection error: {}", e)),
         }
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
@@ -759,12 +1156,12 @@ impl<'a, 'b> Commander<'a, 'b> {
         match (from_res, to_res) {
             (Ok(from), Ok(to)) => {
                 if let Err(e) = self.lib.move_data(from, to) {
-                    logger::error(&format!("Lib move error: {}", e));
+                    self.fail(&format!("Lib move error: {}", e));
                 }
             }
-            (Err(e), _) | (_, Err(e)) => logger::error(&format!("Lib move error: {}", e)),
+            (Err(e), _) | (_, Err(e)) => self.fail(&format!("Lib move error: {}", e)),
         }
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
@@ -781,29 +1178,167 @@ impl<'a, 'b> Commander<'a, 'b> {
         match (index_res, count_res) {
             (Ok(idx), Ok(count)) => {
                 if let Err(e) = self.lib.remove_data(idx, count) {
-                    logger::error(&format!("Lib remove error: {}", e));
+                    self.fail(&format!("Lib remove error: {}", e));
                 }
             }
-            (Err(e), _) | (_, Err(e)) => logger::error(&format!("Lib remove error: {}", e)),
+            (Err(e), _) | (_, Err(e)) => self.fail(&format!("Lib remove error: {}", e)),
         }
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
     pub(crate) fn lib_clear(&mut self, _args: &[&str]) -> bool {
         self.lib.clear_data();
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
         true
     }
 
     pub(crate) fn lib_filter(&mut self, args: &[&str]) -> bool {
         let filter_arg = args.get(0).map_or("", |v| v);
         let filter = parse_filter_string(filter_arg).unwrap_or_else(|_| {
-            logger::error(&format!("Failed to parse filter string: {}", filter_arg));
+            self.fail(&format!("Failed to parse filter string: {}", filter_arg));
             Vec::new()
         });
         self.lib.filter(filter);
-        println!("{}", self.lib.to_string(None));
+        self.show_store(&self.lib, None);
+        true
+    }
+
+    /// Mutable Lib entry addressed by an index argument
+    fn lib_entry_mut(&mut self, index: &str) -> Result<&mut VzData, String> {
+        let idx = index
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid index: {}", index))?;
+        self.lib
+            .data
+            .get_mut(idx)
+            .ok_or_else(|| "Index out of bounds".to_string())
+    }
+
+    pub(crate) fn lib_label(&mut self, args: &[&str]) -> bool {
+        let label = match args.get(1) {
+            Some(label) => {
+                let valid = label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && label
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+                if !valid {
+                    self.fail(&format!("Invalid label: {}", label));
+                    return true;
+                }
+                Some(label.to_string())
+            }
+            None => None,
+        };
+        match self.lib_entry_mut(args[0]) {
+            Ok(data) => data.base_mut().label = label,
+            Err(e) => self.fail(&format!("Lib label error: {}", e)),
+        }
+        self.show_store(&self.lib, None);
+        true
+    }
+
+    pub(crate) fn lib_note(&mut self, args: &[&str]) -> bool {
+        let text = args[1..].join(" ");
+        match self.lib_entry_mut(args[0]) {
+            Ok(data) => data.base_mut().comment = Some(text).filter(|t| !t.is_empty()),
+            Err(e) => self.fail(&format!("Lib note error: {}", e)),
+        }
+        self.show_store(&self.lib, None);
+        true
+    }
+
+    pub(crate) fn lib_tag(&mut self, args: &[&str]) -> bool {
+        match self.lib_entry_mut(args[0]) {
+            Ok(data) => {
+                let tags = &mut data.base_mut().tags;
+                for arg in &args[1..] {
+                    match arg.strip_prefix('-') {
+                        Some(tag) => tags.retain(|t| t != tag),
+                        None if !tags.iter().any(|t| t == arg) => tags.push(arg.to_string()),
+                        None => {}
+                    }
+                }
+            }
+            Err(e) => self.fail(&format!("Lib tag error: {}", e)),
+        }
+        self.show_store(&self.lib, None);
+        true
+    }
+
+    pub(crate) fn lib_export(&mut self, args: &[&str]) -> bool {
+        let path = args[0];
+        let modules = match list_modules(self.script, None) {
+            Ok(modules) => modules,
+            Err(e) => {
+                self.fail(&format!("Failed to list modules: {}", e));
+                return true;
+            }
+        };
+        let file = bookmark::export(&self.lib.data, &modules);
+        let written = serde_json::to_string_pretty(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|s| std::fs::write(path, s + "\n").map_err(|e| e.to_string()));
+        match written {
+            Ok(()) if self.json_output() => self.emit_json(json!({
+                "type": "LibExport",
+                "file": path,
+                "count": file.entries.len(),
+            })),
+            Ok(()) => println!(
+                "{} {} entries to {}",
+                "[EXPORT]".green(),
+                file.entries.len().to_string().yellow(),
+                path
+            ),
+            Err(e) => self.fail(&format!("Failed to write {}: {}", path, e)),
+        }
+        true
+    }
+
+    pub(crate) fn lib_import(&mut self, args: &[&str]) -> bool {
+        let path = args[0];
+        let file = match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| {
+                serde_json::from_str::<bookmark::BookmarkFile>(&s).map_err(|e| e.to_string())
+            }) {
+            Ok(file) => file,
+            Err(e) => {
+                self.fail(&format!("Failed to read {}: {}", path, e));
+                return true;
+            }
+        };
+        let modules = match list_modules(self.script, None) {
+            Ok(modules) => modules,
+            Err(e) => {
+                self.fail(&format!("Failed to list modules: {}", e));
+                return true;
+            }
+        };
+        let result = match bookmark::import(file, &modules) {
+            Ok(result) => result,
+            Err(e) => {
+                self.fail(&format!("Failed to import {}: {}", path, e));
+                return true;
+            }
+        };
+        if !result.missing.is_empty() && !self.json_output() {
+            println!(
+                "{} Skipped entries in modules not loaded: {}",
+                "[IMPORT]".yellow(),
+                result.missing.join(", ").dark_grey()
+            );
+        }
+        if result.absolute > 0 && !self.json_output() {
+            println!(
+                "{} {} entries outside any module kept at their saved address",
+                "[IMPORT]".yellow(),
+                result.absolute
+            );
+        }
+        self.lib.add_datas(result.datas);
+        self.show_store(&self.lib, None);
         true
     }
 
@@ -814,9 +1349,18 @@ impl<'a, 'b> Commander<'a, 'b> {
             .into_iter()
             .map(|m| VzData::Module(m))
             .collect::<Vec<_>>();
+        if filter.is_none() {
+            self.module_names = modules
+                .iter()
+                .filter_map(|m| match m {
+                    VzData::Module(m) => Some(m.name.clone()),
+                    _ => None,
+                })
+                .collect();
+        }
         self.field.clear_data();
         self.field.add_datas(modules);
-        println!("{}", self.field.to_string(None));
+        self.show_results(&self.field);
         true
     }
 
@@ -830,7 +1374,7 @@ impl<'a, 'b> Commander<'a, 'b> {
             .collect::<Vec<_>>();
         self.field.clear_data();
         self.field.add_datas(ranges);
-        println!("{}", self.field.to_string(None));
+        self.show_results(&self.field);
         true
     }
 
@@ -841,13 +1385,13 @@ impl<'a, 'b> Commander<'a, 'b> {
         let module = match res {
             Ok(data) => {
                 if data.is_empty() {
-                    logger::error("No data selected");
+                    self.fail("No data selected");
                     return true;
                 } else if let Some(VzData::Module(m)) = data.first() {
                     filter = _args.get(1).map(|s| s.to_string());
                     m.clone()
                 } else {
-                    logger::error("Selected data is not a module");
+                    self.fail("Selected data is not a module");
                     return true;
                 }
             }
@@ -857,7 +1401,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                         filter = _args.get(0).map(|s| s.to_string());
                         m.clone()
                     } else {
-                        logger::error(&format!(
+                        self.fail(&format!(
                             "Selector error: {}. Navigator data is not a VzModule.",
                             e
                         ));
@@ -865,7 +1409,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
                 None => {
-                    logger::error(&format!("Selector error: {}. Navigator has no data.", e));
+                    self.fail(&format!("Selector error: {}. Navigator has no data.", e));
                     return true;
                 }
             },
@@ -877,7 +1421,7 @@ impl<'a, 'b> Commander<'a, 'b> {
             .collect::<Vec<_>>();
         self.field.clear_data();
         self.field.add_datas(functions);
-        println!("{}", self.field.to_string(None));
+        self.show_results(&self.field);
         true
     }
 
@@ -888,13 +1432,13 @@ impl<'a, 'b> Commander<'a, 'b> {
         let module = match res {
             Ok(data) => {
                 if data.is_empty() {
-                    logger::error("No data selected");
+                    self.fail("No data selected");
                     return true;
                 } else if let Some(VzData::Module(m)) = data.first() {
                     filter = _args.get(1).map(|s| s.to_string());
                     m.clone()
                 } else {
-                    logger::error("Selected data is not a module");
+                    self.fail("Selected data is not a module");
                     return true;
                 }
             }
@@ -904,7 +1448,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                         filter = _args.get(0).map(|s| s.to_string());
                         m.clone()
                     } else {
-                        logger::error(&format!(
+                        self.fail(&format!(
                             "Selector error: {}. Navigator data is not a VzModule.",
                             e
                         ));
@@ -912,7 +1456,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
                 None => {
-                    logger::error(&format!("Selector error: {}. Navigator has no data.", e));
+                    self.fail(&format!("Selector error: {}. Navigator has no data.", e));
                     return true;
                 }
             },
@@ -924,54 +1468,30 @@ impl<'a, 'b> Commander<'a, 'b> {
             .collect::<Vec<_>>();
         self.field.clear_data();
         self.field.add_datas(variables);
-        println!("{}", self.field.to_string(None));
+        self.show_results(&self.field);
         true
     }
 
     pub(crate) fn read(&mut self, args: &[&str]) -> bool {
-        let arg0 = args.get(0).map(|s| s.to_string()).unwrap_or_default();
-        let res = self.selector(arg0.as_str());
-        let (address, value_type) = match res {
-            Ok(data) => {
-                if data.is_empty() {
-                    logger::error("No data selected");
-                    return true;
-                }
-                let addr = match get_address_from_data(data[0])
-                    .ok_or_else(|| "No valid address found in selected data".to_string())
-                    .and_then(|addr| {
-                        if addr == 0 {
-                            Err("Address cannot be zero".to_string())
-                        } else {
-                            Ok(addr)
-                        }
-                    }) {
-                    Ok(addr) => addr,
-                    Err(e) => {
-                        logger::error(&e);
-                        return true;
-                    }
-                };
-                let vtype = args
-                    .get(1)
-                    .and_then(|s| parse_value_type(s).ok())
-                    .unwrap_or(VzValueType::Byte);
-                (addr, vtype)
-            }
-            Err(_) => match Self::parse_number(&arg0) {
-                Ok(addr) => {
-                    let vtype = args
-                        .get(1)
-                        .and_then(|s| parse_value_type(s).ok())
-                        .unwrap_or(VzValueType::Byte);
-                    (addr, vtype)
-                }
-                Err(e) => {
-                    logger::error(&format!("Invalid address: {}", e));
-                    return true;
-                }
-            },
+        let arg0 = args.first().copied().unwrap_or_default();
+        let address = match self.resolve_target_address(arg0).and_then(|addr| {
+            if addr == 0 {
+                Err("Address cannot be zero".to_string())
+            } else {
+                Ok(addr)
+            }
+        }) {
+            Ok(addr) => addr,
+            Err(e) => {
+                self.fail(&format!("Invalid address: {}", e));
+                return true;
+            }
         };
+        self.vars.push_address(address);
+        let value_type = args
+            .get(1)
+            .and_then(|s| parse_value_type(s).ok())
+            .unwrap_or(VzValueType::Byte);
 
         let length = args
             .get(2)
@@ -979,8 +1499,32 @@ impl<'a, 'b> Commander<'a, 'b> {
             .unwrap_or(16);
 
         // Perform read operation
-        match read_memory_by_type(&mut self.script, address, &value_type, Some(length), true) {
+        let detailed = !self.json_output();
+        match read_memory_by_type(
+            &mut self.script,
+            address,
+            &value_type,
+            Some(length),
+            detailed,
+        ) {
+            Ok(result) if self.json_output() => {
+                self.vars
+                    .set_last_value(Self::plain_read_value(&result, &value_type));
+                // Numeric reads come back as plain numbers once styling is off
+                let value = serde_json::from_str::<Value>(&result)
+                    .ok()
+                    .filter(|v| v.is_number())
+                    .unwrap_or(Value::String(result));
+                self.emit_json(json!({
+                    "type": "Read",
+                    "address": format!("{:#x}", address),
+                    "value_type": value_type,
+                    "value": value,
+                }));
+            }
             Ok(result) => {
+                self.vars
+                    .set_last_value(Self::plain_read_value(&result, &value_type));
                 println!(
                     "{} {} {} = {}",
                     "[READ]".green(),
@@ -990,62 +1534,37 @@ impl<'a, 'b> Commander<'a, 'b> {
                 );
             }
             Err(e) => {
-                logger::error(&format!("Memory read error: {}", e));
+                self.fail(&format!("Memory read error: {}", e));
             }
         }
         true
     }
 
     pub(crate) fn write(&mut self, args: &[&str]) -> bool {
-        // Parse arguments: [address] <value> [type]
-        if args.len() < 1 {
-            logger::error("Write command requires at least value arguments");
+        // Parse arguments: <address> <value> [type]
+        if args.len() < 2 {
+            self.fail("Write command requires address and value arguments");
             return true;
         }
 
-        let arg0 = args.get(0).map(|s| s.to_string()).unwrap_or_default();
-        let res = self.selector(arg0.as_str());
-        let (address, value_str, value_type) = match res {
-            Ok(data) => {
-                if data.is_empty() {
-                    logger::error("No data selected");
-                    return true;
-                }
-                let addr = match get_address_from_data(data[0])
-                    .ok_or_else(|| "No valid address found in selected data".to_string())
-                    .and_then(|addr| {
-                        if addr == 0 {
-                            Err("Address cannot be zero".to_string())
-                        } else {
-                            Ok(addr)
-                        }
-                    }) {
-                    Ok(addr) => addr,
-                    Err(e) => {
-                        logger::error(&e);
-                        return true;
-                    }
-                };
-                let vtype = args
-                    .get(2)
-                    .and_then(|s| parse_value_type(s).ok())
-                    .unwrap_or(VzValueType::Byte);
-                (addr, args[1].to_string(), vtype)
-            }
-            Err(_) => match Self::parse_number(&arg0) {
-                Ok(addr) => {
-                    let vtype = args
-                        .get(2)
-                        .and_then(|s| parse_value_type(s).ok())
-                        .unwrap_or(VzValueType::Byte);
-                    (addr, args[1].to_string(), vtype)
-                }
-                Err(e) => {
-                    logger::error(&format!("Invalid address: {}", e));
-                    return true;
-                }
-            },
+        let address = match self.resolve_target_address(args[0]).and_then(|addr| {
+            if addr == 0 {
+                Err("Address cannot be zero".to_string())
+            } else {
+                Ok(addr)
+            }
+        }) {
+            Ok(addr) => addr,
+            Err(e) => {
+                self.fail(&format!("Invalid address: {}", e));
+                return true;
+            }
         };
+        let value_str = args[1].to_string();
+        let value_type = args
+            .get(2)
+            .and_then(|s| parse_value_type(s).ok())
+            .unwrap_or(VzValueType::Byte);
 
         // Perform write operation
 
@@ -1060,7 +1579,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                 );
             }
             Err(e) => {
-                logger::error(&format!("Memory write error: {}", e));
+                self.fail(&format!("Memory write error: {}", e));
             }
         }
         true
@@ -1069,115 +1588,57 @@ impl<'a, 'b> Commander<'a, 'b> {
     pub(crate) fn debug_exports(&mut self, _args: &[&str]) -> bool {
         match self.script.list_exports() {
             Ok(exports) => println!("{:?}", &exports),
-            Err(e) => logger::error(&format!("Failed to list exports: {}", e)),
+            Err(e) => self.fail(&format!("Failed to list exports: {}", e)),
         }
         true
     }
 
     pub(crate) fn view(&mut self, args: &[&str]) -> bool {
-        let arg0 = args.get(0).map(|s| s.to_string()).unwrap_or_default();
-        let res = self.selector(arg0.as_str());
-        let (address, size, value_type) = match res {
-            Ok(data) => {
-                if data.is_empty() {
-                    match self.navigator.get_data() {
-                        Some(nav_data) => {
-                            let addr = match get_address_from_data(nav_data) {
-                                Some(addr) if addr != 0 => addr,
-                                _ => {
-                                    logger::error("No valid address found in navigator data");
-                                    return true;
-                                }
-                            };
-                            let size = args
-                                .get(0)
-                                .and_then(|s| {
-                                    crate::util::format::parse_hex_or_decimal_usize(s).ok()
-                                })
-                                .unwrap_or(256);
-                            let vtype = args
-                                .get(1)
-                                .and_then(|s| parse_value_type(s).ok())
-                                .unwrap_or(VzValueType::Byte);
-                            (addr, size, vtype)
-                        }
-                        None => {
-                            logger::error("No data selected and navigator is empty");
-                            return true;
-                        }
-                    }
-                } else {
-                    let addr = match get_address_from_data(data[0])
-                        .ok_or_else(|| "No valid address found in selected data".to_string())
-                        .and_then(|addr| {
-                            if addr == 0 {
-                                Err("Address cannot be zero".to_string())
-                            } else {
-                                Ok(addr)
-                            }
-                        }) {
-                        Ok(addr) => addr,
-                        Err(e) => {
-                            logger::error(&e);
-                            return true;
-                        }
-                    };
-                    let size = args
-                        .get(1)
-                        .and_then(|s| crate::util::format::parse_hex_or_decimal_usize(s).ok())
-                        .unwrap_or(256);
-                    let vtype = args
-                        .get(2)
-                        .and_then(|s| parse_value_type(s).ok())
-                        .unwrap_or(VzValueType::Byte);
-                    (addr, size, vtype)
-                }
-            }
-            Err(_) => match Self::parse_number(&arg0) {
-                Ok(addr) => {
-                    let size = args
-                        .get(1)
-                        .and_then(|s| crate::util::format::parse_hex_or_decimal_usize(s).ok())
-                        .unwrap_or(256);
-                    let vtype = args
-                        .get(2)
-                        .and_then(|s| parse_value_type(s).ok())
-                        .unwrap_or(VzValueType::Byte);
-                    (addr, size, vtype)
-                }
-                Err(_) => match self.navigator.get_data() {
-                    Some(nav_data) => {
-                        let addr = match get_address_from_data(nav_data) {
-                            Some(addr) if addr != 0 => addr,
-                            _ => {
-                                logger::error("No valid address found in navigator data");
-                                return true;
-                            }
-                        };
-                        let size = args
-                            .get(0)
-                            .and_then(|s| crate::util::format::parse_hex_or_decimal_usize(s).ok())
-                            .unwrap_or(256);
-                        let vtype = args
-                            .get(1)
-                            .and_then(|s| parse_value_type(s).ok())
-                            .unwrap_or(VzValueType::Byte);
-                        (addr, size, vtype)
-                    }
+        // The target is optional; without one the arguments start at size
+        let target = args.first().map(|t| self.resolve_target_address(t));
+        let (address, rest) = match target {
+            Some(Ok(addr)) => {
+                self.vars.push_address(addr);
+                (addr, args.get(1..).unwrap_or_default())
+            }
+            Some(Err(e)) if self.navigator.get_data().is_none() => {
+                self.fail(&format!("Invalid address: {}", e));
+                return true;
+            }
+            _ => match self.navigator.get_data() {
+                Some(nav_data) => match get_address_from_data(nav_data) {
+                    Some(addr) => (addr, args),
                     None => {
-                        logger::error("Invalid address and no navigator data available");
+                        self.fail("No valid address found in navigator data");
                         return true;
                     }
                 },
+                None => {
+                    self.fail("No data selected and navigator is empty");
+                    return true;
+                }
             },
         };
+        if address == 0 {
+            self.fail("Address cannot be zero");
+            return true;
+        }
+        let size = rest
+            .first()
+            .and_then(|s| crate::util::format::parse_hex_or_decimal_usize(s).ok())
+            .unwrap_or(256);
+        let value_type = rest
+            .get(1)
+            .and_then(|s| parse_value_type(s).ok())
+            .unwrap_or(VzValueType::Byte);
 
-        match view_memory(&mut self.script, address, &value_type, size) {
+        let labels = self.labels();
+        match view_memory(self.script, address, &value_type, size, &labels) {
             Ok(result) => {
                 println!("{}", result);
             }
             Err(e) => {
-                logger::error(&format!("Memory view error: {}", e));
+                self.fail(&format!("Memory view error: {}", e));
             }
         }
         true
@@ -1189,10 +1650,10 @@ impl<'a, 'b> Commander<'a, 'b> {
 
     pub(crate) fn clear_screen(&mut self, _args: &[&str]) -> bool {
         if let Err(e) = stdout().execute(terminal::Clear(terminal::ClearType::All)) {
-            logger::error(&format!("Failed to clear screen: {}", e));
+            self.fail(&format!("Failed to clear screen: {}", e));
         }
         if let Err(e) = stdout().execute(crossterm::cursor::MoveTo(0, 0)) {
-            logger::error(&format!("Failed to move cursor: {}", e));
+            self.fail(&format!("Failed to move cursor: {}", e));
         }
         true
     }
@@ -1202,21 +1663,38 @@ impl<'a, 'b> Commander<'a, 'b> {
     // ========================================================================
 
     pub(crate) fn hook_add(&mut self, args: &[&str]) -> bool {
+        self.attach_hook(args, false)
+    }
+
+    /// `hook add` whose hook also stops each matching call's thread until
+    /// `continue`
+    pub(crate) fn hook_break(&mut self, args: &[&str]) -> bool {
+        self.attach_hook(args, true)
+    }
+
+    fn attach_hook(&mut self, args: &[&str], breakpoint: bool) -> bool {
         if args.is_empty() {
-            logger::error("Target address or selector required");
+            self.fail("Target address or selector required");
             return true;
         }
 
         let arg0 = args[0];
 
-        // Try to resolve the target address
-        let address = self.resolve_target_address(arg0);
-        let address = match address {
-            Ok(addr) => addr,
-            Err(e) => {
-                logger::error(&format!("Failed to resolve target: {}", e));
-                return true;
-            }
+        // Try to resolve the target address; `module!symbol` in a module that
+        // is not loaded yet becomes a pending hook
+        let mut pending = None;
+        let address = match self.resolve_target_address(arg0) {
+            Ok(addr) => Some(addr),
+            Err(e) => match self.unloaded_target(arg0) {
+                Some(target) => {
+                    pending = Some(target);
+                    None
+                }
+                None => {
+                    self.fail(&format!("Failed to resolve target: {}", e));
+                    return true;
+                }
+            },
         };
 
         // Parse options from remaining args
@@ -1227,9 +1705,14 @@ impl<'a, 'b> Commander<'a, 'b> {
         config.insert("logRetval".to_string(), json!(false));
         config.insert("backtrace".to_string(), json!(false));
         config.insert("argCount".to_string(), json!(4));
+        config.insert("breakpoint".to_string(), json!(breakpoint));
 
         // Parse option flags
-        for arg in args.iter().skip(1) {
+        let mut sig: Option<Signature> = None;
+        let mut lookup_signature = true;
+        let mut quiet = false;
+        let mut options = args.iter().skip(1);
+        while let Some(arg) = options.next() {
             match *arg {
                 "-e" | "--enter" => {
                     config.insert("onEnter".to_string(), json!(true));
@@ -1252,15 +1735,89 @@ impl<'a, 'b> Commander<'a, 'b> {
                     config.insert("logArgs".to_string(), json!(true));
                     config.insert("logRetval".to_string(), json!(true));
                 }
+                "-s" | "--sig" => {
+                    let Some(decl) = options.next() else {
+                        self.fail("--sig requires a declaration");
+                        return true;
+                    };
+                    match signature::parse_signature(decl) {
+                        Ok(parsed) => sig = Some(parsed),
+                        Err(e) => {
+                            self.fail(&format!("Invalid signature: {}", e));
+                            return true;
+                        }
+                    }
+                }
+                "--if" => {
+                    let Some(expr) = options.next() else {
+                        self.fail("--if requires a condition");
+                        return true;
+                    };
+                    match parse_hook_condition(expr) {
+                        Ok(condition) => {
+                            config.insert("condition".to_string(), condition);
+                            config.insert("conditionText".to_string(), json!(expr));
+                        }
+                        Err(e) => {
+                            self.fail(&format!("Invalid condition: {}", e));
+                            return true;
+                        }
+                    }
+                }
+                "-q" | "--quiet" => {
+                    quiet = true;
+                }
+                "-n" | "--nosig" => {
+                    lookup_signature = false;
+                }
                 _ => {}
             }
         }
 
-        // Call the hook_attach RPC
-        let result = self
-            .script
-            .exports
-            .call("hook_attach", Some(json!([format!("{}", address), config])));
+        // Known functions are decoded by default, return value included
+        if sig.is_none() && lookup_signature {
+            if let Some(found) = self.signature_at(address, arg0) {
+                config.insert("logArgs".to_string(), json!(true));
+                if found.ret.is_some() {
+                    config.insert("onLeave".to_string(), json!(true));
+                    config.insert("logRetval".to_string(), json!(true));
+                }
+                sig = Some(found);
+            }
+        }
+        if let Some(sig) = sig {
+            config.insert("logArgs".to_string(), json!(true));
+            if sig.has_out_args() {
+                config.insert("onLeave".to_string(), json!(true));
+            }
+            config.insert(
+                "signature".to_string(),
+                json!({"name": sig.name, "args": sig.args, "ret": sig.ret}),
+            );
+        }
+        // Quiet hooks only count calls, whatever the signature would print
+        if quiet {
+            config.insert("onEnter".to_string(), json!(false));
+            config.insert("onLeave".to_string(), json!(false));
+        }
+
+        let (address, result) = match address {
+            Some(address) => (
+                format!("{:#x}", address),
+                self.script
+                    .exports
+                    .call("hook_attach", Some(json!([format!("{}", address), config]))),
+            ),
+            None => {
+                let (module, symbol) = pending.unwrap_or_default();
+                (
+                    format!("{}!{} (pending until {} is loaded)", module, symbol, module),
+                    self.script
+                        .exports
+                        .call("hook_defer", Some(json!([module, symbol, config]))),
+                )
+            }
+        };
 
         match result {
             Ok(Some(value)) => {
@@ -1270,23 +1827,395 @@ impl<'a, 'b> Commander<'a, 'b> {
                             .get("id")
                             .and_then(|v| v.as_str())
                             .unwrap_or("unknown");
+                        self.vars.set_last_hook(id.to_string());
                         println!(
-                            "{} Hook added: {} @ {}",
+                            "{} {} added: {} @ {}",
                             "[HOOK]".green(),
+                            if breakpoint { "Break hook" } else { "Hook" },
                             id.cyan(),
-                            format!("{:#x}", address).yellow()
+                            address.yellow()
                         );
                     } else {
                         let error = value
                             .get("error")
                             .and_then(|v| v.as_str())
                             .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to add hook: {}", error));
+                        self.fail(&format!("Failed to add hook: {}", error));
+                    }
+                }
+            }
+            Ok(None) => self.fail("No response from hook_attach"),
+            Err(e) => self.fail(&format!("Hook attach error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn hook_replace(&mut self, args: &[&str]) -> bool {
+        let arg0 = args[0];
+        let address = match self.resolve_target_address(arg0) {
+            Ok(addr) => addr,
+            Err(e) => {
+                self.fail(&format!("Failed to resolve target: {}", e));
+                return true;
+            }
+        };
+
+        let mut retval: Option<&str> = None;
+        let mut skip = false;
+        let mut abi: Option<&str> = None;
+        let mut argc: Option<usize> = None;
+        let mut options = args.iter().skip(1);
+        while let Some(arg) = options.next() {
+            match *arg {
+                "--skip" => skip = true,
+                "-r" | "--ret" | "--abi" | "--argc" => {
+                    let Some(value) = options.next() else {
+                        self.fail(&format!("{} requires a value", arg));
+                        return true;
+                    };
+                    match *arg {
+                        "--abi" => abi = Some(value),
+                        "--argc" => match Self::parse_usize(value) {
+                            Ok(n) => argc = Some(n),
+                            Err(e) => {
+                                self.fail(&format!("Invalid argument count: {}", e));
+                                return true;
+                            }
+                        },
+                        _ => retval = Some(value),
                     }
                 }
+                other => {
+                    self.fail(&format!("Unknown option: {}", other));
+                    return true;
+                }
+            }
+        }
+        if skip == retval.is_some() {
+            self.fail("Specify either --ret <value> or --skip");
+            return true;
+        }
+
+        // A known signature gives the callback its return and argument types
+        let sig = self.signature_at(Some(address), arg0);
+        let ret_type = sig
+            .as_ref()
+            .and_then(|s| s.ret.as_ref())
+            .map_or("pointer", ArgSpec::native_type);
+        let arg_types: Vec<&str> = match (&sig, argc) {
+            (_, Some(n)) => vec!["pointer"; n],
+            (Some(sig), None) => sig.args.iter().map(ArgSpec::native_type).collect(),
+            (None, None) => Vec::new(),
+        };
+
+        let mut config = json!({"skip": skip, "retType": ret_type, "argTypes": arg_types});
+        if let Some(abi) = abi {
+            config["abi"] = json!(abi);
+        }
+        if let Some(value) = retval {
+            let parsed = if ret_type == "pointer" {
+                // Negative values wrap, so `--ret -1` returns all bits set
+                match value.strip_prefix('-') {
+                    Some(v) => Self::parse_number(v).map(|n| n.wrapping_neg()),
+                    None => Self::parse_number(value),
+                }
+                .map(|n| json!(format!("{:#x}", n)))
+            } else {
+                value
+                    .parse::<f64>()
+                    .map(|f| json!(f))
+                    .map_err(|e| e.to_string())
+            };
+            match parsed {
+                Ok(v) => config["retval"] = v,
+                Err(e) => {
+                    self.fail(&format!("Invalid return value: {}", e));
+                    return true;
+                }
+            }
+        }
+
+        let result = self.script.exports.call(
+            "hook_replace",
+            Some(json!([format!("{:#x}", address), config])),
+        );
+        match result {
+            Ok(Some(value)) => {
+                if value.get("success").and_then(|v| v.as_bool()) == Some(true) {
+                    let id = value
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown");
+                    self.vars.set_last_hook(id.to_string());
+                    let action = match retval {
+                        Some(v) => format!("returns {}", v),
+                        None => "skipped".to_string(),
+                    };
+                    println!(
+                        "{} Replaced: {} @ {} ({})",
+                        "[HOOK]".green(),
+                        id.cyan(),
+                        format!("{:#x}", address).yellow(),
+                        action
+                    );
+                } else {
+                    let error = value
+                        .get("error")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("Unknown error");
+                    self.fail(&format!("Failed to replace function: {}", error));
+                }
+            }
+            Ok(None) => self.fail("No response from hook_replace"),
+            Err(e) => self.fail(&format!("Hook replace error: {}", e)),
+        }
+        true
+    }
+
+    /// Signature for a hook target, by the debug symbol starting at `address`.
+    /// Aliased libc exports such as glibc's `open` may resolve to a differently
+    /// named symbol, so inside libc the name it was hooked as (`open`,
+    /// `libc.so.6!open`) is tried too. Without an address, e.g. for a module
+    /// that is not loaded yet, only a libc-qualified name is looked up.
+    fn signature_at(&mut self, address: Option<u64>, target: &str) -> Option<Signature> {
+        let (symbol, module) = match address {
+            Some(address) => {
+                let function = self.function_starting_at(address)?;
+                (Some(function.name), function.module)
+            }
+            None => (
+                None,
+                target.split_once('!')?.0.trim_matches('"').to_string(),
+            ),
+        };
+        if let Some(sig) = symbol.and_then(|name| self.signatures.get(&name)) {
+            return Some(sig.clone());
+        }
+        let name = target.rsplit('!').next().unwrap_or(target);
+        let is_libc = ["libc.", "libc-", "libsystem_"]
+            .iter()
+            .any(|prefix| module.starts_with(prefix));
+        if !is_libc
+            || name.is_empty()
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return None;
+        }
+        self.signatures.get(name).cloned()
+    }
+
+    /// Module and symbol of a `module!symbol` target whose module is not
+    /// loaded in the process
+    fn unloaded_target(&mut self, target: &str) -> Option<(String, String)> {
+        let (module, symbol) = target.split_once('!')?;
+        let module = module.trim_matches('"');
+        if module.is_empty() || symbol.is_empty() || symbol.contains(char::is_whitespace) {
+            return None;
+        }
+        match self
+            .script
+            .exports
+            .call("find_module", Some(json!([module])))
+        {
+            Ok(None) | Ok(Some(Value::Null)) => Some((module.to_string(), symbol.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Debug symbol starting exactly at `address`
+    fn function_starting_at(&mut self, address: u64) -> Option<VzFunction> {
+        let function = self.functions_at(&[address]).pop()??;
+        (function.address == address).then_some(function)
+    }
+
+    /// Function symbols containing each address, with the address each starts at
+    fn functions_at(&mut self, addresses: &[u64]) -> Vec<Option<VzFunction>> {
+        let hex: Vec<String> = addresses.iter().map(|a| format!("{:#x}", a)).collect();
+        let symbols = match self
+            .script
+            .exports
+            .call("symbol_starts", Some(json!([hex])))
+        {
+            Ok(Some(Value::Array(symbols))) => symbols,
+            _ => return vec![None; addresses.len()],
+        };
+        symbols
+            .iter()
+            .map(|symbol| {
+                Some(VzFunction {
+                    base: new_base(VzDataType::Function),
+                    name: symbol.get("name")?.as_str()?.to_string(),
+                    address: string_to_u64(symbol.get("address")?.as_str()?),
+                    module: symbol.get("moduleName")?.as_str()?.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn hook_sigs(&mut self, args: &[&str]) -> bool {
+        let filter = args.first().copied().unwrap_or("");
+        let sigs: Vec<&Signature> = self
+            .signatures
+            .iter()
+            .into_iter()
+            .filter(|s| s.name.contains(filter))
+            .collect();
+        if self.json_output() {
+            for sig in sigs {
+                self.emit_json(json!(sig));
+            }
+            return true;
+        }
+        if sigs.is_empty() {
+            println!("{}", "No matching signatures".dark_grey());
+            return true;
+        }
+        println!("{} Signatures: {}", "[SIGS]".green(), sigs.len());
+        for sig in sigs {
+            println!("  {}", sig);
+        }
+        true
+    }
+
+    pub(crate) fn hook_sigload(&mut self, args: &[&str]) -> bool {
+        match self.signatures.load_file(std::path::Path::new(args[0])) {
+            Ok(count) => println!(
+                "{} Loaded {} signatures from {}",
+                "[SIGS]".green(),
+                count,
+                args[0]
+            ),
+            Err(e) => self.fail(&e),
+        }
+        true
+    }
+
+    pub(crate) fn trace_start(&mut self, args: &[&str]) -> bool {
+        let mut imports = false;
+        let mut with_retval = false;
+        let mut positional = Vec::new();
+        for arg in args {
+            match *arg {
+                "-i" | "--imports" => imports = true,
+                "-r" | "--ret" => with_retval = true,
+                _ => positional.push(*arg),
+            }
+        }
+        let Some(&module) = positional.first() else {
+            self.fail("Module name required, e.g. trace libssl.so SSL_*");
+            return true;
+        };
+        let pattern = match positional.get(1) {
+            Some(pattern) => *pattern,
+            None if imports => "*",
+            None => {
+                self.fail("Function pattern required, e.g. trace libssl.so SSL_*");
+                return true;
+            }
+        };
+
+        let base = match self
+            .script
+            .exports
+            .call("find_module", Some(json!([module])))
+        {
+            Ok(Some(m)) => match m.get("address").and_then(|v| v.as_str()) {
+                Some(base) => base.to_string(),
+                None => {
+                    self.fail(&format!("Module not found: {}", module));
+                    return true;
+                }
+            },
+            Ok(None) => {
+                self.fail(&format!("Module not found: {}", module));
+                return true;
+            }
+            Err(e) => {
+                self.fail(&format!("Module lookup failed: {}", e));
+                return true;
+            }
+        };
+        let rpc = if imports {
+            "list_imports"
+        } else {
+            "list_functions"
+        };
+        let entries = match self.script.exports.call(rpc, Some(json!([base, null]))) {
+            Ok(Some(Value::Array(entries))) => entries,
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                self.fail(&format!("Failed to list functions: {}", e));
+                return true;
+            }
+        };
+
+        // Imports include variables, and aliases share an address
+        let glob = trace::glob_to_regex(pattern);
+        let mut seen = HashSet::new();
+        let targets: Vec<Value> = entries
+            .iter()
+            .filter(|e| !imports || e.get("type").and_then(|v| v.as_str()) == Some("function"))
+            .filter_map(|e| {
+                let name = e.get("name")?.as_str()?;
+                let address = e.get("address")?.as_str()?;
+                (glob.is_match(name) && seen.insert(address.to_string()))
+                    .then(|| json!({"name": name, "address": address}))
+            })
+            .collect();
+        if targets.is_empty() {
+            self.fail(&format!("No functions in {} match {}", module, pattern));
+            return true;
+        }
+        if targets.len() > trace::MAX_TRACE_TARGETS {
+            self.fail(&format!(
+                "{} functions match {}; narrow the pattern (limit {})",
+                targets.len(),
+                pattern,
+                trace::MAX_TRACE_TARGETS
+            ));
+            return true;
+        }
+
+        let result = self
+            .script
+            .exports
+            .call("trace_start", Some(json!([targets, with_retval])));
+        match result {
+            Ok(Some(value)) => {
+                let count = value.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+                println!(
+                    "{} Tracing {} functions in {}",
+                    "[TRACE]".green(),
+                    count.to_string().yellow(),
+                    module.cyan()
+                );
+                let failed: Vec<&str> = value
+                    .get("failed")
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                if !failed.is_empty() {
+                    println!(
+                        "{} Could not hook: {}",
+                        "[TRACE]".yellow(),
+                        failed.join(", ").dark_grey()
+                    );
+                }
             }
-            Ok(None) => logger::error("No response from hook_attach"),
-            Err(e) => logger::error(&format!("Hook attach error: {}", e)),
+            Ok(None) => self.fail("No response from trace_start"),
+            Err(e) => self.fail(&format!("Trace error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn trace_stop(&mut self, _args: &[&str]) -> bool {
+        match self.script.exports.call("trace_stop", None) {
+            Ok(Some(value)) => {
+                let count = value.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+                println!("{} Removed {} trace hooks", "[TRACE]".green(), count);
+            }
+            Ok(None) => self.fail("No response from trace_stop"),
+            Err(e) => self.fail(&format!("Trace stop error: {}", e)),
         }
         true
     }
@@ -1295,186 +2224,1918 @@ impl<'a, 'b> Commander<'a, 'b> {
         let id = match args.get(0) {
             Some(id) => *id,
             None => {
-                logger::error("Hook ID required");
+                self.fail("Hook ID required");
                 return true;
             }
         };
 
+        self.resume_stopped(Some(id));
         let result = self.script.exports.call("hook_detach", Some(json!([id])));
 
         match result {
             Ok(Some(value)) => {
                 if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
                     if success {
+                        eventlog::stop_capture(id);
                         println!("{} Hook removed: {}", "[HOOK]".green(), id.cyan());
                     } else {
                         let error = value
                             .get("error")
                             .and_then(|v| v.as_str())
                             .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to remove hook: {}", error));
+                        self.fail(&format!("Failed to remove hook: {}", error));
+                    }
+                }
+            }
+            Ok(None) => self.fail("No response from hook_detach"),
+            Err(e) => self.fail(&format!("Hook detach error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn hook_list(&mut self, _args: &[&str]) -> bool {
+        let result = self.script.exports.call("hook_list", None);
+
+        match result {
+            Ok(Some(value)) if self.json_output() => {
+                for hook in value.as_array().into_iter().flatten() {
+                    self.emit_json(json!(VzData::Hook(Self::hook_from_json(hook))));
+                }
+            }
+            Ok(Some(value)) => {
+                if let Some(hooks) = value.as_array() {
+                    if hooks.is_empty() {
+                        println!("{}", "No active hooks".dark_grey());
+                    } else {
+                        println!("{} Active hooks: {}", "[HOOKS]".green(), hooks.len());
+                        for hook in hooks {
+                            let id = hook.get("id").and_then(|v| v.as_str()).unwrap_or("?");
+                            // Pending hooks have no address yet, only `module!symbol`
+                            let pending = hook.get("pending").and_then(|v| v.as_str());
+                            let key = if pending.is_some() {
+                                "target"
+                            } else {
+                                "address"
+                            };
+                            let address = hook.get(key).and_then(|v| v.as_str()).unwrap_or("?");
+                            let enabled = hook
+                                .get("enabled")
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false);
+                            let status = match pending {
+                                Some("failed") => format!(
+                                    "failed: {}",
+                                    hook.get("error").and_then(|v| v.as_str()).unwrap_or("?")
+                                )
+                                .red(),
+                                _ if !enabled => "disabled".to_string().dark_grey(),
+                                Some(_) => "pending".to_string().yellow(),
+                                None => "enabled".to_string().green(),
+                            };
+
+                            let config = hook.get("config");
+                            let on_enter = config
+                                .and_then(|c| c.get("onEnter"))
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false);
+                            let on_leave = config
+                                .and_then(|c| c.get("onLeave"))
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false);
+                            let log_args = config
+                                .and_then(|c| c.get("logArgs"))
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false);
+                            let log_retval = config
+                                .and_then(|c| c.get("logRetval"))
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false);
+
+                            let replace = config
+                                .and_then(|c| c.get("replace"))
+                                .filter(|r| !r.is_null());
+                            let flags = match replace {
+                                Some(r)
+                                    if r.get("skip").and_then(|v| v.as_bool()) == Some(true) =>
+                                {
+                                    "replaced: skip".to_string()
+                                }
+                                Some(r) => match r.get("retval") {
+                                    Some(Value::String(v)) => format!("replaced: ret {}", v),
+                                    Some(v) => format!("replaced: ret {}", v),
+                                    None => "replaced".to_string(),
+                                },
+                                None => format!(
+                                    "{}{}{}{}",
+                                    if on_enter { "E" } else { "-" },
+                                    if on_leave { "L" } else { "-" },
+                                    if log_args { "A" } else { "-" },
+                                    if log_retval { "R" } else { "-" }
+                                ),
+                            };
+
+                            let sig = config
+                                .and_then(|c| c.get("signature"))
+                                .and_then(|s| s.get("name"))
+                                .and_then(|v| v.as_str())
+                                .map(|name| format!(" sig: {}", name))
+                                .unwrap_or_default();
+                            let condition = config
+                                .and_then(|c| c.get("conditionText"))
+                                .and_then(|v| v.as_str())
+                                .map(|expr| format!(" if: {}", expr))
+                                .unwrap_or_default();
+                            let breakpoint = config
+                                .and_then(|c| c.get("breakpoint"))
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false);
+
+                            println!(
+                                "  {} @ {} [{}] ({}){}{}{}",
+                                id.cyan(),
+                                address.yellow(),
+                                flags.dark_grey(),
+                                status,
+                                if breakpoint { " break".red() } else { "".red() },
+                                sig.dark_grey(),
+                                condition.dark_grey()
+                            );
+                        }
                     }
                 }
             }
-            Ok(None) => logger::error("No response from hook_detach"),
-            Err(e) => logger::error(&format!("Hook detach error: {}", e)),
+            Ok(None) if self.json_output() => {}
+            Ok(None) => println!("{}", "No active hooks".dark_grey()),
+            Err(e) => self.fail(&format!("Hook list error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn hook_stats(&mut self, args: &[&str]) -> bool {
+        let mut id: Option<&str> = None;
+        let mut sort = "calls";
+        let mut options = args.iter();
+        while let Some(arg) = options.next() {
+            match *arg {
+                "-s" | "--sort" => match options.next() {
+                    Some(key) => sort = key,
+                    None => {
+                        self.fail("--sort requires a column");
+                        return true;
+                    }
+                },
+                _ => id = Some(arg),
+            }
+        }
+
+        let result = self.script.exports.call("hook_stats", Some(json!([id])));
+        let value = match result {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                self.fail("No response from hook_stats");
+                return true;
+            }
+            Err(e) => {
+                self.fail(&format!("Hook stats error: {}", e));
+                return true;
+            }
+        };
+        let mut stats: Vec<HookStats> = match serde_json::from_value(value) {
+            Ok(stats) => stats,
+            Err(e) => {
+                self.fail(&format!("Invalid hook stats: {}", e));
+                return true;
+            }
+        };
+        if let Err(e) = hookstats::sort_stats(&mut stats, sort) {
+            self.fail(&e);
+            return true;
+        }
+
+        if self.json_output() {
+            for s in &stats {
+                self.emit_json(json!(s));
+            }
+        } else if stats.is_empty() {
+            match id {
+                Some(id) => self.fail(&format!("Hook not found: {}", id)),
+                None => println!("{}", "No active hooks".dark_grey()),
+            }
+        } else if id.is_some() {
+            println!("{}", hookstats::render_detail(&stats[0]));
+        } else {
+            println!("{} Hook stats: {}", "[HOOKS]".green(), stats.len());
+            println!("{}", hookstats::render_table(&stats));
+        }
+        true
+    }
+
+    pub(crate) fn hook_enable(&mut self, args: &[&str]) -> bool {
+        let id = match args.get(0) {
+            Some(id) => *id,
+            None => {
+                self.fail("Hook ID required");
+                return true;
+            }
+        };
+
+        let result = self.script.exports.call("hook_enable", Some(json!([id])));
+
+        match result {
+            Ok(Some(value)) => {
+                if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
+                    if success {
+                        println!("{} Hook enabled: {}", "[HOOK]".green(), id.cyan());
+                    } else {
+                        let error = value
+                            .get("error")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("Unknown error");
+                        self.fail(&format!("Failed to enable hook: {}", error));
+                    }
+                }
+            }
+            Ok(None) => self.fail("No response from hook_enable"),
+            Err(e) => self.fail(&format!("Hook enable error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn hook_disable(&mut self, args: &[&str]) -> bool {
+        let id = match args.get(0) {
+            Some(id) => *id,
+            None => {
+                self.fail("Hook ID required");
+                return true;
+            }
+        };
+
+        self.resume_stopped(Some(id));
+        let result = self.script.exports.call("hook_disable", Some(json!([id])));
+
+        match result {
+            Ok(Some(value)) => {
+                if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
+                    if success {
+                        println!("{} Hook disabled: {}", "[HOOK]".green(), id.cyan());
+                    } else {
+                        let error = value
+                            .get("error")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("Unknown error");
+                        self.fail(&format!("Failed to disable hook: {}", error));
+                    }
+                }
+            }
+            Ok(None) => self.fail("No response from hook_disable"),
+            Err(e) => self.fail(&format!("Hook disable error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn hook_clear(&mut self, _args: &[&str]) -> bool {
+        self.resume_stopped(None);
+        let result = self.script.exports.call("hook_clear_all", None);
+
+        match result {
+            Ok(Some(value)) => {
+                if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
+                    if success {
+                        eventlog::stop_all_captures();
+                        let count = value.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+                        println!("{} Cleared {} hooks", "[HOOK]".green(), count);
+                    } else {
+                        let error = value
+                            .get("error")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("Unknown error");
+                        self.fail(&format!("Failed to clear hooks: {}", error));
+                    }
+                }
+            }
+            Ok(None) => self.fail("No response from hook_clear_all"),
+            Err(e) => self.fail(&format!("Hook clear error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn hook_log(&mut self, args: &[&str]) -> bool {
+        let mut append = false;
+        let mut file: Option<&str> = None;
+        for arg in args {
+            match *arg {
+                "-a" | "--append" => append = true,
+                other => file = Some(other),
+            }
+        }
+
+        match file {
+            None => match eventlog::status() {
+                Some((path, written)) => println!(
+                    "{} Logging hook events to {} ({} written)",
+                    "[LOG]".cyan(),
+                    path.display().to_string().yellow(),
+                    written
+                ),
+                None => println!("{}", "Hook events are not being logged".dark_grey()),
+            },
+            Some("off") => match eventlog::stop() {
+                Some((path, written)) => println!(
+                    "{} Stopped logging to {} ({} events)",
+                    "[LOG]".green(),
+                    path.display().to_string().yellow(),
+                    written
+                ),
+                None => self.fail("Hook events are not being logged"),
+            },
+            Some(file) => match eventlog::start(std::path::Path::new(file), append) {
+                Ok(()) => println!(
+                    "{} Logging hook events to {}",
+                    "[LOG]".green(),
+                    file.yellow()
+                ),
+                Err(e) => self.fail(&format!("Failed to open hook log: {}", e)),
+            },
+        }
+        true
+    }
+
+    pub(crate) fn hook_capture(&mut self, args: &[&str]) -> bool {
+        let Some(&id) = args.first() else {
+            let captures = eventlog::captures();
+            if captures.is_empty() {
+                println!("{}", "No hooks are being captured".dark_grey());
+            }
+            for (id, left) in captures {
+                println!(
+                    "{} {} {}",
+                    "[CAPTURE]".cyan(),
+                    id.cyan(),
+                    format!("({} events left)", left).dark_grey()
+                );
+            }
+            return true;
+        };
+
+        let max = match args.get(1).copied() {
+            Some("off") => {
+                if eventlog::stop_capture(id) {
+                    println!("{} Stopped capturing {}", "[CAPTURE]".green(), id.cyan());
+                } else {
+                    self.fail(&format!("Hook {} is not being captured", id));
+                }
+                return true;
+            }
+            Some(max) => match Self::parse_usize(max) {
+                Ok(0) => {
+                    self.fail("Capture limit must be at least 1");
+                    return true;
+                }
+                Ok(max) => max,
+                Err(e) => {
+                    self.fail(&format!("Invalid capture limit: {}", e));
+                    return true;
+                }
+            },
+            None => DEFAULT_CAPTURE_MAX,
+        };
+        if !self.hook_ids().iter().any(|h| h == id) {
+            self.fail(&format!("Hook not found: {}", id));
+            return true;
+        }
+        eventlog::capture(id, max);
+        println!(
+            "{} Capturing up to {} events of {} into Field",
+            "[CAPTURE]".green(),
+            max,
+            id.cyan()
+        );
+        true
+    }
+
+    /// Append events captured since the last command to Field
+    fn collect_captured(&mut self) {
+        let events = eventlog::take_captured();
+        if !events.is_empty() {
+            self.field
+                .add_datas(events.iter().map(|e| VzData::HookEvent(e.into())).collect());
+        }
+    }
+
+    /// IDs of the hooks the agent knows, pending ones included
+    fn hook_ids(&mut self) -> Vec<String> {
+        match self.script.exports.call("hook_list", None) {
+            Ok(Some(value)) => value
+                .as_array()
+                .map(|hooks| {
+                    hooks
+                        .iter()
+                        .filter_map(|h| h.get("id").and_then(|v| v.as_str()))
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    // ========================================================================
+    // Event Log Commands
+    // ========================================================================
+
+    pub(crate) fn events_load(&mut self, args: &[&str]) -> bool {
+        match eventlog::load(std::path::Path::new(args[0])) {
+            Ok((events, skipped)) => {
+                self.events = events;
+                println!(
+                    "{} Loaded {} events from {}",
+                    "[EVENTS]".green(),
+                    self.events.len(),
+                    args[0].yellow()
+                );
+                if skipped > 0 {
+                    println!(
+                        "{}",
+                        format!("  {} lines were not hook events", skipped).dark_grey()
+                    );
+                }
+            }
+            Err(e) => self.fail(&format!("Failed to load events: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn events_list(&mut self, args: &[&str]) -> bool {
+        let count = match args.first().map(|s| Self::parse_usize(s)) {
+            Some(Ok(count)) => count,
+            Some(Err(e)) => {
+                self.fail(&format!("Invalid count: {}", e));
+                return true;
+            }
+            None => self.events.len(),
+        };
+        let events: Vec<&HookEvent> = self.events.iter().take(count).collect();
+        self.show_events(&events);
+        true
+    }
+
+    pub(crate) fn events_filter(&mut self, args: &[&str]) -> bool {
+        // The filter may be quoted or spread over several arguments
+        let expr = args.join(" ");
+        let segments = match parse_filter_string(&expr) {
+            Ok(segments) if !segments.is_empty() => segments,
+            Ok(_) => {
+                self.fail("Filter expression required");
+                return true;
+            }
+            Err(e) => {
+                self.fail(&format!("Failed to parse filter string: {}", e));
+                return true;
+            }
+        };
+        for segment in &segments {
+            if let FilterSegment::Condition(cond) = segment {
+                if !eventlog::is_filter_key(&cond.key) {
+                    self.fail(&format!(
+                        "Unknown event key '{}' (expected {})",
+                        cond.key,
+                        eventlog::FILTER_KEYS
+                    ));
+                    return true;
+                }
+            }
+        }
+
+        let events: Vec<&HookEvent> = self
+            .events
+            .iter()
+            .filter(|e| eventlog::matches(e, &segments))
+            .collect();
+        self.show_events(&events);
+        true
+    }
+
+    pub(crate) fn events_clear(&mut self, _args: &[&str]) -> bool {
+        self.events.clear();
+        println!("{} Cleared loaded events", "[EVENTS]".green());
+        true
+    }
+
+    /// Print events as the handler would have, or as JSON lines
+    fn show_events(&self, events: &[&HookEvent]) {
+        if self.json_output() {
+            for event in events {
+                self.emit_json(json!(event));
+            }
+            return;
+        }
+        if self.events.is_empty() {
+            println!("{}", "No events loaded (events load <file>)".dark_grey());
+            return;
+        }
+        println!(
+            "{} {} of {} events",
+            "[EVENTS]".green(),
+            events.len(),
+            self.events.len()
+        );
+        for event in events {
+            println!("{}", event.render());
+        }
+    }
+
+    // ========================================================================
+    // Break Hooks
+    // ========================================================================
+
+    /// Threads stopped at break hooks with the hook that stopped them, oldest
+    /// first
+    fn stopped_threads(&mut self) -> Vec<(u64, String)> {
+        let result = self.script.exports.call("break_list", None);
+        let Ok(Some(Value::Array(stopped))) = result else {
+            return Vec::new();
+        };
+        stopped
+            .iter()
+            .filter_map(|s| {
+                Some((
+                    s.get("threadId")?.as_u64()?,
+                    s.get("id")?.as_str()?.to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Thread that `continue`, `args` and `thread setreg` act on: the selected
+    /// thread if it is stopped, else the one stopped longest
+    fn stopped_thread(&mut self) -> Option<u64> {
+        let stopped = self.stopped_threads();
+        if let Some(VzData::Thread(t)) = self.navigator.get_data() {
+            if stopped.iter().any(|(tid, _)| *tid == t.id) {
+                return Some(t.id);
+            }
+        }
+        stopped.first().map(|(tid, _)| *tid)
+    }
+
+    /// Thread that thread commands and register names default to: the
+    /// selected thread, else as `stopped_thread`, falling back to the first
+    /// thread of the process
+    fn current_thread(&mut self) -> Option<u64> {
+        if let Some(VzData::Thread(t)) = self.navigator.get_data() {
+            return Some(t.id);
+        }
+        if let Some(tid) = self.stopped_thread() {
+            return Some(tid);
+        }
+        self.script
+            .exports
+            .call("list_threads", None)
+            .ok()??
+            .as_array()?
+            .first()?
+            .get("id")?
+            .as_u64()
+    }
+
+    /// Post the message a stopped thread is waiting for in the agent
+    fn resume_thread(&mut self, thread_id: u64) -> Result<(), String> {
+        let message = json!({ "type": format!("break:resume:{}", thread_id) });
+        self.script
+            .post(message.to_string(), None)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Resume the threads stopped at `hook`, or at any hook, before it is
+    /// removed; nothing else could wake them afterwards
+    fn resume_stopped(&mut self, hook: Option<&str>) {
+        for (tid, id) in self.stopped_threads() {
+            if hook.is_some_and(|h| h != id) {
+                continue;
+            }
+            if let Err(e) = self.resume_thread(tid) {
+                self.fail(&format!("Failed to resume thread {}: {}", tid, e));
+            }
+        }
+    }
+
+    /// Register-sized operand: an address expression, or a negative number
+    /// wrapped to the full width
+    fn register_operand(&mut self, value: &str) -> Result<u64, String> {
+        match value.strip_prefix('-') {
+            Some(v) => Self::parse_number(v).map(|n| n.wrapping_neg()),
+            None => self.resolve_target_address(value),
+        }
+    }
+
+    pub(crate) fn continue_thread(&mut self, args: &[&str]) -> bool {
+        let stopped: Vec<u64> = self
+            .stopped_threads()
+            .into_iter()
+            .map(|(tid, _)| tid)
+            .collect();
+        let requested = match args.first() {
+            Some(&"all") => None,
+            Some(tid) => match tid.parse::<u64>() {
+                Ok(tid) => Some(tid),
+                Err(e) => {
+                    self.fail(&format!("Invalid thread ID: {}", e));
+                    return true;
+                }
+            },
+            None => self.stopped_thread(),
+        };
+        let threads = match requested {
+            Some(tid) if !stopped.contains(&tid) => {
+                self.fail(&format!("Thread {} is not stopped", tid));
+                return true;
+            }
+            Some(tid) => vec![tid],
+            None => stopped,
+        };
+        if threads.is_empty() {
+            self.fail("No thread is stopped at a break hook");
+            return true;
+        }
+
+        for tid in threads {
+            match self.resume_thread(tid) {
+                Ok(()) => println!(
+                    "{} Thread {} resumed",
+                    "[BREAK]".green(),
+                    tid.to_string().yellow()
+                ),
+                Err(e) => self.fail(&format!("Failed to resume thread {}: {}", tid, e)),
+            }
+        }
+        true
+    }
+
+    pub(crate) fn break_args(&mut self, args: &[&str]) -> bool {
+        let Some(thread_id) = self.stopped_thread() else {
+            self.fail("No thread is stopped at a break hook");
+            return true;
+        };
+
+        if let Some(index) = args.first() {
+            let Some(value) = args.get(1) else {
+                self.fail("Usage: args <index> <value>");
+                return true;
+            };
+            let index = match Self::parse_usize(index) {
+                Ok(index) => index,
+                Err(e) => {
+                    self.fail(&format!("Invalid argument index: {}", e));
+                    return true;
+                }
+            };
+            let value = match self.register_operand(value) {
+                Ok(value) => value,
+                Err(e) => {
+                    self.fail(&format!("Invalid value: {}", e));
+                    return true;
+                }
+            };
+            let result = self.script.exports.call(
+                "break_set_arg",
+                Some(json!([thread_id, index, format!("{:#x}", value)])),
+            );
+            match result {
+                Ok(Some(result))
+                    if result.get("success").and_then(|v| v.as_bool()) == Some(true) =>
+                {
+                    if self.json_output() {
+                        self.emit_json(json!({"index": index, "value": format!("{:#x}", value)}));
+                    } else {
+                        println!(
+                            "{} [{}] = {}",
+                            "[ARGS]".green(),
+                            index,
+                            format!("{:#x}", value).yellow()
+                        )
+                    }
+                }
+                Ok(Some(result)) => self.fail(&format!(
+                    "Failed to set argument: {}",
+                    result
+                        .get("error")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("Unknown error")
+                )),
+                Ok(None) => self.fail("No response from break_set_arg"),
+                Err(e) => self.fail(&format!("Argument write error: {}", e)),
+            }
+            return true;
+        }
+
+        let result = self
+            .script
+            .exports
+            .call("break_args", Some(json!([thread_id])));
+        match result {
+            Ok(Some(value)) if self.json_output() => self.emit_json(json!({
+                "type": "Args",
+                "thread": thread_id,
+                "args": value,
+            })),
+            Ok(Some(value)) => {
+                let values: Vec<EventValue> = serde_json::from_value(value).unwrap_or_default();
+                println!(
+                    "{} Thread {} arguments:",
+                    "[ARGS]".cyan(),
+                    thread_id.to_string().yellow()
+                );
+                for (i, value) in values.iter().enumerate() {
+                    println!("  [{}] {}", i, value.render());
+                }
+            }
+            Ok(None) => self.fail("No response from break_args"),
+            Err(e) => self.fail(&format!("Argument read error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn thread_setreg(&mut self, args: &[&str]) -> bool {
+        let Some(thread_id) = self.stopped_thread() else {
+            self.fail("No thread is stopped at a break hook");
+            return true;
+        };
+        let name = args[0];
+        let value = match self.register_operand(args[1]) {
+            Ok(value) => value,
+            Err(e) => {
+                self.fail(&format!("Invalid value: {}", e));
+                return true;
+            }
+        };
+
+        let result = self.script.exports.call(
+            "break_set_reg",
+            Some(json!([thread_id, name, format!("{:#x}", value)])),
+        );
+        match result {
+            Ok(Some(result)) if result.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                println!(
+                    "{} {} = {}",
+                    "[REGS]".green(),
+                    name.cyan(),
+                    format!("{:#x}", value).yellow()
+                )
+            }
+            Ok(Some(result)) => self.fail(&format!(
+                "Failed to set register: {}",
+                result
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from break_set_reg"),
+            Err(e) => self.fail(&format!("Register write error: {}", e)),
+        }
+        true
+    }
+
+    // ========================================================================
+    // Stalker Commands
+    // ========================================================================
+
+    pub(crate) fn stalk_start(&mut self, args: &[&str]) -> bool {
+        if args.is_empty() {
+            return self.stalk_list(args);
+        }
+        let mut thread_id = None;
+        let mut mode = None;
+        let mut module = None;
+        let mut iter = args.iter();
+        while let Some(&arg) = iter.next() {
+            match arg {
+                "--calls" | "--blocks" | "--insns" => {
+                    let flag = &arg[2..];
+                    if mode.is_some_and(|m| m != flag) {
+                        self.fail("Use only one of --calls, --blocks and --insns");
+                        return true;
+                    }
+                    mode = Some(flag);
+                }
+                "-m" | "--module" => match iter.next() {
+                    Some(&name) => module = Some(name),
+                    None => {
+                        self.fail("--module requires a module name");
+                        return true;
+                    }
+                },
+                _ => match arg.parse::<u64>() {
+                    Ok(id) if thread_id.is_none() => thread_id = Some(id),
+                    _ => {
+                        self.fail(&format!("Unexpected argument: {}", arg));
+                        return true;
+                    }
+                },
+            }
+        }
+        let Some(thread_id) = thread_id else {
+            self.fail("Thread ID required, e.g. stalk 1234 --calls");
+            return true;
+        };
+        let mode = mode.unwrap_or("calls");
+
+        let result = self
+            .script
+            .exports
+            .call("stalk_follow", Some(json!([thread_id, mode, module])));
+        match result {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                let scope = module
+                    .map(|m| format!(" in {}", m.cyan()))
+                    .unwrap_or_default();
+                println!(
+                    "{} Stalking thread {} ({}{}); `stalk stop` ends it",
+                    "[STALK]".green(),
+                    thread_id.to_string().yellow(),
+                    mode,
+                    scope
+                );
+            }
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to stalk thread: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from stalk_follow"),
+            Err(e) => self.fail(&format!("Stalk error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn stalk_stop(&mut self, args: &[&str]) -> bool {
+        let thread_id = match args.first().map(|s| s.parse::<u64>()) {
+            Some(Ok(id)) => Some(id),
+            Some(Err(_)) => {
+                self.fail(&format!("Invalid thread ID: {}", args[0]));
+                return true;
+            }
+            None => None,
+        };
+        let result = self
+            .script
+            .exports
+            .call("stalk_unfollow", Some(json!([thread_id])));
+        match result {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                let threads: Vec<String> = value
+                    .get("threads")
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().map(|t| t.to_string()).collect())
+                    .unwrap_or_default();
+                if threads.is_empty() {
+                    println!("{}", "No threads are being stalked".dark_grey());
+                } else {
+                    println!(
+                        "{} Stopped stalking thread(s) {}",
+                        "[STALK]".green(),
+                        threads.join(", ").yellow()
+                    );
+                }
+            }
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to stop stalking: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from stalk_unfollow"),
+            Err(e) => self.fail(&format!("Stalk stop error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn stalk_list(&mut self, _args: &[&str]) -> bool {
+        let stalked = match self.script.exports.call("stalk_list", None) {
+            Ok(Some(Value::Array(stalked))) => stalked,
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                self.fail(&format!("Stalk list error: {}", e));
+                return true;
+            }
+        };
+        if self.json_output() {
+            for entry in stalked {
+                self.emit_json(entry);
+            }
+            return true;
+        }
+        if stalked.is_empty() {
+            println!("{}", "No threads are being stalked".dark_grey());
+            return true;
+        }
+        println!("{} Stalked threads: {}", "[STALK]".green(), stalked.len());
+        for entry in &stalked {
+            let thread_id = entry.get("threadId").and_then(|v| v.as_u64()).unwrap_or(0);
+            let mode = entry.get("mode").and_then(|v| v.as_str()).unwrap_or("?");
+            let scope = entry
+                .get("module")
+                .and_then(|v| v.as_str())
+                .map(|m| format!(" in {}", m))
+                .unwrap_or_default();
+            println!(
+                "  Thread {} {}",
+                thread_id.to_string().yellow(),
+                format!("({}{})", mode, scope).dark_grey()
+            );
+        }
+        true
+    }
+
+    // ========================================================================
+    // Coverage Commands
+    // ========================================================================
+
+    pub(crate) fn coverage_start(&mut self, args: &[&str]) -> bool {
+        let module = match args {
+            [] => None,
+            ["-m" | "--module", name] => Some(*name),
+            ["-m" | "--module"] => {
+                self.fail("--module requires a module name");
+                return true;
+            }
+            [other, ..] => {
+                self.fail(&format!("Unexpected argument: {}", other));
+                return true;
+            }
+        };
+        let result = self
+            .script
+            .exports
+            .call("coverage_start", Some(json!([module])));
+        match result {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                let threads = value.get("threads").and_then(|v| v.as_u64()).unwrap_or(0);
+                let scope = module
+                    .map(|m| format!(" in {}", m.cyan()))
+                    .unwrap_or_default();
+                println!(
+                    "{} Collecting block coverage{} on {} threads",
+                    "[COVERAGE]".green(),
+                    scope,
+                    threads.to_string().yellow()
+                );
+            }
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to start coverage: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from coverage_start"),
+            Err(e) => self.fail(&format!("Coverage error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn coverage_stop(&mut self, _args: &[&str]) -> bool {
+        match self.script.exports.call("coverage_stop", None) {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                let blocks = value.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+                println!(
+                    "{} Stopped; {} blocks collected. `coverage save <file>` writes them",
+                    "[COVERAGE]".green(),
+                    blocks.to_string().yellow()
+                );
+            }
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to stop coverage: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from coverage_stop"),
+            Err(e) => self.fail(&format!("Coverage stop error: {}", e)),
+        }
+        true
+    }
+
+    /// Save the blocks of the last `coverage start`, which may still be running
+    pub(crate) fn coverage_save(&mut self, args: &[&str]) -> bool {
+        let blocks: Vec<(u64, u64)> = match self.script.exports.call("coverage_blocks", None) {
+            Ok(Some(Value::Array(blocks))) => blocks
+                .iter()
+                .filter_map(|b| Some((string_to_u64(b.get(0)?.as_str()?), b.get(1)?.as_u64()?)))
+                .collect(),
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                self.fail(&format!("Failed to read coverage: {}", e));
+                return true;
+            }
+        };
+        if blocks.is_empty() {
+            self.fail("No coverage collected; start with `coverage start`");
+            return true;
+        }
+        let modules = match self.script.exports.call("list_modules", Some(json!([[]]))) {
+            Ok(Some(modules)) => coverage::modules_from_json(&modules),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                self.fail(&format!("Failed to list modules: {}", e));
+                return true;
+            }
+        };
+        let coverage = Coverage::new(modules, &blocks);
+        match coverage.save(std::path::Path::new(args[0])) {
+            Ok(()) => println!(
+                "{} Saved {} blocks in {} modules to {}",
+                "[COVERAGE]".green(),
+                coverage.blocks.len().to_string().yellow(),
+                coverage.modules_hit(),
+                args[0]
+            ),
+            Err(e) => self.fail(&e),
+        }
+        true
+    }
+
+    /// Compare two drcov files, symbolizing the blocks only one of them hit
+    /// against the modules loaded now. The functions involved replace Field.
+    pub(crate) fn coverage_diff(&mut self, args: &[&str]) -> bool {
+        let (name_a, name_b) = (args[0], args[1]);
+        let runs = Coverage::load(std::path::Path::new(name_a))
+            .and_then(|a| Coverage::load(std::path::Path::new(name_b)).map(|b| (a, b)));
+        let (a, b) = match runs {
+            Ok(runs) => runs,
+            Err(e) => {
+                self.fail(&e);
+                return true;
+            }
+        };
+        let diff = coverage::diff(&a, &b);
+        let (keys_a, keys_b) = (a.keys(), b.keys());
+        let bases: HashMap<String, u64> =
+            match self.script.exports.call("list_modules", Some(json!([[]]))) {
+                Ok(Some(modules)) => coverage::modules_from_json(&modules)
+                    .into_iter()
+                    .map(|m| (m.name, m.base))
+                    .collect(),
+                Ok(None) => HashMap::new(),
+                Err(e) => {
+                    self.fail(&format!("Failed to list modules: {}", e));
+                    return true;
+                }
+            };
+
+        // Differing blocks per function, keyed by module and symbol name
+        struct Differing {
+            function: VzFunction,
+            base: u64,
+            blocks_a: usize,
+            blocks_b: usize,
+        }
+        let mut functions: BTreeMap<(String, String), Differing> = BTreeMap::new();
+        let mut unknown = 0;
+        // (base, address, in_a) of every differing block in a loaded module
+        let mut located = Vec::new();
+        let sides = [(&diff.only_a, true), (&diff.only_b, false)];
+        for (blocks, in_a) in sides {
+            for (module, offset) in blocks {
+                match bases.get(module) {
+                    Some(&base) => located.push((base, base + *offset as u64, in_a)),
+                    None => unknown += 1,
+                }
+            }
+        }
+        let addresses: Vec<u64> = located.iter().map(|&(_, address, _)| address).collect();
+        let found = self.functions_at(&addresses);
+        for ((base, _, in_a), function) in located.into_iter().zip(found) {
+            let Some(function) = function else {
+                unknown += 1;
+                continue;
+            };
+            let entry = functions
+                .entry((function.module.clone(), function.name.clone()))
+                .or_insert(Differing {
+                    function,
+                    base,
+                    blocks_a: 0,
+                    blocks_b: 0,
+                });
+            if in_a {
+                entry.blocks_a += 1;
+            } else {
+                entry.blocks_b += 1;
+            }
+        }
+
+        let mut grouped: [Vec<(VzFunction, usize, usize)>; 3] = Default::default();
+        for (_, d) in functions {
+            let entry = d
+                .function
+                .address
+                .checked_sub(d.base)
+                .map(|offset| (d.function.module.clone(), offset as u32));
+            let hit = |keys: &BTreeSet<coverage::BlockKey>| {
+                entry.as_ref().is_some_and(|e| keys.contains(e))
+            };
+            let side = coverage::function_side(
+                (hit(&keys_a), hit(&keys_b)),
+                (d.blocks_a > 0, d.blocks_b > 0),
+            );
+            let (index, note) = match side {
+                DiffSide::OnlyA => (0, format!("only in {}", name_a)),
+                DiffSide::OnlyB => (1, format!("only in {}", name_b)),
+                DiffSide::Both => (2, "run by both".to_string()),
+            };
+            let mut function = d.function;
+            function.base.comment = Some(note);
+            grouped[index].push((function, d.blocks_a, d.blocks_b));
+        }
+
+        if self.json_output() {
+            for (function, blocks_a, blocks_b) in grouped.iter().flatten() {
+                let mut entry = json!(function);
+                entry["blocksA"] = json!(blocks_a);
+                entry["blocksB"] = json!(blocks_b);
+                self.emit_json(entry);
+            }
+        } else {
+            println!(
+                "{} {}: {} blocks, {}: {} blocks; {} only in {}, {} only in {}",
+                "[COVERAGE]".green(),
+                name_a,
+                a.blocks.len(),
+                name_b,
+                b.blocks.len(),
+                diff.only_a.len().to_string().yellow(),
+                name_a,
+                diff.only_b.len().to_string().yellow(),
+                name_b
+            );
+            let headings = [
+                format!("Functions only in {}:", name_a),
+                format!("Functions only in {}:", name_b),
+                "Functions both ran, with blocks only one hit:".to_string(),
+            ];
+            for (heading, functions) in headings.iter().zip(&grouped) {
+                if functions.is_empty() {
+                    continue;
+                }
+                println!("{}", heading.clone().cyan());
+                for (function, blocks_a, blocks_b) in functions {
+                    println!(
+                        "  {}!{} @ {} {}",
+                        function.module,
+                        function.name,
+                        format!("{:#x}", function.address).yellow(),
+                        format!("(+{} / +{} blocks)", blocks_a, blocks_b).dark_grey()
+                    );
+                }
+            }
+            if unknown > 0 {
+                println!(
+                    "{}",
+                    format!("{} blocks are outside known functions", unknown).dark_grey()
+                );
+            }
+        }
+
+        let datas: Vec<VzData> = grouped
+            .into_iter()
+            .flatten()
+            .map(|(function, _, _)| VzData::Function(function))
+            .collect();
+        if !datas.is_empty() {
+            self.field.clear_data();
+            self.field.add_datas(datas);
+        }
+        true
+    }
+
+    // ========================================================================
+    // Native Call Commands
+    // ========================================================================
+
+    pub(crate) fn call(&mut self, args: &[&str]) -> bool {
+        let Some(&arg0) = args.first() else {
+            self.fail("Usage: call <target> [args...]");
+            return true;
+        };
+        let address = match self.resolve_target_address(arg0) {
+            Ok(addr) => addr,
+            Err(e) => {
+                self.fail(&format!("Failed to resolve target: {}", e));
+                return true;
+            }
+        };
+
+        let mut sig: Option<Signature> = None;
+        let mut abi: Option<&str> = None;
+        let mut values: Vec<(&str, bool)> = Vec::new();
+        let mut options = args.iter().enumerate().skip(1);
+        while let Some((i, arg)) = options.next() {
+            match *arg {
+                "-s" | "--sig" | "--abi" => {
+                    let Some((_, value)) = options.next() else {
+                        self.fail(&format!("{} requires a value", arg));
+                        return true;
+                    };
+                    if *arg == "--abi" {
+                        abi = Some(value);
+                        continue;
+                    }
+                    match signature::parse_signature(value) {
+                        Ok(parsed) => sig = Some(parsed),
+                        Err(e) => {
+                            self.fail(&format!("Invalid signature: {}", e));
+                            return true;
+                        }
+                    }
+                }
+                value => values.push((value, self.quoted.get(i).copied().unwrap_or(false))),
+            }
+        }
+
+        let sig = sig.or_else(|| self.signature_at(Some(address), arg0));
+        if let Some(sig) = &sig {
+            // Extra arguments are allowed for variadic functions
+            if values.len() < sig.args.len() {
+                self.fail(&format!(
+                    "{} takes {} arguments, got {}",
+                    sig.name,
+                    sig.args.len(),
+                    values.len()
+                ));
+                return true;
+            }
+        }
+        let mut call_args = Vec::new();
+        for (i, (value, quoted)) in values.iter().enumerate() {
+            let spec = sig.as_ref().and_then(|s| s.args.get(i));
+            match self.call_argument(value, *quoted, spec) {
+                Ok(arg) => call_args.push(arg),
+                Err(e) => {
+                    self.fail(&format!("Invalid argument {}: {}", i, e));
+                    return true;
+                }
+            }
+        }
+        let ret = sig.as_ref().and_then(|s| s.ret.as_ref());
+        let ret_type = match (&sig, ret) {
+            (Some(_), None) => "void",
+            (_, Some(ret)) => ret.native_type(),
+            (None, None) => "pointer",
+        };
+
+        let mut config = json!({"retType": ret_type, "args": call_args, "ret": ret});
+        if let Some(abi) = abi {
+            config["abi"] = json!(abi);
+        }
+        let result = self.script.exports.call(
+            "call_function",
+            Some(json!([format!("{:#x}", address), config])),
+        );
+        match result {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                let mut retval: Option<EventValue> = value
+                    .get("retval")
+                    .and_then(|v| serde_json::from_value(v.clone()).ok());
+                if let Some(retval) = &retval {
+                    let plain = retval.decoded.as_ref().unwrap_or(&retval.value);
+                    self.vars.set_last_value(plain.clone());
+                }
+                if self.json_output() {
+                    self.emit_json(json!({
+                        "address": format!("{:#x}", address),
+                        "retval": retval,
+                    }));
+                    return true;
+                }
+                let returned = match retval.as_mut() {
+                    Some(retval) => {
+                        retval.name = None;
+                        retval.render()
+                    }
+                    None => "void".dark_grey().to_string(),
+                };
+                println!(
+                    "{} {} @ {} returned {}",
+                    "[CALL]".green(),
+                    arg0,
+                    format!("{:#x}", address).yellow(),
+                    returned
+                );
+            }
+            Ok(Some(value)) => {
+                let error = value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error");
+                self.fail(&format!("Call failed: {}", error));
+            }
+            Ok(None) => self.fail("No response from call_function"),
+            Err(e) => self.fail(&format!("Call error: {}", e)),
+        }
+        true
+    }
+
+    /// Marshal one `call` argument for the agent. String parameters take the
+    /// text itself and floating-point ones a number; anything else is an
+    /// address expression. Without a parameter type, text that does not
+    /// resolve is passed as a string. `NULL` and store selectors are always
+    /// pointers. Quoted text is never resolved: it is a string, or a number
+    /// for floating-point parameters.
+    fn call_argument(
+        &mut self,
+        text: &str,
+        quoted: bool,
+        spec: Option<&ArgSpec>,
+    ) -> Result<Value, String> {
+        if quoted {
+            return match spec.map(|s| (&s.value_type, s.native_type())) {
+                Some((VzValueType::Utf16, _)) => Ok(json!({"type": "utf16", "value": text})),
+                Some((_, native @ ("float" | "double"))) => text
+                    .parse::<f64>()
+                    .map(|f| json!({"type": native, "value": f}))
+                    .map_err(|e| format!("{}: {}", text, e)),
+                _ => Ok(json!({"type": "string", "value": text})),
+            };
+        }
+        if text == "NULL" {
+            return Ok(json!({"type": "pointer", "value": "0x0"}));
+        }
+        let pointer = |n: u64| json!({"type": "pointer", "value": format!("{:#x}", n)});
+        // Store entries, e.g. `alloc string` buffers, pass their address
+        if text.contains(':') {
+            if let Some(address) = self
+                .selector(text)
+                .ok()
+                .and_then(|data| data.first().and_then(|d| get_address_from_data(d)))
+            {
+                return Ok(pointer(address));
+            }
+        }
+        match spec {
+            Some(spec) => match (&spec.value_type, spec.native_type()) {
+                (VzValueType::String | VzValueType::Utf8, _) => {
+                    Ok(json!({"type": "string", "value": text}))
+                }
+                (VzValueType::Utf16, _) => Ok(json!({"type": "utf16", "value": text})),
+                (_, "pointer") => self.register_operand(text).map(pointer),
+                (_, native) => text
+                    .parse::<f64>()
+                    .map(|f| json!({"type": native, "value": f}))
+                    .map_err(|e| format!("{}: {}", text, e)),
+            },
+            None => Ok(match self.register_operand(text) {
+                Ok(n) => pointer(n),
+                Err(_) => json!({"type": "string", "value": text}),
+            }),
+        }
+    }
+
+    // ========================================================================
+    // Allocation Commands
+    // ========================================================================
+
+    pub(crate) fn alloc(&mut self, args: &[&str]) -> bool {
+        let Some(&size) = args.first() else {
+            return self.alloc_list(args);
+        };
+        let size = match Self::parse_usize(size) {
+            Ok(0) => {
+                self.fail("Allocation size must be at least 1");
+                return true;
+            }
+            Ok(size) => size,
+            Err(e) => {
+                self.fail(&format!("Invalid size: {}", e));
+                return true;
+            }
+        };
+        let protection = match args.get(1).copied() {
+            Some("-p" | "--prot") => match args.get(2).map(|p| Self::parse_protection(p)) {
+                Some(Ok(protection)) => Some(protection),
+                Some(Err(e)) => {
+                    self.fail(&e);
+                    return true;
+                }
+                None => {
+                    self.fail("--prot requires a value");
+                    return true;
+                }
+            },
+            Some(other) => {
+                self.fail(&format!("Unknown option: {}", other));
+                return true;
+            }
+            None => None,
+        };
+        self.allocate("mem_alloc", json!([size, protection]), VzValueType::Bytes);
+        true
+    }
+
+    pub(crate) fn alloc_string(&mut self, args: &[&str]) -> bool {
+        let text = args.join(" ");
+        self.allocate(
+            "mem_alloc_string",
+            json!([text, "utf8"]),
+            VzValueType::String,
+        );
+        true
+    }
+
+    pub(crate) fn alloc_utf16(&mut self, args: &[&str]) -> bool {
+        let text = args.join(" ");
+        self.allocate(
+            "mem_alloc_string",
+            json!([text, "utf16"]),
+            VzValueType::Utf16,
+        );
+        true
+    }
+
+    pub(crate) fn alloc_list(&mut self, _args: &[&str]) -> bool {
+        match self.script.exports.call("mem_allocations", None) {
+            Ok(Some(Value::Array(allocations))) => {
+                let pointers = allocations
+                    .iter()
+                    .filter_map(|a| Self::allocation_pointer(a, None))
+                    .map(VzData::Pointer)
+                    .collect();
+                self.field.clear_data();
+                self.field.add_datas(pointers);
+                self.show_results(&self.field);
+            }
+            Ok(_) => self.fail("No response from mem_allocations"),
+            Err(e) => self.fail(&format!("Allocation list error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn free(&mut self, args: &[&str]) -> bool {
+        let Some(&target) = args.first() else {
+            self.fail("Usage: free <id|selector>");
+            return true;
+        };
+        let allocations = match self.script.exports.call("mem_allocations", None) {
+            Ok(Some(Value::Array(allocations))) => allocations,
+            Ok(_) => {
+                self.fail("No response from mem_allocations");
+                return true;
+            }
+            Err(e) => {
+                self.fail(&format!("Allocation list error: {}", e));
+                return true;
+            }
+        };
+        let allocations: Vec<VzPointer> = allocations
+            .iter()
+            .filter_map(|a| Self::allocation_pointer(a, None))
+            .collect();
+        // A selected Field or Lib entry names its allocation by address, as
+        // its label may have been changed since
+        let found = if target.contains(':') {
+            let address = match self.selector(target) {
+                Ok(data) => data.first().and_then(|d| get_address_from_data(d)),
+                Err(e) => {
+                    self.fail(&e);
+                    return true;
+                }
+            };
+            allocations
+                .iter()
+                .find(|a| Some(a.address) == address)
+                .ok_or_else(|| format!("Not an allocation: {}", target))
+        } else {
+            allocations
+                .iter()
+                .find(|a| a.base.label.as_deref() == Some(target))
+                .ok_or_else(|| format!("Allocation not found: {}", target))
+        };
+        let (id, address) = match found {
+            Ok(allocation) => (
+                allocation.base.label.clone().unwrap_or_default(),
+                allocation.address,
+            ),
+            Err(e) => {
+                self.fail(&e);
+                return true;
+            }
+        };
+
+        match self.script.exports.call("mem_free", Some(json!([id]))) {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                // Field entries would otherwise point at freed memory
+                let stale: Vec<usize> = (0..self.field.data.len())
+                    .filter(|&i| match &self.field.data[i] {
+                        VzData::Pointer(p) => p.address == address,
+                        _ => false,
+                    })
+                    .collect();
+                for index in stale.into_iter().rev() {
+                    let _ = self.field.remove_data(index, 1);
+                }
+                println!("{} Freed {}", "[ALLOC]".green(), id.cyan());
+            }
+            Ok(Some(value)) => {
+                let error = value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error");
+                self.fail(&format!("Failed to free {}: {}", id, error));
+            }
+            Ok(None) => self.fail("No response from mem_free"),
+            Err(e) => self.fail(&format!("Free error: {}", e)),
+        }
+        true
+    }
+
+    /// Allocate through the agent's `rpc`, report the allocation and append
+    /// it to Field
+    fn allocate(&mut self, rpc: &str, args: Value, value_type: VzValueType) {
+        let value = match self.script.exports.call(rpc, Some(args)) {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                self.fail(&format!("No response from {}", rpc));
+                return;
+            }
+            Err(e) => {
+                self.fail(&format!("Allocation error: {}", e));
+                return;
+            }
+        };
+        if value.get("success").and_then(|v| v.as_bool()) != Some(true) {
+            let error = value
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown error");
+            self.fail(&format!("Failed to allocate: {}", error));
+            return;
+        }
+        let Some(pointer) = Self::allocation_pointer(&value, Some(value_type)) else {
+            self.fail("Invalid allocation response");
+            return;
+        };
+        self.vars.push_address(pointer.address);
+        if self.json_output() {
+            self.emit_json(json!(VzData::Pointer(pointer.clone())));
+        } else {
+            println!(
+                "{} {} @ {} {}",
+                "[ALLOC]".green(),
+                pointer.base.label.clone().unwrap_or_default().cyan(),
+                format!("{:#x}", pointer.address).yellow(),
+                format!(
+                    "({:#x} bytes, {})",
+                    pointer.size,
+                    value
+                        .get("protection")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("?")
+                )
+                .dark_grey()
+            );
+        }
+        self.field.add_datas(vec![VzData::Pointer(pointer)]);
+    }
+
+    /// Field entry for an allocation reported by the agent, labelled with its
+    /// ID. The value type follows the allocation kind unless given.
+    fn allocation_pointer(info: &Value, value_type: Option<VzValueType>) -> Option<VzPointer> {
+        let value_type =
+            value_type.unwrap_or_else(|| match info.get("kind").and_then(|v| v.as_str()) {
+                Some("string") => VzValueType::String,
+                Some("utf16") => VzValueType::Utf16,
+                _ => VzValueType::Bytes,
+            });
+        let mut base = new_base(VzDataType::Pointer);
+        base.label = Some(info.get("id")?.as_str()?.to_string());
+        Some(VzPointer {
+            base,
+            address: string_to_u64(info.get("address")?.as_str()?),
+            size: info.get("size")?.as_u64()? as usize,
+            value_type,
+        })
+    }
+
+    /// `rwx`-style protection as Frida spells it, e.g. `rx` becomes `r-x`
+    fn parse_protection(s: &str) -> Result<String, String> {
+        if s.is_empty() || s.len() > 3 || !s.chars().all(|c| matches!(c, 'r' | 'w' | 'x' | '-')) {
+            return Err(format!(
+                "Invalid protection: {} (expected e.g. rwx, r-x)",
+                s
+            ));
+        }
+        Ok(['r', 'w', 'x']
+            .iter()
+            .map(|&c| if s.contains(c) { c } else { '-' })
+            .collect())
+    }
+
+    // ========================================================================
+    // Watchpoint Commands
+    // ========================================================================
+
+    pub(crate) fn watch(&mut self, args: &[&str]) -> bool {
+        let (target, size) = match args {
+            [] => return self.watch_list(args),
+            [_] => {
+                self.fail("Size required, e.g. watch 0x5000 4 --write");
+                return true;
+            }
+            [target, size, ..] => (*target, *size),
+        };
+        let mut operations = Vec::new();
+        for flag in &args[2..] {
+            match watch::operation(flag) {
+                Some(op) if !operations.contains(&op) => operations.push(op),
+                Some(_) => {}
+                None => {
+                    self.fail(&format!("Unknown option: {}", flag));
+                    return true;
+                }
+            }
+        }
+        // Like a debugger's `watch`, only writes unless told otherwise
+        if operations.is_empty() {
+            operations.push("write");
+        }
+        let size = match Self::parse_usize(size) {
+            Ok(0) => {
+                self.fail("Watch size must be at least 1");
+                return true;
+            }
+            Ok(size) => size,
+            Err(e) => {
+                self.fail(&format!("Invalid size: {}", e));
+                return true;
+            }
+        };
+        let address = match self.resolve_target_address(target) {
+            Ok(address) => address,
+            Err(e) => {
+                self.fail(&e);
+                return true;
+            }
+        };
+
+        let result = self.script.exports.call(
+            "watch_add",
+            Some(json!([format!("{:#x}", address), size, operations])),
+        );
+        match result {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                let id = value.get("id").and_then(|v| v.as_str()).unwrap_or("?");
+                let pages = value.get("pages").and_then(|v| v.as_u64()).unwrap_or(0);
+                println!(
+                    "{} {} watching {} bytes @ {} for {} ({} pages monitored)",
+                    "[WATCH]".green(),
+                    id.cyan(),
+                    size,
+                    format!("{:#x}", address).yellow(),
+                    operations.join("/"),
+                    pages
+                );
+            }
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to add watchpoint: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from watch_add"),
+            Err(e) => self.fail(&format!("Watch error: {}", e)),
+        }
+        true
+    }
+
+    pub(crate) fn watch_list(&mut self, _args: &[&str]) -> bool {
+        let watches = match self.script.exports.call("watch_list", None) {
+            Ok(Some(Value::Array(watches))) => watches,
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                self.fail(&format!("Watch list error: {}", e));
+                return true;
+            }
+        };
+        if self.json_output() {
+            for watch in watches {
+                self.emit_json(watch);
+            }
+            return true;
+        }
+        if watches.is_empty() {
+            println!("{}", "No watchpoints".dark_grey());
+            return true;
+        }
+        println!("{} Watchpoints: {}", "[WATCH]".green(), watches.len());
+        for watch in &watches {
+            let field = |key| watch.get(key).and_then(|v| v.as_str()).unwrap_or("?");
+            let size = watch.get("size").and_then(|v| v.as_u64()).unwrap_or(0);
+            let hits = watch.get("hits").and_then(|v| v.as_u64()).unwrap_or(0);
+            let operations: Vec<&str> = watch
+                .get("operations")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            println!(
+                "  {} {} {} {} hits",
+                field("id").cyan(),
+                field("address").yellow(),
+                format!("({} bytes, {})", size, operations.join("/")).dark_grey(),
+                hits
+            );
+        }
+        true
+    }
+
+    pub(crate) fn watch_remove(&mut self, args: &[&str]) -> bool {
+        let id = args[0];
+        match self.script.exports.call("watch_remove", Some(json!([id]))) {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                println!("{} Watchpoint removed: {}", "[WATCH]".green(), id.cyan());
+            }
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to remove watchpoint: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from watch_remove"),
+            Err(e) => self.fail(&format!("Watch remove error: {}", e)),
         }
         true
     }
 
-    pub(crate) fn hook_list(&mut self, _args: &[&str]) -> bool {
-        let result = self.script.exports.call("hook_list", None);
-
-        match result {
-            Ok(Some(value)) => {
-                if let Some(hooks) = value.as_array() {
-                    if hooks.is_empty() {
-                        println!("{}", "No active hooks".dark_grey());
-                    } else {
-                        println!("{} Active hooks: {}", "[HOOKS]".green(), hooks.len());
-                        for hook in hooks {
-                            let id = hook.get("id").and_then(|v| v.as_str()).unwrap_or("?");
-                            let address =
-                                hook.get("address").and_then(|v| v.as_str()).unwrap_or("?");
-                            let enabled = hook
-                                .get("enabled")
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-                            let status = if enabled {
-                                "enabled".green()
-                            } else {
-                                "disabled".dark_grey()
-                            };
-
-                            let config = hook.get("config");
-                            let on_enter = config
-                                .and_then(|c| c.get("onEnter"))
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-                            let on_leave = config
-                                .and_then(|c| c.get("onLeave"))
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-                            let log_args = config
-                                .and_then(|c| c.get("logArgs"))
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-                            let log_retval = config
-                                .and_then(|c| c.get("logRetval"))
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-
-                            let flags = format!(
-                                "{}{}{}{}",
-                                if on_enter { "E" } else { "-" },
-                                if on_leave { "L" } else { "-" },
-                                if log_args { "A" } else { "-" },
-                                if log_retval { "R" } else { "-" }
-                            );
+    // ========================================================================
+    // Freeze Commands
+    // ========================================================================
 
-                            println!(
-                                "  {} @ {} [{}] ({})",
-                                id.cyan(),
-                                address.yellow(),
-                                flags.dark_grey(),
-                                status
-                            );
-                        }
+    pub(crate) fn freeze(&mut self, args: &[&str]) -> bool {
+        let (target, value_str) = match args {
+            [] => return self.freeze_list(args),
+            [_] => {
+                self.fail("Value required, e.g. freeze field:0 100 int");
+                return true;
+            }
+            [target, value, ..] => (*target, *value),
+        };
+        let mut value_type = None;
+        let mut interval = None;
+        let mut on_write = false;
+        let mut rest = args[2..].iter();
+        while let Some(&arg) = rest.next() {
+            match arg {
+                "--on-write" => on_write = true,
+                "-i" | "--interval" => match rest.next().map(|ms| Self::parse_usize(ms)) {
+                    Some(Ok(ms)) if ms > 0 => interval = Some(ms),
+                    _ => {
+                        self.fail("--interval requires a number of milliseconds");
+                        return true;
+                    }
+                },
+                _ if value_type.is_none() => match parse_value_type(arg) {
+                    Ok(parsed) => value_type = Some(parsed),
+                    Err(_) => {
+                        self.fail(&format!("Unknown type or option: {}", arg));
+                        return true;
                     }
+                },
+                _ => {
+                    self.fail(&format!("Unexpected argument: {}", arg));
+                    return true;
                 }
             }
-            Ok(None) => println!("{}", "No active hooks".dark_grey()),
-            Err(e) => logger::error(&format!("Hook list error: {}", e)),
         }
-        true
-    }
-
-    pub(crate) fn hook_enable(&mut self, args: &[&str]) -> bool {
-        let id = match args.get(0) {
-            Some(id) => *id,
-            None => {
-                logger::error("Hook ID required");
+        if on_write && interval.is_some() {
+            self.fail("Use either --interval or --on-write");
+            return true;
+        }
+        // The same default as `write`
+        let value_type = value_type.unwrap_or(VzValueType::Byte);
+        let (writer, value) = match typed_writer(value_str, &value_type) {
+            Ok(writer) => writer,
+            Err(e) => {
+                self.fail(&e);
+                return true;
+            }
+        };
+        let address = match self.resolve_target_address(target) {
+            Ok(address) => address,
+            Err(e) => {
+                self.fail(&format!("Invalid address: {}", e));
                 return true;
             }
         };
 
-        let result = self.script.exports.call("hook_enable", Some(json!([id])));
-
+        let config = json!({
+            "size": written_size(&value, &value_type),
+            "type": value_type.to_string(),
+            "text": value_str,
+            "interval": interval,
+            "onWrite": on_write,
+        });
+        let result = self.script.exports.call(
+            "freeze_add",
+            Some(json!([format!("{:#x}", address), writer, value, config])),
+        );
         match result {
-            Ok(Some(value)) => {
-                if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
-                    if success {
-                        println!("{} Hook enabled: {}", "[HOOK]".green(), id.cyan());
-                    } else {
-                        let error = value
-                            .get("error")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to enable hook: {}", error));
-                    }
-                }
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                println!(
+                    "{} {} {} {} = {} ({})",
+                    "[FREEZE]".green(),
+                    value
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("?")
+                        .cyan(),
+                    format!("{:#x}", address).yellow(),
+                    format!("[{}]", value_type).blue(),
+                    value_str,
+                    Self::freeze_schedule(&value)
+                );
             }
-            Ok(None) => logger::error("No response from hook_enable"),
-            Err(e) => logger::error(&format!("Hook enable error: {}", e)),
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to freeze value: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from freeze_add"),
+            Err(e) => self.fail(&format!("Freeze error: {}", e)),
         }
         true
     }
 
-    pub(crate) fn hook_disable(&mut self, args: &[&str]) -> bool {
-        let id = match args.get(0) {
-            Some(id) => *id,
-            None => {
-                logger::error("Hook ID required");
+    pub(crate) fn freeze_list(&mut self, _args: &[&str]) -> bool {
+        let freezes = match self.script.exports.call("freeze_list", None) {
+            Ok(Some(Value::Array(freezes))) => freezes,
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                self.fail(&format!("Freeze list error: {}", e));
                 return true;
             }
         };
-
-        let result = self.script.exports.call("hook_disable", Some(json!([id])));
-
-        match result {
-            Ok(Some(value)) => {
-                if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
-                    if success {
-                        println!("{} Hook disabled: {}", "[HOOK]".green(), id.cyan());
-                    } else {
-                        let error = value
-                            .get("error")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to disable hook: {}", error));
-                    }
-                }
+        if self.json_output() {
+            for freeze in freezes {
+                self.emit_json(freeze);
             }
-            Ok(None) => logger::error("No response from hook_disable"),
-            Err(e) => logger::error(&format!("Hook disable error: {}", e)),
+            return true;
+        }
+        if freezes.is_empty() {
+            println!("{}", "No frozen values".dark_grey());
+            return true;
+        }
+        println!("{} Frozen values: {}", "[FREEZE]".green(), freezes.len());
+        for freeze in &freezes {
+            let field = |key| freeze.get(key).and_then(|v| v.as_str()).unwrap_or("?");
+            let writes = freeze.get("writes").and_then(|v| v.as_u64()).unwrap_or(0);
+            println!(
+                "  {} {} {} = {} {}",
+                field("id").cyan(),
+                field("address").yellow(),
+                format!("[{}]", field("type")).blue(),
+                field("value"),
+                format!("({}, {} writes)", Self::freeze_schedule(freeze), writes).dark_grey()
+            );
         }
         true
     }
 
-    pub(crate) fn hook_clear(&mut self, _args: &[&str]) -> bool {
-        let result = self.script.exports.call("hook_clear_all", None);
-
-        match result {
-            Ok(Some(value)) => {
-                if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
-                    if success {
-                        let count = value.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
-                        println!("{} Cleared {} hooks", "[HOOK]".green(), count);
-                    } else {
-                        let error = value
-                            .get("error")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to clear hooks: {}", error));
-                    }
-                }
+    pub(crate) fn unfreeze(&mut self, args: &[&str]) -> bool {
+        let Some(&id) = args.first() else {
+            self.fail("Usage: unfreeze <id>");
+            return true;
+        };
+        match self.script.exports.call("freeze_remove", Some(json!([id]))) {
+            Ok(Some(value)) if value.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                println!("{} Unfrozen: {}", "[FREEZE]".green(), id.cyan());
             }
-            Ok(None) => logger::error("No response from hook_clear_all"),
-            Err(e) => logger::error(&format!("Hook clear error: {}", e)),
+            Ok(Some(value)) => self.fail(&format!(
+                "Failed to unfreeze: {}",
+                value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown error")
+            )),
+            Ok(None) => self.fail("No response from freeze_remove"),
+            Err(e) => self.fail(&format!("Unfreeze error: {}", e)),
         }
         true
     }
 
+    /// When a freeze from `freeze_add`/`freeze_list` rewrites its value
+    fn freeze_schedule(freeze: &Value) -> String {
+        match freeze.get("interval").and_then(|v| v.as_u64()) {
+            Some(ms) => format!("every {} ms", ms),
+            None => "on write".to_string(),
+        }
+    }
+
     // ========================================================================
     // Disassembly Commands
     // ========================================================================
@@ -1488,7 +4149,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                     (addr, 20usize)
                 }
                 None => {
-                    logger::error("No address specified and navigator is empty");
+                    self.fail("No address specified and navigator is empty");
                     return true;
                 }
             }
@@ -1496,10 +4157,11 @@ impl<'a, 'b> Commander<'a, 'b> {
             let addr = match self.resolve_target_address(args[0]) {
                 Ok(a) => a,
                 Err(e) => {
-                    logger::error(&format!("Failed to resolve address: {}", e));
+                    self.fail(&format!("Failed to resolve address: {}", e));
                     return true;
                 }
             };
+            self.vars.push_address(addr);
             let count = args
                 .get(1)
                 .and_then(|s| Self::parse_usize(s).ok())
@@ -1508,7 +4170,7 @@ impl<'a, 'b> Commander<'a, 'b> {
         };
 
         if address == 0 {
-            logger::error("Invalid address: 0x0");
+            self.fail("Invalid address: 0x0");
             return true;
         }
 
@@ -1528,36 +4190,12 @@ impl<'a, 'b> Commander<'a, 'b> {
                             "[DISAS]".cyan(),
                             format!("{:#x}", address).yellow()
                         );
-                        for insn in instructions {
-                            let addr = insn.get("address").and_then(|v| v.as_str()).unwrap_or("?");
-                            let mnemonic =
-                                insn.get("mnemonic").and_then(|v| v.as_str()).unwrap_or("?");
-                            let op_str = insn.get("opStr").and_then(|v| v.as_str()).unwrap_or("");
-                            let bytes = insn
-                                .get("bytes")
-                                .and_then(|v| v.as_array())
-                                .map(|arr| {
-                                    arr.iter()
-                                        .filter_map(|b| b.as_u64())
-                                        .map(|b| format!("{:02x}", b))
-                                        .collect::<Vec<_>>()
-                                        .join(" ")
-                                })
-                                .unwrap_or_default();
-
-                            println!(
-                                "  {} {} {} {}",
-                                addr.yellow(),
-                                format!("{:<24}", bytes).dark_grey(),
-                                mnemonic.cyan(),
-                                op_str
-                            );
-                        }
+                        self.print_instructions(instructions);
                     }
                 }
             }
-            Ok(None) => logger::error("No response from disassemble"),
-            Err(e) => logger::error(&format!("Disassembly error: {}", e)),
+            Ok(None) => self.fail("No response from disassemble"),
+            Err(e) => self.fail(&format!("Disassembly error: {}", e)),
         }
         true
     }
@@ -1567,22 +4205,25 @@ impl<'a, 'b> Commander<'a, 'b> {
             match self.navigator.get_data() {
                 Some(data) => get_address_from_data(data).unwrap_or(0),
                 None => {
-                    logger::error("No address specified and navigator is empty");
+                    self.fail("No address specified and navigator is empty");
                     return true;
                 }
             }
         } else {
             match self.resolve_target_address(args[0]) {
-                Ok(a) => a,
+                Ok(a) => {
+                    self.vars.push_address(a);
+                    a
+                }
                 Err(e) => {
-                    logger::error(&format!("Failed to resolve address: {}", e));
+                    self.fail(&format!("Failed to resolve address: {}", e));
                     return true;
                 }
             }
         };
 
         if address == 0 {
-            logger::error("Invalid address: 0x0");
+            self.fail("Invalid address: 0x0");
             return true;
         }
 
@@ -1603,36 +4244,12 @@ impl<'a, 'b> Commander<'a, 'b> {
                             format!("{:#x}", address).yellow(),
                             instructions.len()
                         );
-                        for insn in instructions {
-                            let addr = insn.get("address").and_then(|v| v.as_str()).unwrap_or("?");
-                            let mnemonic =
-                                insn.get("mnemonic").and_then(|v| v.as_str()).unwrap_or("?");
-                            let op_str = insn.get("opStr").and_then(|v| v.as_str()).unwrap_or("");
-                            let bytes = insn
-                                .get("bytes")
-                                .and_then(|v| v.as_array())
-                                .map(|arr| {
-                                    arr.iter()
-                                        .filter_map(|b| b.as_u64())
-                                        .map(|b| format!("{:02x}", b))
-                                        .collect::<Vec<_>>()
-                                        .join(" ")
-                                })
-                                .unwrap_or_default();
-
-                            println!(
-                                "  {} {} {} {}",
-                                addr.yellow(),
-                                format!("{:<24}", bytes).dark_grey(),
-                                mnemonic.cyan(),
-                                op_str
-                            );
-                        }
+                        self.print_instructions(instructions);
                     }
                 }
             }
-            Ok(None) => logger::error("No response from disassemble_function"),
-            Err(e) => logger::error(&format!("Disassembly error: {}", e)),
+            Ok(None) => self.fail("No response from disassemble_function"),
+            Err(e) => self.fail(&format!("Disassembly error: {}", e)),
         }
         true
     }
@@ -1643,14 +4260,14 @@ impl<'a, 'b> Commander<'a, 'b> {
 
     pub(crate) fn patch_bytes(&mut self, args: &[&str]) -> bool {
         if args.len() < 2 {
-            logger::error("Usage: patch bytes <target> <hex_bytes>");
+            self.fail("Usage: patch bytes <target> <hex_bytes>");
             return true;
         }
 
         let address = match self.resolve_target_address(args[0]) {
             Ok(a) => a,
             Err(e) => {
-                logger::error(&format!("Failed to resolve address: {}", e));
+                self.fail(&format!("Failed to resolve address: {}", e));
                 return true;
             }
         };
@@ -1663,7 +4280,7 @@ impl<'a, 'b> Commander<'a, 'b> {
             .collect();
 
         if bytes.is_empty() {
-            logger::error("Invalid hex bytes");
+            self.fail("Invalid hex bytes");
             return true;
         }
 
@@ -1700,26 +4317,26 @@ impl<'a, 'b> Commander<'a, 'b> {
                             .get("error")
                             .and_then(|v| v.as_str())
                             .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to patch: {}", error));
+                        self.fail(&format!("Failed to patch: {}", error));
                     }
                 }
             }
-            Ok(None) => logger::error("No response from patch_bytes"),
-            Err(e) => logger::error(&format!("Patch error: {}", e)),
+            Ok(None) => self.fail("No response from patch_bytes"),
+            Err(e) => self.fail(&format!("Patch error: {}", e)),
         }
         true
     }
 
     pub(crate) fn patch_nop(&mut self, args: &[&str]) -> bool {
         if args.is_empty() {
-            logger::error("Usage: nop <target> [count]");
+            self.fail("Usage: nop <target> [count]");
             return true;
         }
 
         let address = match self.resolve_target_address(args[0]) {
             Ok(a) => a,
             Err(e) => {
-                logger::error(&format!("Failed to resolve address: {}", e));
+                self.fail(&format!("Failed to resolve address: {}", e));
                 return true;
             }
         };
@@ -1761,12 +4378,12 @@ impl<'a, 'b> Commander<'a, 'b> {
                             .get("error")
                             .and_then(|v| v.as_str())
                             .unwrap_or("Unknown error");
-                        logger::error(&format!("Failed to NOP: {}", error));
+                        self.fail(&format!("Failed to NOP: {}", error));
                     }
                 }
             }
-            Ok(None) => logger::error("No response from nop_instructions"),
-            Err(e) => logger::error(&format!("NOP error: {}", e)),
+            Ok(None) => self.fail("No response from nop_instructions"),
+            Err(e) => self.fail(&format!("NOP error: {}", e)),
         }
         true
     }
@@ -1774,7 +4391,7 @@ impl<'a, 'b> Commander<'a, 'b> {
     pub(crate) fn patch_restore(&mut self, args: &[&str]) -> bool {
         // This would need to maintain a history of patches to restore
         // For now, just show a message
-        logger::error("Patch restore not yet implemented. Save original bytes when patching.");
+        self.fail("Patch restore not yet implemented. Save original bytes when patching.");
         true
     }
 
@@ -1784,7 +4401,7 @@ impl<'a, 'b> Commander<'a, 'b> {
 
     pub(crate) fn scan_bytes(&mut self, args: &[&str]) -> bool {
         if args.is_empty() {
-            logger::error("Usage: scan bytes <pattern> [protection]");
+            self.fail("Usage: scan bytes <pattern> [protection]");
             return true;
         }
 
@@ -1829,15 +4446,15 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
-            Ok(None) => logger::error("No response from scan_pattern"),
-            Err(e) => logger::error(&format!("Scan error: {}", e)),
+            Ok(None) => self.fail("No response from scan_pattern"),
+            Err(e) => self.fail(&format!("Scan error: {}", e)),
         }
         true
     }
 
     pub(crate) fn scan_string(&mut self, args: &[&str]) -> bool {
         if args.is_empty() {
-            logger::error("Usage: scan string <text> [protection]");
+            self.fail("Usage: scan string <text> [protection]");
             return true;
         }
 
@@ -1879,15 +4496,15 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
-            Ok(None) => logger::error("No response from scan_string"),
-            Err(e) => logger::error(&format!("Scan error: {}", e)),
+            Ok(None) => self.fail("No response from scan_string"),
+            Err(e) => self.fail(&format!("Scan error: {}", e)),
         }
         true
     }
 
     pub(crate) fn scan_value(&mut self, args: &[&str]) -> bool {
         if args.len() < 2 {
-            logger::error("Usage: scan value <type> <value> [protection]");
+            self.fail("Usage: scan value <type> <value> [protection]");
             return true;
         }
 
@@ -1938,15 +4555,15 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
-            Ok(None) => logger::error("No response from scan_value"),
-            Err(e) => logger::error(&format!("Scan error: {}", e)),
+            Ok(None) => self.fail("No response from scan_value"),
+            Err(e) => self.fail(&format!("Scan error: {}", e)),
         }
         true
     }
 
     pub(crate) fn scan_next(&mut self, args: &[&str]) -> bool {
         if args.is_empty() {
-            logger::error("Usage: scan next <value> [comparison]");
+            self.fail("Usage: scan next <value> [comparison]");
             return true;
         }
 
@@ -1996,8 +4613,8 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
-            Ok(None) => logger::error("No response from scan_next"),
-            Err(e) => logger::error(&format!("Scan error: {}", e)),
+            Ok(None) => self.fail("No response from scan_next"),
+            Err(e) => self.fail(&format!("Scan error: {}", e)),
         }
         true
     }
@@ -2017,8 +4634,8 @@ impl<'a, 'b> Commander<'a, 'b> {
                     count.to_string().yellow()
                 );
             }
-            Ok(None) => logger::error("No response from scan_changed"),
-            Err(e) => logger::error(&format!("Scan error: {}", e)),
+            Ok(None) => self.fail("No response from scan_changed"),
+            Err(e) => self.fail(&format!("Scan error: {}", e)),
         }
         true
     }
@@ -2038,8 +4655,8 @@ impl<'a, 'b> Commander<'a, 'b> {
                     count.to_string().yellow()
                 );
             }
-            Ok(None) => logger::error("No response from scan_unchanged"),
-            Err(e) => logger::error(&format!("Scan error: {}", e)),
+            Ok(None) => self.fail("No response from scan_unchanged"),
+            Err(e) => self.fail(&format!("Scan error: {}", e)),
         }
         true
     }
@@ -2059,8 +4676,8 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
-            Ok(None) => logger::error("No response from scan_snapshot"),
-            Err(e) => logger::error(&format!("Snapshot error: {}", e)),
+            Ok(None) => self.fail("No response from scan_snapshot"),
+            Err(e) => self.fail(&format!("Snapshot error: {}", e)),
         }
         true
     }
@@ -2081,6 +4698,20 @@ impl<'a, 'b> Commander<'a, 'b> {
         );
 
         match result {
+            Ok(Some(value)) if self.json_output() => {
+                for (i, r) in value.as_array().into_iter().flatten().enumerate() {
+                    let address = r.get("address").and_then(|v| v.as_str()).unwrap_or("0");
+                    let mut entry = json!(VzData::ScanResult(VzScanResult {
+                        base: new_base(VzDataType::ScanResult),
+                        address: crate::gum::vzdata::string_to_u64(address),
+                        size: 4,
+                        value: r.get("value").map(|v| v.to_string()),
+                        pattern: None,
+                    }));
+                    entry["index"] = json!(offset + i);
+                    self.emit_json(entry);
+                }
+            }
             Ok(Some(value)) => {
                 if let Some(results) = value.as_array() {
                     if results.is_empty() {
@@ -2113,8 +4744,9 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
+            Ok(None) if self.json_output() => {}
             Ok(None) => println!("{}", "No scan results".dark_grey()),
-            Err(e) => logger::error(&format!("Error getting results: {}", e)),
+            Err(e) => self.fail(&format!("Error getting results: {}", e)),
         }
         true
     }
@@ -2152,11 +4784,11 @@ impl<'a, 'b> Commander<'a, 'b> {
 
                     self.field.clear_data();
                     self.field.add_datas(scan_results);
-                    println!("{}", self.field.to_string(None));
+                    self.show_results(&self.field);
                 }
             }
             Ok(None) => println!("{}", "No scan results".dark_grey()),
-            Err(e) => logger::error(&format!("Error loading results: {}", e)),
+            Err(e) => self.fail(&format!("Error loading results: {}", e)),
         }
         true
     }
@@ -2172,8 +4804,8 @@ impl<'a, 'b> Commander<'a, 'b> {
                     }
                 }
             }
-            Ok(None) => logger::error("No response from clear_scan"),
-            Err(e) => logger::error(&format!("Clear error: {}", e)),
+            Ok(None) => self.fail("No response from clear_scan"),
+            Err(e) => self.fail(&format!("Clear error: {}", e)),
         }
         true
     }
@@ -2188,10 +4820,15 @@ impl<'a, 'b> Commander<'a, 'b> {
         match result {
             Ok(Some(value)) => {
                 if let Some(threads) = value.as_array() {
+                    let json_output = self.json_output();
                     if threads.is_empty() {
-                        println!("{}", "No threads found".dark_grey());
+                        if !json_output {
+                            println!("{}", "No threads found".dark_grey());
+                        }
                     } else {
-                        println!("{} {} threads:", "[THREADS]".cyan(), threads.len());
+                        if !json_output {
+                            println!("{} {} threads:", "[THREADS]".cyan(), threads.len());
+                        }
 
                         let thread_datas: Vec<VzData> = threads
                             .iter()
@@ -2200,16 +4837,22 @@ impl<'a, 'b> Commander<'a, 'b> {
                                 let state =
                                     t.get("state").and_then(|v| v.as_str()).unwrap_or("unknown");
 
-                                println!(
-                                    "  Thread {} ({})",
-                                    id.to_string().yellow(),
-                                    state.dark_grey()
-                                );
-
-                                Some(VzData::Thread(VzThread {
+                                let thread = VzData::Thread(VzThread {
                                     base: new_base(VzDataType::Thread),
                                     id,
-                                }))
+                                });
+                                if json_output {
+                                    let mut entry = json!(thread);
+                                    entry["state"] = json!(state);
+                                    self.emit_json(entry);
+                                } else {
+                                    println!(
+                                        "  Thread {} ({})",
+                                        id.to_string().yellow(),
+                                        state.dark_grey()
+                                    );
+                                }
+                                Some(thread)
                             })
                             .collect();
 
@@ -2219,33 +4862,19 @@ impl<'a, 'b> Commander<'a, 'b> {
                 }
             }
             Ok(None) => println!("{}", "No threads found".dark_grey()),
-            Err(e) => logger::error(&format!("Thread list error: {}", e)),
+            Err(e) => self.fail(&format!("Thread list error: {}", e)),
         }
         true
     }
 
     pub(crate) fn thread_regs(&mut self, args: &[&str]) -> bool {
-        let thread_id = args.get(0).and_then(|s| s.parse::<u64>().ok());
-
-        let thread_id = match thread_id {
+        let thread_id = match args.get(0).and_then(|s| s.parse::<u64>().ok()) {
             Some(id) => id,
-            None => {
-                // Try to get first thread
-                let result = self.script.exports.call("list_threads", None);
-                match result {
-                    Ok(Some(value)) => value
-                        .as_array()
-                        .and_then(|arr| arr.first())
-                        .and_then(|t| t.get("id"))
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0),
-                    _ => 0,
-                }
-            }
+            None => self.current_thread().unwrap_or(0),
         };
 
         if thread_id == 0 {
-            logger::error("No valid thread ID");
+            self.fail("No valid thread ID");
             return true;
         }
 
@@ -2255,6 +4884,17 @@ impl<'a, 'b> Commander<'a, 'b> {
             .call("get_thread_context", Some(json!([thread_id])));
 
         match result {
+            Ok(Some(value)) if self.json_output() => {
+                if value.is_object() {
+                    self.emit_json(json!({
+                        "type": "Registers",
+                        "thread": thread_id,
+                        "registers": value,
+                    }));
+                } else {
+                    self.fail("Thread context not available");
+                }
+            }
             Ok(Some(value)) => {
                 if value.is_null() {
                     println!("{}", "Thread context not available".dark_grey());
@@ -2272,7 +4912,7 @@ impl<'a, 'b> Commander<'a, 'b> {
                 }
             }
             Ok(None) => println!("{}", "Thread context not available".dark_grey()),
-            Err(e) => logger::error(&format!("Register read error: {}", e)),
+            Err(e) => self.fail(&format!("Register read error: {}", e)),
         }
         true
     }
@@ -2287,22 +4927,11 @@ impl<'a, 'b> Commander<'a, 'b> {
         // First get thread context to find SP
         let thread_id = match thread_id {
             Some(id) => id,
-            None => {
-                let result = self.script.exports.call("list_threads", None);
-                match result {
-                    Ok(Some(value)) => value
-                        .as_array()
-                        .and_then(|arr| arr.first())
-                        .and_then(|t| t.get("id"))
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0),
-                    _ => 0,
-                }
-            }
+            None => self.current_thread().unwrap_or(0),
         };
 
         if thread_id == 0 {
-            logger::error("No valid thread ID");
+            self.fail("No valid thread ID");
             return true;
         }
 
@@ -2327,7 +4956,7 @@ impl<'a, 'b> Commander<'a, 'b> {
         };
 
         if sp == 0 {
-            logger::error("Could not determine stack pointer");
+            self.fail("Could not determine stack pointer");
             return true;
         }
 
@@ -2369,13 +4998,20 @@ impl<'a, 'b> Commander<'a, 'b> {
                 }
             }
             Ok(None) => println!("{}", "Could not read stack".dark_grey()),
-            Err(e) => logger::error(&format!("Stack read error: {}", e)),
+            Err(e) => self.fail(&format!("Stack read error: {}", e)),
         }
         true
     }
 
     pub(crate) fn thread_backtrace(&mut self, args: &[&str]) -> bool {
-        let result = self.script.exports.call("backtrace", None);
+        let thread_id = match args.first().and_then(|s| s.parse::<u64>().ok()) {
+            Some(id) => Some(id),
+            None => self.current_thread(),
+        };
+        let result = self
+            .script
+            .exports
+            .call("backtrace", Some(json!([thread_id])));
 
         match result {
             Ok(Some(value)) => {
@@ -2385,26 +5021,21 @@ impl<'a, 'b> Commander<'a, 'b> {
                     } else {
                         println!("{} Backtrace ({} frames):", "[BT]".cyan(), frames.len());
                         for (i, frame) in frames.iter().enumerate() {
-                            let addr = frame.get("address").and_then(|v| v.as_str()).unwrap_or("?");
-                            let module = frame.get("module").and_then(|v| v.as_str());
-                            let symbol = frame.get("symbol").and_then(|v| v.as_str());
-                            let offset = frame.get("offset").and_then(|v| v.as_i64());
-
-                            let location = match (module, symbol, offset) {
-                                (Some(m), Some(s), Some(o)) => format!("{}!{} +{:#x}", m, s, o),
-                                (Some(m), Some(s), None) => format!("{}!{}", m, s),
-                                (Some(m), None, Some(o)) => format!("{} +{:#x}", m, o),
-                                (Some(m), None, None) => m.to_string(),
-                                _ => "???".to_string(),
-                            };
-
-                            println!("  #{:<2} {} {}", i, addr.yellow(), location.dark_grey());
+                            match serde_json::from_value::<Frame>(frame.clone()) {
+                                Ok(frame) => println!(
+                                    "  #{:<2} {} {}",
+                                    i,
+                                    frame.address.clone().yellow(),
+                                    frame.location().dark_grey()
+                                ),
+                                Err(_) => println!("  #{:<2} {}", i, "???".dark_grey()),
+                            }
                         }
                     }
                 }
             }
             Ok(None) => println!("{}", "No backtrace available".dark_grey()),
-            Err(e) => logger::error(&format!("Backtrace error: {}", e)),
+            Err(e) => self.fail(&format!("Backtrace error: {}", e)),
         }
         true
     }
@@ -2413,47 +5044,259 @@ impl<'a, 'b> Commander<'a, 'b> {
     // Helper Methods
     // ========================================================================
 
+    /// Report an error from the running command
+    fn fail(&mut self, message: &str) {
+        self.failed = true;
+        logger::error(message);
+    }
+
+    fn json_output(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
+
+    /// Print one compact JSON object per line
+    fn emit_json(&self, value: Value) {
+        println!("{}", value);
+    }
+
+    /// Strip styling and the hex/length annotation from a `read` result
+    fn plain_read_value(result: &str, value_type: &VzValueType) -> String {
+        let plain = String::from_utf8_lossy(&strip_ansi_escapes::strip(result)).to_string();
+        match value_type {
+            VzValueType::String | VzValueType::Utf8 | VzValueType::Utf16 => plain
+                .strip_prefix('"')
+                .and_then(|s| s.strip_suffix('"'))
+                .unwrap_or(&plain)
+                .to_string(),
+            _ => plain
+                .rsplit_once(" (")
+                .map(|(value, _)| value.to_string())
+                .unwrap_or(plain),
+        }
+    }
+
+    /// Build a VzHook from an entry returned by the hook_list RPC
+    fn hook_from_json(hook: &Value) -> VzHook {
+        let config = hook.get("config");
+        let flag = |name: &str| {
+            config
+                .and_then(|c| c.get(name))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        };
+        VzHook {
+            base: new_base(VzDataType::Hook),
+            id: hook
+                .get("id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("?")
+                .to_string(),
+            address: hook
+                .get("address")
+                .and_then(|v| v.as_str())
+                .map(crate::gum::vzdata::string_to_u64)
+                .unwrap_or(0),
+            target_name: hook
+                .get("target")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            module: None,
+            enabled: hook
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            on_enter: flag("onEnter"),
+            on_leave: flag("onLeave"),
+            log_args: flag("logArgs"),
+            log_retval: flag("logRetval"),
+        }
+    }
+
+    /// Print a store page, or its entries as JSON lines
+    fn show_store(&self, store: &Store, page: Option<usize>) {
+        if self.json_output() {
+            store
+                .to_json(page)
+                .into_iter()
+                .for_each(|v| self.emit_json(v));
+        } else {
+            println!("{}", store.to_string(page));
+        }
+    }
+
+    /// Like `show_store`, but JSON mode emits every entry rather than one page
+    fn show_results(&self, store: &Store) {
+        if self.json_output() {
+            store
+                .all_to_json()
+                .into_iter()
+                .for_each(|v| self.emit_json(v));
+        } else {
+            println!("{}", store.to_string(None));
+        }
+    }
+
+    /// Print disassembled instructions, marking Lib labels at their address
+    /// and on operands that refer to a labelled address
+    fn print_instructions(&self, instructions: &[Value]) {
+        let labels = self.labels();
+        let operand_re = Regex::new(r"0x[0-9a-fA-F]+").unwrap();
+        for insn in instructions {
+            let addr = insn.get("address").and_then(|v| v.as_str()).unwrap_or("?");
+            let mnemonic = insn.get("mnemonic").and_then(|v| v.as_str()).unwrap_or("?");
+            let op_str = insn.get("opStr").and_then(|v| v.as_str()).unwrap_or("");
+            let bytes = insn
+                .get("bytes")
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|b| b.as_u64())
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .unwrap_or_default();
+
+            if let Some(label) = labels.get(&string_to_u64(addr)) {
+                println!("{}", format!("<{}>:", label).magenta());
+            }
+            let referenced = operand_re
+                .find_iter(op_str)
+                .filter_map(|m| labels.get(&string_to_u64(m.as_str())))
+                .map(|label| format!("<{}>", label))
+                .collect::<Vec<_>>();
+            let comment = if referenced.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", format!("; {}", referenced.join(" ")).magenta())
+            };
+
+            println!(
+                "  {} {} {} {}{}",
+                addr.yellow(),
+                format!("{:<24}", bytes).dark_grey(),
+                mnemonic.cyan(),
+                op_str,
+                comment
+            );
+        }
+    }
+
+    /// Labels of Lib entries keyed by address
+    fn labels(&self) -> BTreeMap<u64, String> {
+        self.lib
+            .data
+            .iter()
+            .filter_map(|d| Some((get_address_from_data(d)?, d.base().label.clone()?)))
+            .collect()
+    }
+
     /// Resolve a target string to an address
-    /// Accepts: hex address, decimal address, or selector
+    /// Accepts: hex address, decimal address, selector, or an address expression
+    /// such as `libc.so.6+0x1234`, `libc.so.6!open+8`, `[rsp+0x10]`, `$nav+4`
     fn resolve_target_address(&mut self, target: &str) -> Result<u64, String> {
         // First try to parse as a number
         if let Ok(addr) = Self::parse_number(target) {
             return Ok(addr);
         }
 
-        // Try selector
-        match self.selector(target) {
-            Ok(data) => {
-                if data.is_empty() {
-                    Err("No data found for selector".to_string())
-                } else {
-                    get_address_from_data(data[0])
-                        .ok_or_else(|| "Selected data has no address".to_string())
-                }
+        // Whole-string selectors keep their meaning, e.g. `field:0-3` is a range
+        if let Ok(data) = self.selector(target) {
+            if let Some(addr) = data.first().and_then(|d| get_address_from_data(d)) {
+                return Ok(addr);
             }
-            Err(e) => {
-                // Try to resolve as symbol name
-                let result = self
-                    .script
-                    .exports
-                    .call("find_symbol", Some(json!([target])));
+        }
 
-                match result {
-                    Ok(Some(value)) => {
-                        if value.is_null() {
-                            Err(format!("Symbol not found: {}", target))
-                        } else {
-                            value
-                                .get("address")
-                                .and_then(|v| v.as_str())
-                                .map(|s| crate::gum::vzdata::string_to_u64(s))
-                                .ok_or_else(|| format!("Invalid symbol address for: {}", target))
-                        }
-                    }
-                    Ok(None) => Err(format!("Symbol not found: {}", target)),
-                    Err(_) => Err(e),
-                }
+        expr::evaluate(target, self)
+    }
+
+    /// Whether `name` starts with a store prefix such as `field:` or `l:`
+    fn is_store_selector(name: &str) -> bool {
+        matches!(
+            name.split_once(':'),
+            Some(("field" | "fld" | "f" | "lib" | "l", _))
+        )
+    }
+
+    /// Address of the first entry a selector picks; `field:3.arg1` is a value
+    /// recorded in a captured hook event
+    fn selector_address(&mut self, name: &str) -> Result<u64, String> {
+        if let Some((selector, member)) = name.rsplit_once('.') {
+            let data = self.selector(selector)?;
+            return match data.first() {
+                Some(VzData::HookEvent(event)) => event
+                    .member(member)
+                    .ok_or_else(|| format!("Hook event has no {}: {}", member, name)),
+                _ => Err(format!("Only hook events have members: {}", name)),
+            };
+        }
+        let data = self.selector(name)?;
+        data.first()
+            .and_then(|d| get_address_from_data(d))
+            .ok_or_else(|| format!("Selected data has no address: {}", name))
+    }
+}
+
+impl Resolver for Commander<'_, '_> {
+    /// Names resolve as `$nav`/`$var`, `module!export`, store selector, Lib
+    /// label, module, debug symbol, then a register of the selected or stopped
+    /// thread
+    fn resolve_name(&mut self, name: &str) -> Result<u64, String> {
+        if name == "$nav" {
+            return self
+                .navigator
+                .get_data()
+                .and_then(get_address_from_data)
+                .ok_or_else(|| "Navigator has no address".to_string());
+        }
+        if name.starts_with('$') {
+            return match self.vars.get(name) {
+                Some(value) => Self::parse_number(&value),
+                None => Err(format!("Unknown variable: {}", name)),
+            };
+        }
+
+        if let Some((module, export)) = name.split_once('!') {
+            return match self
+                .script
+                .exports
+                .call("resolve_export", Some(json!([module, export])))
+            {
+                Ok(Some(Value::String(addr))) => Ok(string_to_u64(&addr)),
+                Ok(_) => Err(format!("Export not found: {}", name)),
+                Err(e) => Err(format!("Export lookup failed: {}", e)),
+            };
+        }
+
+        // `Foo::bar` is a symbol; only a store name before the colon makes a selector
+        let mut selector_error = None;
+        if Self::is_store_selector(name) {
+            match self.selector_address(name) {
+                Ok(addr) => return Ok(addr),
+                Err(e) => selector_error = Some(e),
             }
         }
+
+        if let Some((&addr, _)) = self.labels().iter().find(|(_, label)| *label == name) {
+            return Ok(addr);
+        }
+
+        let thread_id = match self.navigator.get_data() {
+            Some(VzData::Thread(t)) => Some(t.id),
+            _ => None,
+        };
+        match self
+            .script
+            .exports
+            .call("resolve_name", Some(json!([name, thread_id])))
+        {
+            Ok(Some(Value::String(addr))) => Ok(string_to_u64(&addr)),
+            Ok(_) => Err(selector_error.unwrap_or_else(|| format!("Symbol not found: {}", name))),
+            Err(e) => Err(format!("Symbol lookup failed: {}", e)),
+        }
+    }
+
+    fn read_pointer(&mut self, address: u64) -> Result<u64, String> {
+        readpointer(self.script, address)
     }
 }
@@ -11,7 +11,7 @@ pub(crate) fn build() -> Vec<Command> {
         "Disassemble instructions at address",
         vec!["dis", "u"],  // 'u' is common in debuggers for unassemble
         vec![
-            CommandArg::optional("target", "Address, selector, or function name"),
+            CommandArg::optional("target", "Address expression, selector, or function name"),
             CommandArg::optional("count", "Number of instructions to disassemble (default 20)"),
         ],
         vec![
@@ -19,7 +19,7 @@ pub(crate) fn build() -> Vec<Command> {
                 "func",
                 "Disassemble an entire function until return",
                 vec![
-                    CommandArg::optional("target", "Address or selector of function"),
+                    CommandArg::optional("target", "Address expression or selector of function"),
                 ],
                 |c, a| Commander::disas_function(c, a),
             )
@@ -35,7 +35,7 @@ pub(crate) fn build() -> Vec<Command> {
         "bytes",
         "Patch bytes at address",
         vec![
-            CommandArg::required("target", "Address or selector"),
+            CommandArg::required("target", "Address expression or selector"),
             CommandArg::required("bytes", "Hex bytes to write (e.g., '90 90 90')"),
         ],
         |c, a| Commander::patch_bytes(c, a),
@@ -45,7 +45,7 @@ pub(crate) fn build() -> Vec<Command> {
         "nop",
         "NOP out instructions at address",
         vec![
-            CommandArg::required("target", "Address or selector"),
+            CommandArg::required("target", "Address expression or selector"),
             CommandArg::optional("count", "Number of instructions to NOP (default 1)"),
         ],
         |c, a| Commander::patch_nop(c, a),
@@ -55,7 +55,7 @@ pub(crate) fn build() -> Vec<Command> {
         "restore",
         "Restore original bytes at address",
         vec![
-            CommandArg::required("target", "Address or selector"),
+            CommandArg::required("target", "Address expression or selector"),
         ],
         |c, a| Commander::patch_restore(c, a),
     ));
@@ -75,7 +75,7 @@ pub(crate) fn build() -> Vec<Command> {
         "NOP out instructions (shortcut for patch nop)",
         vec![],
         vec![
-            CommandArg::required("target", "Address or selector"),
+            CommandArg::required("target", "Address expression or selector"),
             CommandArg::optional("count", "Number of instructions to NOP (default 1)"),
         ],
         vec![],
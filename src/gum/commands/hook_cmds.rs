@@ -12,14 +12,43 @@ pub(crate) fn build() -> Vec<Command> {
             "add",
             "Add a hook to target address or function",
             vec![
-                CommandArg::required("target", "Address, selector, or function name"),
-                CommandArg::optional("options", "Hook options: -e (enter) -l (leave) -a (args) -r (retval) -b (backtrace)"),
+                CommandArg::required("target", "Address expression, selector, or function name"),
+                CommandArg::optional("options", "Hook options: -e (enter) -l (leave) -a (args) -r (retval) -b (backtrace) -s <decl> (typed signature) -q (count only, no output) -n (no signature lookup) --if <cond> (filter calls)"),
             ],
             |c, a| Commander::hook_add(c, a),
         )
         .alias("a"),
     );
 
+    hook_subs.push(
+        SubCommand::new(
+            "break",
+            "Add a hook that stops each calling thread until continue",
+            vec![
+                CommandArg::required("target", "Address expression, selector, or function name"),
+                CommandArg::optional("options", "Options of hook add"),
+            ],
+            |c, a| Commander::hook_break(c, a),
+        )
+        .alias("br"),
+    );
+
+    hook_subs.push(
+        SubCommand::new(
+            "replace",
+            "Replace a function with a stub returning a fixed value",
+            vec![
+                CommandArg::required("target", "Address expression, selector, or function name"),
+                CommandArg::optional(
+                    "options",
+                    "--ret <value> or --skip; --abi <abi> --argc <n> for the stub",
+                ),
+            ],
+            |c, a| Commander::hook_replace(c, a),
+        )
+        .alias("rep"),
+    );
+
     hook_subs.push(
         SubCommand::new(
             "remove",
@@ -60,6 +89,59 @@ pub(crate) fn build() -> Vec<Command> {
         .alias("dis"),
     );
 
+    hook_subs.push(SubCommand::new(
+        "stats",
+        "Show call counts and timings per hook, or per thread for one hook",
+        vec![
+            CommandArg::optional("id", "Hook ID; if omitted, shows all hooks"),
+            CommandArg::optional(
+                "options",
+                "-s <column>: sort by calls (default), threads, total, avg, max or id",
+            ),
+        ],
+        |c, a| Commander::hook_stats(c, a),
+    ));
+
+    hook_subs.push(SubCommand::new(
+        "sigs",
+        "List known function signatures used to decode hooked calls",
+        vec![CommandArg::optional(
+            "filter",
+            "Substring of the function name",
+        )],
+        |c, a| Commander::hook_sigs(c, a),
+    ));
+
+    hook_subs.push(SubCommand::new(
+        "sigload",
+        "Load signatures from a file of C declarations",
+        vec![CommandArg::required("file", "Signature file path")],
+        |c, a| Commander::hook_sigload(c, a),
+    ));
+
+    hook_subs.push(SubCommand::new(
+        "log",
+        "Write hook events to a JSON Lines file, or show the active log",
+        vec![
+            CommandArg::optional("file", "Log file path, or off to stop logging"),
+            CommandArg::optional("options", "-a (append instead of truncating)"),
+        ],
+        |c, a| Commander::hook_log(c, a),
+    ));
+
+    hook_subs.push(
+        SubCommand::new(
+            "capture",
+            "Add a hook's events to Field as HookEvent entries, or list captures",
+            vec![
+                CommandArg::optional("id", "Hook ID to capture"),
+                CommandArg::optional("max", "Events to capture (default 1000), or off to stop"),
+            ],
+            |c, a| Commander::hook_capture(c, a),
+        )
+        .alias("cap"),
+    );
+
     hook_subs.push(SubCommand::new(
         "clear",
         "Remove all active hooks",
@@ -76,5 +158,83 @@ pub(crate) fn build() -> Vec<Command> {
         Some(|c, a| Commander::hook_list(c, a)), // Default to list
     ));
 
+    let mut events_subs: Vec<SubCommand> = Vec::new();
+
+    events_subs.push(SubCommand::new(
+        "load",
+        "Load hook events from a hook log file",
+        vec![CommandArg::required(
+            "file",
+            "JSON Lines file written by hook log",
+        )],
+        |c, a| Commander::events_load(c, a),
+    ));
+
+    events_subs.push(
+        SubCommand::new(
+            "list",
+            "Show loaded events",
+            vec![CommandArg::optional(
+                "count",
+                "Number of events (default: all)",
+            )],
+            |c, a| Commander::events_list(c, a),
+        )
+        .alias("ls"),
+    );
+
+    events_subs.push(
+        SubCommand::new(
+            "filter",
+            "Show loaded events matching a filter, e.g. id=hook_2 & tid=5",
+            vec![CommandArg::required(
+                "filter",
+                "Keys: id, type, tid, depth, address, timestamp, argN, retval, caller, backtrace",
+            )],
+            |c, a| Commander::events_filter(c, a),
+        )
+        .alias("f"),
+    );
+
+    events_subs.push(SubCommand::new(
+        "clear",
+        "Forget loaded events",
+        vec![],
+        |c, a| Commander::events_clear(c, a),
+    ));
+
+    cmds.push(Command::new(
+        "events",
+        "Load and query hook event logs",
+        vec!["ev"],
+        vec![],
+        events_subs,
+        Some(|c, a| Commander::events_list(c, a)), // Default to list
+    ));
+
+    cmds.push(Command::new(
+        "continue",
+        "Resume a thread stopped at a break hook",
+        vec!["c"],
+        vec![CommandArg::optional(
+            "thread_id",
+            "Thread ID or all (default: current)",
+        )],
+        vec![],
+        Some(|c, a| Commander::continue_thread(c, a)),
+    ));
+
+    cmds.push(Command::new(
+        "args",
+        "Show or set the arguments of a thread stopped at a break hook",
+        vec![],
+        vec![
+            CommandArg::optional("index", "Argument index to set"),
+            CommandArg::optional("value", "New value (address expression)"),
+        ],
+        vec![],
+        Some(|c, a| Commander::break_args(c, a)),
+    ));
+
     cmds
 }
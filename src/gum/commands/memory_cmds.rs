@@ -64,7 +64,7 @@ pub(crate) fn build() -> Vec<Command> {
         vec![
             CommandArg::optional(
                 "target_or_size",
-                "Selector/address or size if using navigator",
+                "Address expression/selector, or size if using navigator",
             ),
             CommandArg::optional("size", "Bytes to view (default 256)"),
             CommandArg::optional("type", "Value type (Byte, Word, DWord, QWord, etc.)"),
@@ -79,7 +79,7 @@ pub(crate) fn build() -> Vec<Command> {
         "Read memory at address/selection",
         vec!["r"],
         vec![
-            CommandArg::required("target", "Selector or numeric address"),
+            CommandArg::required("target", "Address expression or selector"),
             CommandArg::optional("type", "Value type (default Byte)"),
             CommandArg::optional("length", "Number of elements/bytes (default 16)"),
         ],
@@ -93,7 +93,7 @@ pub(crate) fn build() -> Vec<Command> {
         "Write value to address/selection",
         vec!["w"],
         vec![
-            CommandArg::required("target", "Selector or numeric address"),
+            CommandArg::required("target", "Address expression or selector"),
             CommandArg::required("value", "Value to write"),
             CommandArg::optional("type", "Value type (default Byte)"),
         ],
@@ -101,5 +101,68 @@ pub(crate) fn build() -> Vec<Command> {
         Some(|c, a| Commander::write(c, a)),
     ));
 
+    // watch
+    let watch_subs: Vec<SubCommand> = vec![
+        SubCommand::new("list", "List watchpoints", vec![], |c, a| {
+            Commander::watch_list(c, a)
+        })
+        .alias("ls"),
+        SubCommand::new(
+            "remove",
+            "Remove a watchpoint",
+            vec![CommandArg::required("id", "Watchpoint ID, e.g. watch_0")],
+            |c, a| Commander::watch_remove(c, a),
+        )
+        .alias("rm"),
+    ];
+
+    cmds.push(Command::new(
+        "watch",
+        "Report the instructions that access a memory range",
+        vec!["wa"],
+        vec![
+            CommandArg::optional("target", "Address expression or selector"),
+            CommandArg::optional("size", "Bytes to watch"),
+            CommandArg::optional(
+                "options",
+                "--write (default), --read, --exec; combine them to watch several",
+            ),
+        ],
+        watch_subs,
+        Some(|c, a| Commander::watch(c, a)),
+    ));
+
+    // freeze
+    cmds.push(Command::new(
+        "freeze",
+        "Keep rewriting a value in memory",
+        vec!["fz"],
+        vec![
+            CommandArg::optional("target", "Address expression or selector"),
+            CommandArg::optional("value", "Value to hold"),
+            CommandArg::optional("type", "Value type (default Byte)"),
+            CommandArg::optional(
+                "options",
+                "-i <ms> (rewrite interval, default 100) or --on-write (rewrite when written)",
+            ),
+        ],
+        vec![
+            SubCommand::new("list", "List frozen values", vec![], |c, a| {
+                Commander::freeze_list(c, a)
+            })
+            .alias("ls"),
+        ],
+        Some(|c, a| Commander::freeze(c, a)),
+    ));
+
+    cmds.push(Command::new(
+        "unfreeze",
+        "Stop rewriting a frozen value",
+        vec!["ufz"],
+        vec![CommandArg::required("id", "Freeze ID, e.g. freeze_0")],
+        vec![],
+        Some(|c, a| Commander::unfreeze(c, a)),
+    ));
+
     cmds
 }
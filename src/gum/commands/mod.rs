@@ -6,6 +6,8 @@ pub mod store_cmds;
 pub mod hook_cmds;
 pub mod disasm_cmds;
 pub mod scan_cmds;
+pub mod trace_cmds;
+pub mod native_cmds;
 
 use crate::gum::commander::{Command, CommandArg, SubCommand};
 use crate::gum::commander::Commander;
@@ -13,7 +15,7 @@ use crate::gum::commander::Commander;
 pub fn build_all() -> Vec<Command> {
     let mut cmds: Vec<Command> = Vec::new();
 
-    // Core commands: debug, help, exit, clear
+    // Core commands: debug, help, exit, source, output, set, clear
     cmds.push(Command::new(
         "debug",
         "Debug functions",
@@ -44,6 +46,39 @@ pub fn build_all() -> Vec<Command> {
         Some(|c, a| Commander::exit(c, a)),
     ));
 
+    cmds.push(Command::new(
+        "source",
+        "Run commands from a file",
+        vec!["."],
+        vec![CommandArg::required(
+            "file",
+            "Script file with one command per line",
+        )],
+        vec![],
+        Some(|c, a| Commander::source(c, a)),
+    ));
+
+    cmds.push(Command::new(
+        "output",
+        "Show or set result format (text, json)",
+        vec!["out"],
+        vec![CommandArg::optional("format", "text or json")],
+        vec![],
+        Some(|c, a| Commander::output_format(c, a)),
+    ));
+
+    cmds.push(Command::new(
+        "set",
+        "Set a $variable, or list variables",
+        vec![],
+        vec![
+            CommandArg::optional("name", "Variable name, e.g. $base"),
+            CommandArg::optional("expr", "= <address expression>"),
+        ],
+        vec![],
+        Some(|c, a| Commander::set_variable(c, a)),
+    ));
+
     cmds.push(Command::new(
         "clear",
         "Clear the terminal screen",
@@ -56,10 +91,12 @@ pub fn build_all() -> Vec<Command> {
     // Grouped commands by category
     cmds.extend(nav_cmds::build());      // Navigation: select, deselect, add, sub, goto
     cmds.extend(store_cmds::build());    // Stores: field, lib
-    cmds.extend(memory_cmds::build());   // Memory: list, view, read, write
-    cmds.extend(hook_cmds::build());     // Hooking: hook add/remove/list/enable/disable
+    cmds.extend(memory_cmds::build());   // Memory: list, view, read, write, watch, freeze
+    cmds.extend(hook_cmds::build());     // Hooking: hook, events, continue, args
     cmds.extend(disasm_cmds::build());   // Disassembly: disas, patch, nop
     cmds.extend(scan_cmds::build());     // Scanning: scan, thread
+    cmds.extend(trace_cmds::build());    // Tracing: trace, stalk, coverage
+    cmds.extend(native_cmds::build());   // Native: call, alloc, free
 
     cmds
 }
@@ -0,0 +1,69 @@
+// src/gum/commands/native_cmds.rs
+
+use crate::gum::commander::{Command, CommandArg, Commander, SubCommand};
+
+pub(crate) fn build() -> Vec<Command> {
+    let mut cmds: Vec<Command> = Vec::new();
+
+    cmds.push(Command::new(
+        "call",
+        "Call a function in the target and print its return value",
+        vec![],
+        vec![
+            CommandArg::required("target", "Address expression, selector, or function name"),
+            CommandArg::optional("args", "Arguments: numbers, address expressions or strings"),
+            CommandArg::optional(
+                "options",
+                "-s <decl> (typed signature) --abi <abi> (calling convention)",
+            ),
+        ],
+        vec![],
+        Some(|c, a| Commander::call(c, a)),
+    ));
+
+    let alloc_subs: Vec<SubCommand> = vec![
+        SubCommand::new(
+            "string",
+            "Allocate a NUL-terminated UTF-8 string",
+            vec![CommandArg::required("text", "String contents")],
+            |c, a| Commander::alloc_string(c, a),
+        )
+        .alias("str"),
+        SubCommand::new(
+            "utf16",
+            "Allocate a NUL-terminated UTF-16 string",
+            vec![CommandArg::required("text", "String contents")],
+            |c, a| Commander::alloc_utf16(c, a),
+        ),
+        SubCommand::new("list", "List live allocations in Field", vec![], |c, a| {
+            Commander::alloc_list(c, a)
+        })
+        .alias("ls"),
+    ];
+
+    cmds.push(Command::new(
+        "alloc",
+        "Allocate memory in the target; the buffer is added to Field",
+        vec![],
+        vec![
+            CommandArg::optional("size", "Bytes to allocate; lists allocations if omitted"),
+            CommandArg::optional("options", "--prot <rwx> (whole pages with that protection)"),
+        ],
+        alloc_subs,
+        Some(|c, a| Commander::alloc(c, a)),
+    ));
+
+    cmds.push(Command::new(
+        "free",
+        "Release an allocation",
+        vec![],
+        vec![CommandArg::required(
+            "id",
+            "Allocation ID (e.g., alloc_0), or a selector of its entry",
+        )],
+        vec![],
+        Some(|c, a| Commander::free(c, a)),
+    ));
+
+    cmds
+}
@@ -53,7 +53,10 @@ pub(crate) fn build() -> Vec<Command> {
         "goto",
         "Jump navigator to an absolute address",
         vec![],
-        vec![CommandArg::required("address", "Address to jump to (hex or dec)")],
+        vec![CommandArg::required(
+            "address",
+            "Address expression to jump to (e.g. libc.so.6+0x10)",
+        )],
         vec![],
         Some(|c, a| Commander::goto(c, a)),
     ));
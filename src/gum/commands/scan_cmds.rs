@@ -162,6 +162,19 @@ pub(crate) fn build() -> Vec<Command> {
         .alias("bt"),
     );
 
+    thread_subs.push(
+        SubCommand::new(
+            "setreg",
+            "Set a register of a thread stopped at a break hook",
+            vec![
+                CommandArg::required("register", "Register name as shown by thread regs"),
+                CommandArg::required("value", "New value (address expression)"),
+            ],
+            |c, a| Commander::thread_setreg(c, a),
+        )
+        .alias("sr"),
+    );
+
     cmds.push(Command::new(
         "thread",
         "Thread inspection operations",
@@ -148,6 +148,45 @@ pub(crate) fn build() -> Vec<Command> {
         vec![CommandArg::optional("expr", "Filter expression")],
         |c, a| Commander::lib_filter(c, a),
     ));
+    lib_subs.push(SubCommand::new(
+        "label",
+        "Name a Lib item; the label shows wherever its address appears",
+        vec![
+            CommandArg::required("index", "Lib index"),
+            CommandArg::optional("name", "Label; if omitted, removes the label"),
+        ],
+        |c, a| Commander::lib_label(c, a),
+    ));
+    lib_subs.push(SubCommand::new(
+        "note",
+        "Attach a comment to a Lib item",
+        vec![
+            CommandArg::required("index", "Lib index"),
+            CommandArg::optional("text", "Comment text; if omitted, removes the comment"),
+        ],
+        |c, a| Commander::lib_note(c, a),
+    ));
+    lib_subs.push(SubCommand::new(
+        "tag",
+        "Add tags to a Lib item, or remove them with a leading '-'",
+        vec![
+            CommandArg::required("index", "Lib index"),
+            CommandArg::required("tags", "Tags to add, or -tag to remove"),
+        ],
+        |c, a| Commander::lib_tag(c, a),
+    ));
+    lib_subs.push(SubCommand::new(
+        "export",
+        "Write Lib store to a file as module+offset bookmarks",
+        vec![CommandArg::required("file", "Output file path")],
+        |c, a| Commander::lib_export(c, a),
+    ));
+    lib_subs.push(SubCommand::new(
+        "import",
+        "Load bookmarks from a file, rebased onto loaded modules",
+        vec![CommandArg::required("file", "Bookmark file path")],
+        |c, a| Commander::lib_import(c, a),
+    ));
 
     cmds.push(Command::new(
         "lib",
@@ -0,0 +1,99 @@
+// src/gum/commands/trace_cmds.rs
+
+use crate::gum::commander::{Command, CommandArg, Commander, SubCommand};
+
+pub(crate) fn build() -> Vec<Command> {
+    let mut cmds: Vec<Command> = Vec::new();
+
+    let trace_subs: Vec<SubCommand> = vec![SubCommand::new(
+        "stop",
+        "Remove all trace hooks",
+        vec![],
+        |c, a| Commander::trace_stop(c, a),
+    )];
+
+    cmds.push(Command::new(
+        "trace",
+        "Trace calls to every function of a module matching a pattern",
+        vec!["tr"],
+        vec![
+            CommandArg::required("module", "Module name"),
+            CommandArg::optional("pattern", "Glob over function names, e.g. SSL_*"),
+            CommandArg::optional(
+                "options",
+                "-i (trace the module's imports) -r (print return values)",
+            ),
+        ],
+        trace_subs,
+        Some(|c, a| Commander::trace_start(c, a)),
+    ));
+
+    let stalk_subs: Vec<SubCommand> = vec![
+        SubCommand::new(
+            "stop",
+            "Stop stalking a thread, or every stalked thread",
+            vec![CommandArg::optional("thread_id", "Thread ID")],
+            |c, a| Commander::stalk_stop(c, a),
+        ),
+        SubCommand::new("list", "List stalked threads", vec![], |c, a| {
+            Commander::stalk_list(c, a)
+        })
+        .alias("ls"),
+    ];
+
+    cmds.push(Command::new(
+        "stalk",
+        "Follow a thread with Stalker and print the code it runs",
+        vec!["st"],
+        vec![
+            CommandArg::optional("thread_id", "Thread ID; without one, list stalked threads"),
+            CommandArg::optional(
+                "options",
+                "--calls (default), --blocks or --insns; -m <module> keeps events touching that module",
+            ),
+        ],
+        stalk_subs,
+        Some(|c, a| Commander::stalk_start(c, a)),
+    ));
+
+    let coverage_subs: Vec<SubCommand> = vec![
+        SubCommand::new(
+            "start",
+            "Collect the basic blocks run by every current thread",
+            vec![CommandArg::optional(
+                "options",
+                "-m <module> (only blocks in that module)",
+            )],
+            |c, a| Commander::coverage_start(c, a),
+        ),
+        SubCommand::new("stop", "Stop collecting coverage", vec![], |c, a| {
+            Commander::coverage_stop(c, a)
+        }),
+        SubCommand::new(
+            "save",
+            "Write the collected blocks to a drcov file",
+            vec![CommandArg::required("file", "Output path, e.g. run.drcov")],
+            |c, a| Commander::coverage_save(c, a),
+        ),
+        SubCommand::new(
+            "diff",
+            "Show the blocks and functions only one of two drcov files hit",
+            vec![
+                CommandArg::required("a", "First drcov file"),
+                CommandArg::required("b", "Second drcov file"),
+            ],
+            |c, a| Commander::coverage_diff(c, a),
+        ),
+    ];
+
+    cmds.push(Command::new(
+        "coverage",
+        "Basic block coverage with Stalker, saved in drcov format",
+        vec!["cov"],
+        vec![],
+        coverage_subs,
+        None,
+    ));
+
+    cmds
+}
@@ -0,0 +1,274 @@
+// src/gum/completer.rs
+
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Context, Helper,
+};
+
+/// Argument names whose values are addresses or store selectors.
+const SELECTOR_ARGS: &[&str] = &[
+    "selector",
+    "target",
+    "target_or_size",
+    "module_selector",
+    "address",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct SubCommandShape {
+    /// Primary name followed by aliases
+    pub names: Vec<String>,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandShape {
+    /// Primary name followed by aliases
+    pub names: Vec<String>,
+    pub args: Vec<String>,
+    pub subcommands: Vec<SubCommandShape>,
+}
+
+/// Snapshot of everything the completer can offer, refreshed after each command.
+#[derive(Debug, Clone, Default)]
+pub struct CompletionData {
+    pub commands: Vec<CommandShape>,
+    pub field_len: usize,
+    pub lib_len: usize,
+    pub hook_ids: Vec<String>,
+    pub modules: Vec<String>,
+    /// Defined `$variables`, including the leading `$`
+    pub variables: Vec<String>,
+}
+
+pub struct VlitzHelper {
+    pub data: CompletionData,
+}
+
+impl VlitzHelper {
+    pub fn new(data: CompletionData) -> Self {
+        VlitzHelper { data }
+    }
+
+    fn candidates(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+        let word = &line[start..];
+        let tokens: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let mut out = if tokens.is_empty() {
+            self.command_names()
+        } else if word.starts_with('$') {
+            let mut vars = self.data.variables.clone();
+            vars.push("$nav".to_string());
+            vars
+        } else {
+            match self.find_command(tokens[0]) {
+                Some(cmd) => self.command_candidates(cmd, &tokens[1..], word),
+                None => Vec::new(),
+            }
+        };
+        out.retain(|c| c.starts_with(word));
+        out.sort();
+        out.dedup();
+        (start, out)
+    }
+
+    fn command_names(&self) -> Vec<String> {
+        self.data
+            .commands
+            .iter()
+            .flat_map(|c| c.names.iter().cloned())
+            .collect()
+    }
+
+    fn find_command(&self, name: &str) -> Option<&CommandShape> {
+        self.data
+            .commands
+            .iter()
+            .find(|c| c.names.iter().any(|n| n == name))
+    }
+
+    fn command_candidates(&self, cmd: &CommandShape, rest: &[&str], word: &str) -> Vec<String> {
+        if !cmd.subcommands.is_empty() {
+            if rest.is_empty() {
+                let mut out: Vec<String> = cmd
+                    .subcommands
+                    .iter()
+                    .flat_map(|s| s.names.iter().cloned())
+                    .collect();
+                if let Some(arg) = cmd.args.first() {
+                    out.extend(self.arg_candidates(arg, word));
+                }
+                return out;
+            }
+            if let Some(sub) = cmd
+                .subcommands
+                .iter()
+                .find(|s| s.names.iter().any(|n| n == rest[0]))
+            {
+                return match sub.args.get(rest.len() - 1) {
+                    Some(arg) => self.arg_candidates(arg, word),
+                    None => Vec::new(),
+                };
+            }
+        }
+        match cmd.args.get(rest.len()) {
+            Some(arg) => self.arg_candidates(arg, word),
+            None => Vec::new(),
+        }
+    }
+
+    fn arg_candidates(&self, arg: &str, word: &str) -> Vec<String> {
+        match arg {
+            "id" => self.data.hook_ids.clone(),
+            "command" => self.command_names(),
+            a if SELECTOR_ARGS.contains(&a) => {
+                let mut out = self.selector_candidates(word);
+                out.extend(self.data.modules.iter().cloned());
+                out
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn selector_candidates(&self, word: &str) -> Vec<String> {
+        let stores = [("field", self.data.field_len), ("lib", self.data.lib_len)];
+        let mut out = Vec::new();
+        for (name, len) in stores {
+            if len == 0 {
+                continue;
+            }
+            let prefix = format!("{}:", name);
+            if word.starts_with(&prefix) {
+                out.push(format!("{}all", prefix));
+                out.extend((0..len).map(|i| format!("{}{}", prefix, i)));
+            } else {
+                out.push(prefix);
+            }
+        }
+        out
+    }
+}
+
+impl Completer for VlitzHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, candidates) = self.candidates(&line[..pos]);
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .map(|c| Pair {
+                    display: c.clone(),
+                    replacement: c,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl Hinter for VlitzHelper {
+    type Hint = String;
+}
+
+impl Highlighter for VlitzHelper {}
+
+impl Validator for VlitzHelper {}
+
+impl Helper for VlitzHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper() -> VlitzHelper {
+        VlitzHelper::new(CompletionData {
+            commands: vec![
+                CommandShape {
+                    names: vec!["hook".into(), "hk".into()],
+                    args: vec![],
+                    subcommands: vec![
+                        SubCommandShape {
+                            names: vec!["add".into(), "a".into()],
+                            args: vec!["target".into(), "options".into()],
+                        },
+                        SubCommandShape {
+                            names: vec!["remove".into(), "rm".into()],
+                            args: vec!["id".into()],
+                        },
+                    ],
+                },
+                CommandShape {
+                    names: vec!["help".into(), "h".into()],
+                    args: vec!["command".into()],
+                    subcommands: vec![],
+                },
+                CommandShape {
+                    names: vec!["read".into(), "r".into()],
+                    args: vec!["target".into(), "type".into()],
+                    subcommands: vec![],
+                },
+            ],
+            field_len: 3,
+            lib_len: 0,
+            hook_ids: vec!["hook_0".into(), "hook_1".into()],
+            modules: vec!["libc.so.6".into()],
+            variables: vec!["$_".into(), "$base".into()],
+        })
+    }
+
+    #[test]
+    fn test_complete_command_name() {
+        let (start, c) = helper().candidates("he");
+        assert_eq!(start, 0);
+        assert_eq!(c, vec!["help"]);
+    }
+
+    #[test]
+    fn test_complete_subcommand() {
+        let (start, c) = helper().candidates("hook r");
+        assert_eq!(start, 5);
+        assert_eq!(c, vec!["remove", "rm"]);
+    }
+
+    #[test]
+    fn test_complete_hook_id() {
+        let (_, c) = helper().candidates("hk rm hook_");
+        assert_eq!(c, vec!["hook_0", "hook_1"]);
+    }
+
+    #[test]
+    fn test_complete_selector_prefix() {
+        let (_, c) = helper().candidates("read ");
+        assert_eq!(c, vec!["field:", "libc.so.6"]);
+    }
+
+    #[test]
+    fn test_complete_selector_index() {
+        let (start, c) = helper().candidates("hook add field:");
+        assert_eq!(start, 9);
+        assert_eq!(c, vec!["field:0", "field:1", "field:2", "field:all"]);
+    }
+
+    #[test]
+    fn test_complete_unknown_command() {
+        let (_, c) = helper().candidates("nothing ");
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_complete_variable() {
+        let (start, c) = helper().candidates("read $b");
+        assert_eq!(start, 5);
+        assert_eq!(c, vec!["$base"]);
+    }
+}
@@ -0,0 +1,403 @@
+// src/gum/coverage.rs
+//
+// `coverage` collects the basic blocks Stalker compiles in the followed
+// threads; `coverage save` writes them in drcov v2 format for Lighthouse,
+// Cutter, bncov and friends. Blocks are stored relative to their module, and
+// `coverage diff` compares two saved runs by module name and offset since
+// modules load at different bases from run to run.
+
+use super::vzdata::string_to_u64;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// A loaded module as drcov's module table describes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageModule {
+    pub name: String,
+    pub path: String,
+    pub base: u64,
+    pub end: u64,
+}
+
+/// A block `size` bytes long at `offset` into module `module`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CoverageBlock {
+    pub module: u16,
+    pub offset: u32,
+    pub size: u16,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub modules: Vec<CoverageModule>,
+    pub blocks: Vec<CoverageBlock>,
+}
+
+/// A block by module name and offset
+pub type BlockKey = (String, u32);
+
+/// Blocks one run hit and the other did not
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageDiff {
+    pub only_a: Vec<BlockKey>,
+    pub only_b: Vec<BlockKey>,
+}
+
+/// Which of two runs hit a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    OnlyA,
+    OnlyB,
+    Both,
+}
+
+/// An entry of the agent's `list_modules`
+#[derive(Deserialize)]
+struct ListedModule {
+    name: String,
+    address: String,
+    size: u64,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+/// Modules of a `list_modules` result; entries that do not parse are skipped
+pub fn modules_from_json(value: &Value) -> Vec<CoverageModule> {
+    value
+        .as_array()
+        .map(|modules| {
+            modules
+                .iter()
+                .filter_map(|m| serde_json::from_value::<ListedModule>(m.clone()).ok())
+                .map(|m| {
+                    let base = string_to_u64(&m.address);
+                    CoverageModule {
+                        path: m.path.unwrap_or_else(|| m.name.clone()),
+                        name: m.name,
+                        base,
+                        end: base + m.size,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Coverage {
+    /// Place blocks given as absolute `(start, size)` in `modules`. Blocks
+    /// outside every module cannot be expressed in drcov and are dropped;
+    /// sizes beyond drcov's 16 bits are clamped.
+    pub fn new(modules: Vec<CoverageModule>, blocks: &[(u64, u64)]) -> Coverage {
+        let mut by_base: Vec<usize> = (0..modules.len()).collect();
+        by_base.sort_by_key(|&i| modules[i].base);
+        let mut placed: Vec<CoverageBlock> = blocks
+            .iter()
+            .filter_map(|&(start, size)| {
+                let at = by_base.partition_point(|&i| modules[i].base <= start);
+                let index = *by_base.get(at.checked_sub(1)?)?;
+                let module = &modules[index];
+                (start < module.end).then(|| CoverageBlock {
+                    module: index as u16,
+                    offset: (start - module.base) as u32,
+                    size: size.min(u16::MAX as u64) as u16,
+                })
+            })
+            .collect();
+        placed.sort();
+        placed.dedup();
+        Coverage {
+            modules,
+            blocks: placed,
+        }
+    }
+
+    /// Number of modules with at least one block
+    pub fn modules_hit(&self) -> usize {
+        let mut hit: Vec<u16> = self.blocks.iter().map(|b| b.module).collect();
+        hit.dedup();
+        hit.len()
+    }
+
+    /// drcov v2: a text header and module table, then the blocks as packed
+    /// little-endian `{ u32 offset; u16 size; u16 module; }` records
+    pub fn write_drcov(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "DRCOV VERSION: 2")?;
+        writeln!(out, "DRCOV FLAVOR: vlitz")?;
+        writeln!(out, "Module Table: version 2, count {}", self.modules.len())?;
+        writeln!(
+            out,
+            "Columns: id, base, end, entry, checksum, timestamp, path"
+        )?;
+        for (id, module) in self.modules.iter().enumerate() {
+            writeln!(
+                out,
+                "{:3}, {:#018x}, {:#018x}, {:#018x}, {:#010x}, {:#010x}, {}",
+                id, module.base, module.end, 0, 0, 0, module.path
+            )?;
+        }
+        writeln!(out, "BB Table: {} bbs", self.blocks.len())?;
+        for block in &self.blocks {
+            out.write_all(&block.offset.to_le_bytes())?;
+            out.write_all(&block.size.to_le_bytes())?;
+            out.write_all(&block.module.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let error = |e: io::Error| format!("{}: {}", path.display(), e);
+        let mut out = BufWriter::new(File::create(path).map_err(error)?);
+        self.write_drcov(&mut out).map_err(error)?;
+        out.flush().map_err(error)
+    }
+
+    /// Parse a drcov file. Any module table with a `Columns:` line is read,
+    /// which covers drcov's own versions 2 to 5 as well as ours.
+    pub fn read_drcov(data: &[u8]) -> Result<Coverage, String> {
+        let mut rest = data;
+        let mut next_line = || -> Result<String, String> {
+            let end = rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or("Unexpected end of file before the BB table")?;
+            let line = String::from_utf8_lossy(&rest[..end]).trim_end().to_string();
+            rest = &rest[end + 1..];
+            Ok(line)
+        };
+
+        let version = next_line()?;
+        if version != "DRCOV VERSION: 2" {
+            return Err(format!("Not a drcov v2 file ({})", version));
+        }
+        let mut line = next_line()?;
+        if line.starts_with("DRCOV FLAVOR:") {
+            line = next_line()?;
+        }
+        let count: usize = line
+            .strip_prefix("Module Table:")
+            .and_then(|t| t.rsplit("count").next())
+            .and_then(|n| n.trim().parse().ok())
+            .ok_or_else(|| format!("Expected a module table, found: {}", line))?;
+        let columns: Vec<String> = next_line()?
+            .strip_prefix("Columns:")
+            .ok_or("Module tables without a Columns line are not supported")?
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .collect();
+        let column = |names: &[&str]| {
+            columns
+                .iter()
+                .position(|c| names.contains(&c.as_str()))
+                .ok_or_else(|| format!("Module table has no {} column", names[0]))
+        };
+        let (base_at, end_at, path_at) = (
+            column(&["base", "start"])?,
+            column(&["end"])?,
+            column(&["path"])?,
+        );
+
+        let mut modules = Vec::with_capacity(count);
+        for _ in 0..count {
+            let line = next_line()?;
+            let fields: Vec<&str> = line.splitn(columns.len(), ',').map(str::trim).collect();
+            if fields.len() != columns.len() {
+                return Err(format!("Malformed module entry: {}", line));
+            }
+            let path = fields[path_at].to_string();
+            modules.push(CoverageModule {
+                name: path.rsplit(['/', '\\']).next().unwrap_or(&path).to_string(),
+                base: string_to_u64(fields[base_at]),
+                end: string_to_u64(fields[end_at]),
+                path,
+            });
+        }
+
+        let line = next_line()?;
+        let count: usize = line
+            .strip_prefix("BB Table:")
+            .and_then(|t| t.trim().strip_suffix("bbs"))
+            .and_then(|n| n.trim().parse().ok())
+            .ok_or_else(|| format!("Expected the BB table, found: {}", line))?;
+        if rest.len() < count * 8 {
+            return Err(format!(
+                "BB table is cut short ({} of {} blocks)",
+                rest.len() / 8,
+                count
+            ));
+        }
+        let mut blocks = Vec::with_capacity(count);
+        for record in rest[..count * 8].chunks_exact(8) {
+            let block = CoverageBlock {
+                offset: u32::from_le_bytes(record[0..4].try_into().unwrap()),
+                size: u16::from_le_bytes(record[4..6].try_into().unwrap()),
+                module: u16::from_le_bytes(record[6..8].try_into().unwrap()),
+            };
+            if block.module as usize >= modules.len() {
+                return Err(format!("Block refers to unknown module {}", block.module));
+            }
+            blocks.push(block);
+        }
+        blocks.sort();
+        blocks.dedup();
+        Ok(Coverage { modules, blocks })
+    }
+
+    pub fn load(path: &Path) -> Result<Coverage, String> {
+        let data = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::read_drcov(&data).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Every block as module name and offset
+    pub fn keys(&self) -> BTreeSet<BlockKey> {
+        self.blocks
+            .iter()
+            .map(|b| (self.modules[b.module as usize].name.clone(), b.offset))
+            .collect()
+    }
+}
+
+/// Blocks hit by only one of two runs
+pub fn diff(a: &Coverage, b: &Coverage) -> CoverageDiff {
+    let (a, b) = (a.keys(), b.keys());
+    CoverageDiff {
+        only_a: a.difference(&b).cloned().collect(),
+        only_b: b.difference(&a).cloned().collect(),
+    }
+}
+
+/// Which runs hit a function: those that hit its entry block, or when
+/// neither did (its symbol may not start a block), whichever runs its
+/// differing blocks came from
+pub fn function_side(entry: (bool, bool), differing: (bool, bool)) -> DiffSide {
+    match (entry, differing) {
+        ((true, false), _) | ((false, false), (true, false)) => DiffSide::OnlyA,
+        ((false, true), _) | ((false, false), (false, true)) => DiffSide::OnlyB,
+        _ => DiffSide::Both,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn modules() -> Vec<CoverageModule> {
+        modules_from_json(&json!([
+            {"name": "libc.so.6", "address": "0x7f0000", "size": 0x1000, "path": "/usr/lib/libc.so.6"},
+            {"name": "app", "address": "0x400000", "size": 0x2000, "path": "/opt/app"},
+            {"name": "broken"}
+        ]))
+    }
+
+    #[test]
+    fn test_place_blocks() {
+        let coverage = Coverage::new(
+            modules(),
+            &[
+                (0x401000, 0x10),
+                (0x7f0010, 0x20000),
+                (0x400000, 0x8),
+                (0x402000, 0x4),
+                (0x100, 0x4),
+                (0x401000, 0x10),
+            ],
+        );
+        assert_eq!(coverage.modules[1].end, 0x402000);
+        assert_eq!(
+            coverage.blocks,
+            [
+                CoverageBlock {
+                    module: 0,
+                    offset: 0x10,
+                    size: u16::MAX
+                },
+                CoverageBlock {
+                    module: 1,
+                    offset: 0,
+                    size: 8
+                },
+                CoverageBlock {
+                    module: 1,
+                    offset: 0x1000,
+                    size: 0x10
+                },
+            ]
+        );
+        assert_eq!(coverage.modules_hit(), 2);
+    }
+
+    #[test]
+    fn test_write_drcov() {
+        let coverage = Coverage::new(modules(), &[(0x400010, 6)]);
+        let mut out = Vec::new();
+        coverage.write_drcov(&mut out).unwrap();
+        let header = "DRCOV VERSION: 2\n\
+                      DRCOV FLAVOR: vlitz\n\
+                      Module Table: version 2, count 2\n\
+                      Columns: id, base, end, entry, checksum, timestamp, path\n  \
+                      0, 0x00000000007f0000, 0x00000000007f1000, 0x0000000000000000, 0x00000000, 0x00000000, /usr/lib/libc.so.6\n  \
+                      1, 0x0000000000400000, 0x0000000000402000, 0x0000000000000000, 0x00000000, 0x00000000, /opt/app\n\
+                      BB Table: 1 bbs\n";
+        assert_eq!(&out[..header.len()], header.as_bytes());
+        assert_eq!(&out[header.len()..], [0x10, 0, 0, 0, 6, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_read_drcov() {
+        let coverage = Coverage::new(modules(), &[(0x400010, 6), (0x7f0100, 2)]);
+        let mut out = Vec::new();
+        coverage.write_drcov(&mut out).unwrap();
+        let read = Coverage::read_drcov(&out).unwrap();
+        assert_eq!(read.blocks, coverage.blocks);
+        assert_eq!(read.modules[0].name, "libc.so.6");
+        assert_eq!(read.modules[1], coverage.modules[1]);
+
+        // drcov's own layout, with a module table of a later version
+        let mut drcov = b"DRCOV VERSION: 2\n\
+            DRCOV FLAVOR: drcov\n\
+            Module Table: version 4, count 1\n\
+            Columns: id, containing_id, start, end, entry, offset, checksum, timecheck, path\n  \
+            0,   0, 0x0000000000400000, 0x0000000000402000, 0x0000000000401000, 0000000000000000, 0x00000000, 0x00000000, C:\\bin\\app.exe\r\n\
+            BB Table: 1 bbs\n"
+            .to_vec();
+        drcov.extend([0x20, 0, 0, 0, 4, 0, 0, 0]);
+        let read = Coverage::read_drcov(&drcov).unwrap();
+        assert_eq!(read.modules[0].name, "app.exe");
+        assert_eq!(read.keys(), BTreeSet::from([("app.exe".to_string(), 0x20)]));
+
+        assert!(Coverage::read_drcov(&drcov[..drcov.len() - 1]).is_err());
+        assert!(Coverage::read_drcov(b"DRCOV VERSION: 3\n").is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Coverage::new(modules(), &[(0x400010, 6), (0x400020, 4), (0x7f0100, 2)]);
+        // The same module loaded elsewhere
+        let mut moved = modules();
+        moved[1].base = 0x500000;
+        moved[1].end = 0x502000;
+        let b = Coverage::new(moved, &[(0x500010, 6), (0x500040, 8)]);
+        let diff = diff(&a, &b);
+        assert_eq!(
+            diff.only_a,
+            [("app".to_string(), 0x20), ("libc.so.6".to_string(), 0x100)]
+        );
+        assert_eq!(diff.only_b, [("app".to_string(), 0x40)]);
+    }
+
+    #[test]
+    fn test_function_side() {
+        assert_eq!(function_side((true, false), (false, true)), DiffSide::OnlyA);
+        assert_eq!(function_side((false, true), (true, false)), DiffSide::OnlyB);
+        assert_eq!(function_side((true, true), (true, false)), DiffSide::Both);
+        assert_eq!(
+            function_side((false, false), (false, true)),
+            DiffSide::OnlyB
+        );
+        assert_eq!(function_side((false, false), (true, true)), DiffSide::Both);
+    }
+}
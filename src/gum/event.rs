@@ -0,0 +1,321 @@
+// src/gum/event.rs
+//
+// Hook events streamed by the agent via `send()`. The agent stamps each event
+// with `v`; events from an agent speaking a newer schema are not decoded.
+
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Hook event schema version understood by this build
+pub const HOOK_EVENT_VERSION: u64 = 1;
+
+/// Number of backtrace frames shown per event
+pub(crate) const BACKTRACE_FRAMES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HookEventKind {
+    #[serde(rename = "hook:enter")]
+    Enter,
+    #[serde(rename = "hook:leave")]
+    Leave,
+}
+
+/// A register-sized value with a preview of what it points to, if readable.
+/// Hooks with a signature also carry the parameter name and decoded value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventValue {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points_to: Option<String>,
+}
+
+/// Backtrace frame; `offset` is relative to the module base
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub address: String,
+    #[serde(default)]
+    pub module: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookEvent {
+    #[serde(rename = "type")]
+    pub kind: HookEventKind,
+    pub v: u64,
+    pub id: String,
+    pub address: String,
+    pub thread_id: u64,
+    #[serde(default)]
+    pub depth: u64,
+    /// Milliseconds since the Unix epoch, taken in the target process
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<EventValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retval: Option<EventValue>,
+    /// Out-parameters of a signature, read on leave
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub out: Vec<EventValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backtrace: Vec<Frame>,
+}
+
+impl HookEventKind {
+    /// `enter` or `leave`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEventKind::Enter => "enter",
+            HookEventKind::Leave => "leave",
+        }
+    }
+}
+
+impl Frame {
+    /// `module!symbol +offset` with whichever parts are known
+    pub fn location(&self) -> String {
+        match (&self.module, &self.symbol, self.offset) {
+            (Some(m), Some(s), Some(o)) => format!("{}!{} +{:#x}", m, s, o),
+            (Some(m), Some(s), None) => format!("{}!{}", m, s),
+            (Some(m), None, Some(o)) => format!("{} +{:#x}", m, o),
+            (Some(m), None, None) => m.to_string(),
+            (None, Some(s), _) => s.to_string(),
+            (None, None, _) => "???".to_string(),
+        }
+    }
+}
+
+impl EventValue {
+    pub(crate) fn render(&self) -> String {
+        let name = self
+            .name
+            .as_ref()
+            .map(|n| format!("{} = ", n))
+            .unwrap_or_default();
+        match &self.decoded {
+            // Hexdumps of byte buffers go below the value, indented
+            Some(decoded) if decoded.contains('\n') => {
+                let mut output = format!("{}{}", name, format!("({})", self.value).dark_grey());
+                for line in decoded.lines() {
+                    output.push_str(&format!("\n      {}", line.yellow()));
+                }
+                output
+            }
+            Some(decoded) => format!(
+                "{}{} {}",
+                name,
+                decoded.clone().yellow(),
+                format!("({})", self.value).dark_grey()
+            ),
+            None => {
+                let points_to = self
+                    .points_to
+                    .as_ref()
+                    .map(|p| format!(" -> {}", p))
+                    .unwrap_or_default();
+                format!(
+                    "{}{}{}",
+                    name,
+                    self.value.clone().yellow(),
+                    points_to.dark_grey()
+                )
+            }
+        }
+    }
+}
+
+/// Wall-clock `HH:MM:SS.mmm` (UTC) of a millisecond timestamp
+pub(crate) fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        (secs / 3600) % 24,
+        (secs / 60) % 60,
+        secs % 60,
+        ms % 1000
+    )
+}
+
+impl HookEvent {
+    /// Decode a hook enter/leave payload. `None` for other messages and for
+    /// events from a newer schema version.
+    pub fn parse(payload: &Value) -> Option<HookEvent> {
+        let version = payload.get("v")?.as_u64()?;
+        if version > HOOK_EVENT_VERSION {
+            return None;
+        }
+        serde_json::from_value(payload.clone()).ok()
+    }
+
+    pub fn render(&self) -> String {
+        let tag = match self.kind {
+            HookEventKind::Enter => "[ENTER]".green(),
+            HookEventKind::Leave => "[LEAVE]".magenta(),
+        };
+        let mut output = format!(
+            "{} {} {} @ {} (tid: {}, depth: {})",
+            format_timestamp(self.timestamp).dark_grey(),
+            tag,
+            self.id.clone().cyan(),
+            self.address.clone().yellow(),
+            self.thread_id,
+            self.depth
+        );
+
+        if !self.args.is_empty() {
+            output.push_str("\n  Args:");
+            for (i, arg) in self.args.iter().enumerate() {
+                output.push_str(&format!("\n    [{}] {}", i, arg.render()));
+            }
+        }
+
+        if !self.out.is_empty() {
+            output.push_str("\n  Out:");
+            for value in &self.out {
+                output.push_str(&format!("\n    {}", value.render()));
+            }
+        }
+
+        if let Some(retval) = &self.retval {
+            output.push_str(&format!("\n  Return: {}", retval.render()));
+        }
+
+        if !self.backtrace.is_empty() {
+            output.push_str("\n  Backtrace:");
+            for (i, frame) in self.backtrace.iter().take(BACKTRACE_FRAMES).enumerate() {
+                output.push_str(&format!(
+                    "\n    #{} {} {}",
+                    i,
+                    frame.address.clone().yellow(),
+                    frame.location().dark_grey()
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::format::plain;
+    use serde_json::json;
+
+    fn enter_payload() -> Value {
+        json!({
+            "type": "hook:enter",
+            "v": 1,
+            "id": "hook_0",
+            "address": "0x7f001230",
+            "threadId": 4242,
+            "depth": 0,
+            "timestamp": 3_723_004u64,
+            "args": [
+                {"value": "0x5555000", "pointsTo": "\"/etc/passwd\""},
+                {"value": "0x0"}
+            ],
+            "backtrace": [
+                {"address": "0x55550123", "module": "app", "symbol": "main", "offset": 0x123},
+                {"address": "0x7f009000", "module": "libc.so.6", "symbol": null, "offset": 0x9000}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_parse_enter_event() {
+        let event = HookEvent::parse(&enter_payload()).unwrap();
+        assert_eq!(event.kind, HookEventKind::Enter);
+        assert_eq!(event.thread_id, 4242);
+        assert_eq!(event.args[0].points_to.as_deref(), Some("\"/etc/passwd\""));
+        assert_eq!(event.args[1].points_to, None);
+        assert_eq!(event.backtrace[1].symbol, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_payloads() {
+        let mut newer = enter_payload();
+        newer["v"] = json!(HOOK_EVENT_VERSION + 1);
+        assert_eq!(HookEvent::parse(&newer), None);
+
+        // Pre-versioned agents sent unversioned `hook_enter` events
+        let legacy = json!({"type": "hook_enter", "id": "hook_0", "address": "0x1"});
+        assert_eq!(HookEvent::parse(&legacy), None);
+
+        let scan = json!({"type": "scan:progress", "v": 1, "scanned": 1});
+        assert_eq!(HookEvent::parse(&scan), None);
+    }
+
+    #[test]
+    fn test_render_enter_event() {
+        let out = plain(HookEvent::parse(&enter_payload()).unwrap().render());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "01:02:03.004 [ENTER] hook_0 @ 0x7f001230 (tid: 4242, depth: 0)"
+        );
+        assert_eq!(lines[2], "    [0] 0x5555000 -> \"/etc/passwd\"");
+        assert_eq!(lines[3], "    [1] 0x0");
+        assert_eq!(lines[5], "    #0 0x55550123 app!main +0x123");
+        assert_eq!(lines[6], "    #1 0x7f009000 libc.so.6 +0x9000");
+    }
+
+    #[test]
+    fn test_render_leave_event() {
+        let payload = json!({
+            "type": "hook:leave",
+            "v": 1,
+            "id": "hook_1",
+            "address": "0x1000",
+            "threadId": 1,
+            "depth": 2,
+            "timestamp": 0,
+            "retval": {"value": "0x3"}
+        });
+        let out = plain(HookEvent::parse(&payload).unwrap().render());
+        assert_eq!(
+            out,
+            "00:00:00.000 [LEAVE] hook_1 @ 0x1000 (tid: 1, depth: 2)\n  Return: 0x3"
+        );
+    }
+
+    #[test]
+    fn test_render_decoded_values() {
+        let payload = json!({
+            "type": "hook:leave",
+            "v": 1,
+            "id": "hook_2",
+            "address": "0x1000",
+            "threadId": 1,
+            "timestamp": 0,
+            "out": [{"name": "size", "value": "0x7ffe0010", "decoded": "4096"}],
+            "retval": {"name": "ret", "value": "0x1", "decoded": "1"}
+        });
+        let out = plain(HookEvent::parse(&payload).unwrap().render());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[1], "  Out:");
+        assert_eq!(lines[2], "    size = 4096 (0x7ffe0010)");
+        assert_eq!(lines[3], "  Return: ret = 1 (0x1)");
+
+        let value = EventValue {
+            value: "0x5000".into(),
+            name: Some("buf".into()),
+            decoded: Some("5000  00 01\n5010  02 03".into()),
+            points_to: None,
+        };
+        assert_eq!(
+            plain(value.render()),
+            "buf = (0x5000)\n      5000  00 01\n      5010  02 03"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+// src/gum/eventlog.rs
+//
+// `hook log <file>` tees hook events to a JSON Lines file, one event per line
+// in the shape the agent sent it (timestamp included). `events load` reads such
+// a file back so long runs can be filtered after the target is gone.
+// `hook capture` queues a hook's events here for the REPL to add to Field.
+
+use super::event::HookEvent;
+use super::filter::FilterSegment;
+use super::store::Store;
+use super::vzdata::VzData;
+use crate::util::logger;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Keys `events filter` understands, for error messages
+pub const FILTER_KEYS: &str =
+    "id, type, tid, depth, address, timestamp, argN, retval, caller, backtrace";
+
+/// The active log; events arrive on Frida's thread, commands on the REPL's
+static LOG: Mutex<Option<EventLog>> = Mutex::new(None);
+
+struct EventLog {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+static CAPTURE: Mutex<Capture> = Mutex::new(Capture {
+    remaining: BTreeMap::new(),
+    queue: Vec::new(),
+});
+
+/// Hooks being captured, with how many more events each may add, and the
+/// events captured since the REPL last took them
+struct Capture {
+    remaining: BTreeMap<String, usize>,
+    queue: Vec<HookEvent>,
+}
+
+/// Start logging to `path`, truncating it unless `append`. Replaces any
+/// active log.
+pub fn start(path: &Path, append: bool) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    *lock() = Some(EventLog {
+        path: path.to_path_buf(),
+        file,
+        written: 0,
+    });
+    Ok(())
+}
+
+/// Stop logging; the path and number of events written, if a log was active
+pub fn stop() -> Option<(PathBuf, u64)> {
+    lock().take().map(|log| (log.path, log.written))
+}
+
+/// Path and number of events written of the active log
+pub fn status() -> Option<(PathBuf, u64)> {
+    lock().as_ref().map(|log| (log.path.clone(), log.written))
+}
+
+/// Queue an event of a captured hook and append it to the active log. A
+/// failed write stops logging rather than reporting the same error for every
+/// event.
+pub fn record(event: &HookEvent) {
+    queue_capture(event);
+    let mut guard = lock();
+    let Some(log) = guard.as_mut() else {
+        return;
+    };
+    let line = match serde_json::to_string(event) {
+        Ok(line) => line,
+        Err(e) => {
+            logger::error(&format!("Failed to encode hook event: {}", e));
+            return;
+        }
+    };
+    match writeln!(log.file, "{}", line) {
+        Ok(()) => log.written += 1,
+        Err(e) => {
+            logger::error(&format!("Hook log {} stopped: {}", log.path.display(), e));
+            *guard = None;
+        }
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<EventLog>> {
+    // A panic while holding the lock leaves the log usable
+    LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Capture up to `max` further events of hook `id`
+pub fn capture(id: &str, max: usize) {
+    lock_capture().remaining.insert(id.to_string(), max);
+}
+
+/// Stop capturing hook `id`; whether it was being captured
+pub fn stop_capture(id: &str) -> bool {
+    lock_capture().remaining.remove(id).is_some()
+}
+
+/// Stop capturing every hook
+pub fn stop_all_captures() {
+    lock_capture().remaining.clear();
+}
+
+/// Hooks being captured and how many more events each may add
+pub fn captures() -> Vec<(String, usize)> {
+    let capture = lock_capture();
+    capture
+        .remaining
+        .iter()
+        .map(|(id, left)| (id.clone(), *left))
+        .collect()
+}
+
+/// Events captured since the last call, oldest first
+pub fn take_captured() -> Vec<HookEvent> {
+    std::mem::take(&mut lock_capture().queue)
+}
+
+/// A hook whose limit is reached stops being captured
+fn queue_capture(event: &HookEvent) {
+    let mut capture = lock_capture();
+    let Some(left) = capture.remaining.get_mut(&event.id) else {
+        return;
+    };
+    *left -= 1;
+    if *left == 0 {
+        capture.remaining.remove(&event.id);
+    }
+    capture.queue.push(event.clone());
+}
+
+fn lock_capture() -> std::sync::MutexGuard<'static, Capture> {
+    CAPTURE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Read a log file; see `read_events`
+pub fn load(path: &Path) -> Result<(Vec<HookEvent>, usize), String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    read_events(BufReader::new(file)).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Events of a JSON Lines log, and the number of lines that were not hook
+/// events (blank lines aside), such as a line cut short by a crash
+pub fn read_events(reader: impl BufRead) -> Result<(Vec<HookEvent>, usize), String> {
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line)
+            .ok()
+            .and_then(|v| HookEvent::parse(&v))
+        {
+            Some(event) => events.push(event),
+            None => skipped += 1,
+        }
+    }
+    Ok((events, skipped))
+}
+
+/// Whether an event passes `events filter` segments
+pub fn matches(event: &HookEvent, segments: &[FilterSegment]) -> bool {
+    Store::matches(&VzData::HookEvent(event.into()), segments)
+}
+
+/// Whether `key` is one of `FILTER_KEYS`
+pub fn is_filter_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    match key.strip_prefix("arg") {
+        Some(index) => !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()),
+        None => matches!(
+            key.as_str(),
+            "id" | "type"
+                | "kind"
+                | "tid"
+                | "thread"
+                | "depth"
+                | "address"
+                | "timestamp"
+                | "time"
+                | "retval"
+                | "caller"
+                | "backtrace"
+                | "bt"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gum::filter::parse_filter_string;
+
+    const SAMPLE: &str = r#"{"type":"hook:enter","v":1,"id":"hook_2","address":"0x1000","threadId":5,"timestamp":10,"args":[{"value":"0x5000","name":"path","decoded":"\"/etc/hosts\""},{"value":"0x3"}],"backtrace":[{"address":"0x2000","module":"app","symbol":"main","offset":32}]}
+{"type":"hook:leave","v":1,"id":"hook_2","address":"0x1000","threadId":5,"timestamp":11,"retval":{"value":"0xffffffffffffffff","decoded":"-1"}}
+
+{"type":"hook:enter","v":1,"id":"hook_3","address":"0x3000","threadId":6,"timestamp":
+{"type":"hook:enter","v":1,"id":"hook_3","address":"0x3000","threadId":7,"timestamp":12,"args":[{"value":"0x10"}]}
+"#;
+
+    fn timestamps(events: &[HookEvent], filter: &str) -> Vec<u64> {
+        let segments = parse_filter_string(filter).unwrap();
+        events
+            .iter()
+            .filter(|e| matches(e, &segments))
+            .map(|e| e.timestamp)
+            .collect()
+    }
+
+    #[test]
+    fn test_read_events() {
+        let (events, skipped) = read_events(SAMPLE.as_bytes()).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            events[1].retval.as_ref().unwrap().decoded.as_deref(),
+            Some("-1")
+        );
+    }
+
+    #[test]
+    fn test_filter_events() {
+        let (events, _) = read_events(SAMPLE.as_bytes()).unwrap();
+        assert_eq!(timestamps(&events, "id=hook_2 & tid=5"), [10, 11]);
+        assert_eq!(timestamps(&events, "type=leave"), [11]);
+        assert_eq!(timestamps(&events, "arg0 = /etc/hosts"), [10]);
+        assert_eq!(timestamps(&events, "arg1 >= 3"), [10]);
+        assert_eq!(timestamps(&events, "arg0 = 0x10 | retval < 0"), [11, 12]);
+        assert_eq!(timestamps(&events, "caller : main"), [10]);
+        assert!(timestamps(&events, "unknown = 1").is_empty());
+    }
+
+    #[test]
+    fn test_log_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("vlitz_eventlog_{}.jsonl", std::process::id()));
+        let (events, _) = read_events(SAMPLE.as_bytes()).unwrap();
+        start(&path, false).unwrap();
+        for event in &events {
+            record(event);
+        }
+        assert_eq!(stop(), Some((path.clone(), 3)));
+        record(&events[0]);
+        assert_eq!(load(&path).unwrap(), (events, 0));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_capture_limit() {
+        // An ID of its own, so events recorded by other tests are not captured
+        let (mut events, _) = read_events(SAMPLE.as_bytes()).unwrap();
+        for event in &mut events {
+            event.id = "hook_capture".into();
+        }
+        capture("hook_capture", 2);
+        assert_eq!(captures(), [("hook_capture".to_string(), 2)]);
+        for event in &events {
+            queue_capture(event);
+        }
+        assert!(captures().is_empty());
+        assert_eq!(take_captured(), events[..2]);
+        assert!(take_captured().is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+// src/gum/expr.rs
+//
+// Address expressions: numbers, names resolved by the session (modules,
+// `module!export`, selectors, Lib labels, symbols, registers, `$nav`),
+// `+ - * /`, parentheses and `[expr]` pointer dereference.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(u64),
+    Name(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(u64),
+    Name(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Deref(Box<Expr>),
+}
+
+/// Supplies the session state an expression refers to
+pub trait Resolver {
+    /// Resolve a bare name to an address
+    fn resolve_name(&mut self, name: &str) -> Result<u64, String>;
+    /// Read a pointer-sized value at `address`
+    fn read_pointer(&mut self, address: u64) -> Result<u64, String>;
+}
+
+fn is_name_char(c: char) -> bool {
+    !c.is_whitespace() && !"+-*/()[]\"'".contains(c)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' | '(' | ')' | '[' | ']' => {
+                chars.next();
+                tokens.push(match c {
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    _ => Token::RBracket,
+                });
+            }
+            // Quoted names allow operator characters, e.g. "libc-2.31.so"+0x10,
+            // and may be followed by the rest of a name, as in "ld-2.31.so"!open
+            '"' | '\'' => {
+                chars.next();
+                let rest = &input[i + 1..];
+                let end = rest
+                    .find(c)
+                    .ok_or_else(|| format!("Unterminated quote in: {}", input))?;
+                let mut name = rest[..end].to_string();
+                for _ in 0..rest[..end].chars().count() + 1 {
+                    chars.next();
+                }
+                while let Some(&(_, c)) = chars.peek() {
+                    if !is_name_char(c) {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            }
+            _ => {
+                let mut end = input.len();
+                while let Some(&(j, c)) = chars.peek() {
+                    if !is_name_char(c) {
+                        end = j;
+                        break;
+                    }
+                    chars.next();
+                }
+                let word = &input[i..end];
+                if word.starts_with(|c: char| c.is_ascii_digit()) {
+                    let n = crate::util::format::parse_hex_or_decimal(word)?;
+                    tokens.push(Token::Num(n));
+                } else {
+                    tokens.push(Token::Name(word.to_string()));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(ref t) if *t == expected => Ok(()),
+            Some(t) => Err(format!("Expected {:?}, found {:?}", expected, t)),
+            None => Err(format!("Expected {:?} at end of expression", expected)),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            match self.peek() {
+                Some(Token::Plus) => {
+                    self.next();
+                    lhs = Expr::Add(Box::new(lhs), Box::new(self.term()?));
+                }
+                Some(Token::Minus) => {
+                    self.next();
+                    lhs = Expr::Sub(Box::new(lhs), Box::new(self.term()?));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Star) => {
+                    self.next();
+                    lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+                }
+                Some(Token::Slash) => {
+                    self.next();
+                    lhs = Expr::Div(Box::new(lhs), Box::new(self.unary()?));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Minus) {
+            self.next();
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::Name(name)) => Ok(Expr::Name(name)),
+            Some(Token::LParen) => {
+                let inner = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::LBracket) => {
+                let inner = self.expr()?;
+                self.expect(Token::RBracket)?;
+                Ok(Expr::Deref(Box::new(inner)))
+            }
+            Some(t) => Err(format!("Unexpected {:?} in expression", t)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+pub fn parse_expr(input: &str) -> Result<Expr, String> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err("Empty expression".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("Unexpected {:?} in expression", t)),
+    }
+}
+
+pub fn eval_expr<R: Resolver + ?Sized>(expr: &Expr, resolver: &mut R) -> Result<u64, String> {
+    Ok(match expr {
+        Expr::Num(n) => *n,
+        Expr::Name(name) => resolver.resolve_name(name)?,
+        Expr::Neg(e) => eval_expr(e, resolver)?.wrapping_neg(),
+        Expr::Add(a, b) => eval_expr(a, resolver)?.wrapping_add(eval_expr(b, resolver)?),
+        Expr::Sub(a, b) => eval_expr(a, resolver)?.wrapping_sub(eval_expr(b, resolver)?),
+        Expr::Mul(a, b) => eval_expr(a, resolver)?.wrapping_mul(eval_expr(b, resolver)?),
+        Expr::Div(a, b) => {
+            let lhs = eval_expr(a, resolver)?;
+            match eval_expr(b, resolver)? {
+                0 => return Err("Division by zero".to_string()),
+                rhs => lhs / rhs,
+            }
+        }
+        Expr::Deref(e) => {
+            let address = eval_expr(e, resolver)?;
+            resolver.read_pointer(address)?
+        }
+    })
+}
+
+/// Parse and evaluate an address expression. A single word that does not
+/// evaluate is tried as a whole name, so unquoted `libc-2.31.so` still resolves.
+pub fn evaluate<R: Resolver + ?Sized>(input: &str, resolver: &mut R) -> Result<u64, String> {
+    let result = parse_expr(input).and_then(|expr| eval_expr(&expr, resolver));
+    if result.is_err() && input.contains('-') && input.chars().all(|c| c == '-' || is_name_char(c))
+    {
+        if let Ok(address) = resolver.resolve_name(input) {
+            return Ok(address);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct MockResolver {
+        names: HashMap<&'static str, u64>,
+        memory: HashMap<u64, u64>,
+    }
+
+    impl Resolver for MockResolver {
+        fn resolve_name(&mut self, name: &str) -> Result<u64, String> {
+            self.names
+                .get(name)
+                .copied()
+                .ok_or_else(|| format!("Unknown name: {}", name))
+        }
+
+        fn read_pointer(&mut self, address: u64) -> Result<u64, String> {
+            self.memory
+                .get(&address)
+                .copied()
+                .ok_or_else(|| format!("Unmapped address: {:#x}", address))
+        }
+    }
+
+    fn resolver() -> MockResolver {
+        MockResolver {
+            names: HashMap::from([
+                ("libc.so.6", 0x7f00_0000),
+                ("libc.so.6!open", 0x7f00_1230),
+                ("libc-2.31.so", 0x7e00_0000),
+                ("libc-2.31.so!open", 0x7e00_1230),
+                ("field:3", 0x5000),
+                ("rsp", 0x7ffc_0000),
+                ("$nav", 0x4000),
+            ]),
+            memory: HashMap::from([(0x7ffc_0010, 0xdead_beef), (0xdead_beef, 0x1234)]),
+        }
+    }
+
+    #[test]
+    fn test_eval_number() {
+        assert_eq!(evaluate("0x1000", &mut resolver()), Ok(0x1000));
+        assert_eq!(evaluate("4096", &mut resolver()), Ok(0x1000));
+    }
+
+    #[test]
+    fn test_eval_module_offset() {
+        assert_eq!(
+            evaluate("libc.so.6+0x1234", &mut resolver()),
+            Ok(0x7f00_1234)
+        );
+        assert_eq!(
+            evaluate("libc.so.6!open+8", &mut resolver()),
+            Ok(0x7f00_1238)
+        );
+    }
+
+    #[test]
+    fn test_eval_selector_and_nav() {
+        assert_eq!(evaluate("field:3+0x20", &mut resolver()), Ok(0x5020));
+        assert_eq!(evaluate("$nav - 0x10", &mut resolver()), Ok(0x3ff0));
+    }
+
+    #[test]
+    fn test_eval_deref() {
+        assert_eq!(evaluate("[rsp+0x10]", &mut resolver()), Ok(0xdead_beef));
+        assert_eq!(evaluate("[[rsp+0x10]]+1", &mut resolver()), Ok(0x1235));
+    }
+
+    #[test]
+    fn test_eval_precedence_and_parens() {
+        assert_eq!(evaluate("0x10+2*3", &mut resolver()), Ok(0x16));
+        assert_eq!(evaluate("(0x10+2)*3", &mut resolver()), Ok(0x36));
+        assert_eq!(evaluate("-1", &mut resolver()), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn test_eval_quoted_name() {
+        assert_eq!(
+            evaluate("\"libc-2.31.so\"+0x10", &mut resolver()),
+            Ok(0x7e00_0010)
+        );
+        assert_eq!(
+            evaluate("\"libc-2.31.so\"!open+8", &mut resolver()),
+            Ok(0x7e00_1238)
+        );
+    }
+
+    #[test]
+    fn test_eval_command_line_words() {
+        let words = crate::gum::session::parse_command(
+            "read \"libc-2.31.so\"+0x10 libc-2.31.so 'libc-2.31.so'!open",
+        );
+        let results: Vec<_> = words[1..]
+            .iter()
+            .map(|w| evaluate(&w.text, &mut resolver()))
+            .collect();
+        assert_eq!(
+            results,
+            vec![Ok(0x7e00_0010), Ok(0x7e00_0000), Ok(0x7e00_1230)]
+        );
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        assert!(evaluate("", &mut resolver()).is_err());
+        assert!(evaluate("(1+2", &mut resolver()).is_err());
+        assert!(evaluate("[rsp]", &mut resolver()).is_err());
+        assert!(evaluate("1/0", &mut resolver()).is_err());
+        assert!(evaluate("nope+1", &mut resolver()).is_err());
+        assert!(evaluate("1 2", &mut resolver()).is_err());
+    }
+}
@@ -54,19 +54,19 @@ fn string_to_filter_operator(op_str: &str) -> Result<FilterOperator, String> {
     }
 }
 
-fn parse_value(s: &str) -> Result<FilterValue, String> {
+pub(crate) fn parse_value(s: &str) -> FilterValue {
     if s.starts_with("0x") || s.starts_with("0X") {
         if let Ok(num) = u64::from_str_radix(&s[2..], 16) {
-            return Ok(FilterValue::Number(num as f64));
+            return FilterValue::Number(num as f64);
         }
     }
     if let Ok(num) = s.parse::<f64>() {
-        return Ok(FilterValue::Number(num));
+        return FilterValue::Number(num);
     }
     if let Ok(b) = s.parse::<bool>() {
-        return Ok(FilterValue::Bool(b));
+        return FilterValue::Bool(b);
     }
-    Ok(FilterValue::String(s.to_string()))
+    FilterValue::String(s.to_string())
 }
 
 pub fn parse_filter_string(input: &str) -> Result<Vec<FilterSegment>, String> {
@@ -115,7 +115,7 @@ pub fn parse_filter_string(input: &str) -> Result<Vec<FilterSegment>, String> {
                     return Err("Missing value".to_string());
                 };
 
-                let parsed_value = parse_value(value_str_inner)?;
+                let parsed_value = parse_value(value_str_inner);
 
                 result_filter_segments.push(FilterSegment::Condition(FilterCondition {
                     key,
@@ -134,6 +134,34 @@ pub fn parse_filter_string(input: &str) -> Result<Vec<FilterSegment>, String> {
     Ok(result_filter_segments)
 }
 
+/// Evaluate filter segments left to right with `test` deciding each
+/// condition; `&` and `|` bind equally tightly. No conditions pass everything.
+pub fn evaluate_segments(
+    segments: &[FilterSegment],
+    mut test: impl FnMut(&FilterCondition) -> bool,
+) -> bool {
+    let mut acc: Option<bool> = None;
+    let mut current_op = LogicalOperator::And;
+    for segment in segments {
+        match segment {
+            FilterSegment::Condition(cond) => {
+                let res = test(cond);
+                acc = Some(match acc {
+                    None => res,
+                    Some(prev) => match current_op {
+                        LogicalOperator::And => prev && res,
+                        LogicalOperator::Or => prev || res,
+                    },
+                });
+            }
+            FilterSegment::Logical(op) => {
+                current_op = op.clone();
+            }
+        }
+    }
+    acc.unwrap_or(true)
+}
+
 // Helper to convert FilterValue to serde_json::Value
 fn filter_value_to_json(fv: &FilterValue) -> Value {
     match fv {
@@ -180,3 +208,59 @@ pub fn parse_filter_string_to_json(input: &str) -> Result<Value, String> {
     }
     Ok(Value::Array(json_array))
 }
+
+/// Keys a hook condition can test; the agent computes them on each call
+fn is_hook_condition_key(key: &str) -> bool {
+    let re = Regex::new(r"^(arg\d+|w?str\(arg\d+\)|tid|depth|caller)$")
+        .expect("Failed to compile hook key regex");
+    re.is_match(key)
+}
+
+/// Parse a `hook add --if` condition such as `arg0 = 3 & str(arg1) : "config"`
+/// into the filter JSON evaluated by `filtered()` in the agent
+pub fn parse_hook_condition(input: &str) -> Result<Value, String> {
+    let segments = parse_filter_string(input)?;
+    if segments.is_empty() {
+        return Err("Empty hook condition".to_string());
+    }
+    for segment in &segments {
+        if let FilterSegment::Condition(cond) = segment {
+            if !is_hook_condition_key(&cond.key) {
+                return Err(format!(
+                    "Unknown hook condition key '{}' (expected argN, str(argN), wstr(argN), tid, depth or caller)",
+                    cond.key
+                ));
+            }
+        }
+    }
+    parse_filter_string_to_json(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hook_condition() {
+        assert_eq!(
+            parse_hook_condition("arg0 = 3 & str(arg1) : \"config\"").unwrap(),
+            json!([["arg0", "=", 3.0], "and", ["str(arg1)", ":", "config"]])
+        );
+        assert_eq!(
+            parse_hook_condition("tid = 1234 | caller : libfoo").unwrap(),
+            json!([["tid", "=", 1234.0], "or", ["caller", ":", "libfoo"]])
+        );
+        assert_eq!(
+            parse_hook_condition("wstr(arg2) != 0x10").unwrap(),
+            json!([["wstr(arg2)", "!=", 16.0]])
+        );
+    }
+
+    #[test]
+    fn test_parse_hook_condition_errors() {
+        assert!(parse_hook_condition("").is_err());
+        assert!(parse_hook_condition("fd = 3").is_err());
+        assert!(parse_hook_condition("str(fd) : x").is_err());
+        assert!(parse_hook_condition("arg0 ~ 3").is_err());
+    }
+}
@@ -1,126 +1,43 @@
 // src/gum/handler.rs
+use super::event::HookEvent;
+use super::eventlog;
+use super::stalk::{self, StalkRenderer};
+use super::trace::{self, TraceRenderer};
+use super::watch::WatchHit;
 use crate::util::logger;
 use crossterm::style::Stylize;
 use frida::{Message, MessageLogLevel};
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub struct Handler;
+/// Set when a thread stops at a break hook, so the prompt knows to look
+static BREAK_HIT: AtomicBool = AtomicBool::new(false);
+
+/// Whether a thread stopped at a break hook since the last call
+pub fn take_break_hit() -> bool {
+    BREAK_HIT.swap(false, Ordering::SeqCst)
+}
+
+#[derive(Default)]
+pub struct Handler {
+    trace: TraceRenderer,
+    stalk: StalkRenderer,
+}
 
 impl Handler {
-    /// Parse and format hook event messages
+    /// Parse and format hook event messages and watchpoint hits; hook events
+    /// also go to the `hook log` file, if one is open
     fn format_hook_event(payload: &Value) -> Option<String> {
+        if let Some(event) = HookEvent::parse(payload) {
+            eventlog::record(&event);
+            return Some(event.render());
+        }
+        if let Some(hit) = WatchHit::parse(payload) {
+            return Some(hit.render());
+        }
         let event_type = payload.get("type")?.as_str()?;
 
         match event_type {
-            "hook:enter" => {
-                let id = payload.get("id").and_then(|v| v.as_str()).unwrap_or("?");
-                let address = payload
-                    .get("address")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("?");
-                let thread = payload
-                    .get("threadId")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let depth = payload.get("depth").and_then(|v| v.as_u64()).unwrap_or(0);
-
-                let mut output = format!(
-                    "{} {} @ {} (tid: {}, depth: {})",
-                    "[ENTER]".green(),
-                    id.to_string().cyan(),
-                    address.to_string().yellow(),
-                    thread,
-                    depth
-                );
-
-                // Format arguments if present
-                if let Some(args) = payload.get("args").and_then(|v| v.as_array()) {
-                    if !args.is_empty() {
-                        output.push_str("\n  Args:");
-                        for (i, arg) in args.iter().enumerate() {
-                            let value = arg.get("value").and_then(|v| v.as_str()).unwrap_or("?");
-                            let ptr_info = arg
-                                .get("pointsTo")
-                                .and_then(|v| v.as_str())
-                                .map(|s| format!(" -> {}", s))
-                                .unwrap_or_default();
-                            output.push_str(&format!(
-                                "\n    [{}] {}{}",
-                                i,
-                                value.yellow(),
-                                ptr_info.dark_grey()
-                            ));
-                        }
-                    }
-                }
-
-                // Format backtrace if present
-                if let Some(bt) = payload.get("backtrace").and_then(|v| v.as_array()) {
-                    if !bt.is_empty() {
-                        output.push_str("\n  Backtrace:");
-                        for (i, frame) in bt.iter().take(8).enumerate() {
-                            let addr = frame.get("address").and_then(|v| v.as_str()).unwrap_or("?");
-                            let module = frame.get("module").and_then(|v| v.as_str());
-                            let symbol = frame.get("symbol").and_then(|v| v.as_str());
-                            let offset = frame.get("offset").and_then(|v| v.as_i64());
-
-                            let location = match (module, symbol, offset) {
-                                (Some(m), Some(s), Some(o)) => format!("{}!{} +{:#x}", m, s, o),
-                                (Some(m), Some(s), None) => format!("{}!{}", m, s),
-                                (Some(m), None, _) => m.to_string(),
-                                _ => String::new(),
-                            };
-
-                            output.push_str(&format!(
-                                "\n    #{} {} {}",
-                                i,
-                                addr.yellow(),
-                                location.dark_grey()
-                            ));
-                        }
-                    }
-                }
-
-                Some(output)
-            }
-            "hook:leave" => {
-                let id = payload.get("id").and_then(|v| v.as_str()).unwrap_or("?");
-                let address = payload
-                    .get("address")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("?");
-                let thread = payload
-                    .get("threadId")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let depth = payload.get("depth").and_then(|v| v.as_u64()).unwrap_or(0);
-
-                let mut output = format!(
-                    "{} {} @ {} (tid: {}, depth: {})",
-                    "[LEAVE]".magenta(),
-                    id.to_string().cyan(),
-                    address.to_string().yellow(),
-                    thread,
-                    depth
-                );
-
-                // Format return value if present
-                if let Some(retval) = payload.get("retval") {
-                    let value = retval.get("value").and_then(|v| v.as_str()).unwrap_or("?");
-                    let ptr_info = retval
-                        .get("pointsTo")
-                        .and_then(|v| v.as_str())
-                        .map(|s| format!(" -> {}", s))
-                        .unwrap_or_default();
-                    output.push_str(&format!(
-                        "\n  Return: {}{}",
-                        value.yellow(),
-                        ptr_info.dark_grey()
-                    ));
-                }
-
-                Some(output)
-            }
             "hook:attached" => {
                 let id = payload.get("id").and_then(|v| v.as_str()).unwrap_or("?");
                 let address = payload
@@ -155,6 +72,38 @@ impl Handler {
                     error
                 ))
             }
+            "freeze:error" => {
+                let id = payload.get("id").and_then(|v| v.as_str()).unwrap_or("?");
+                let error = payload
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown error");
+                Some(format!(
+                    "{} Freeze {} stopped: {}",
+                    "[ERROR]".red(),
+                    id.to_string().cyan(),
+                    error
+                ))
+            }
+            "break:hit" => {
+                BREAK_HIT.store(true, Ordering::SeqCst);
+                let id = payload.get("id").and_then(|v| v.as_str()).unwrap_or("?");
+                let address = payload
+                    .get("address")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("?");
+                let thread_id = payload
+                    .get("threadId")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                Some(format!(
+                    "{} Thread {} stopped at {} @ {}; `continue` resumes it",
+                    "[BREAK]".red(),
+                    thread_id.to_string().yellow(),
+                    id.to_string().cyan(),
+                    address.to_string().yellow()
+                ))
+            }
             "scan:progress" => {
                 let scanned = payload.get("scanned").and_then(|v| v.as_u64()).unwrap_or(0);
                 let total = payload.get("total").and_then(|v| v.as_u64()).unwrap_or(0);
@@ -179,11 +128,25 @@ impl Handler {
 }
 
 impl frida::ScriptHandler for Handler {
-    fn on_message(&mut self, message: &Message, _data: Option<Vec<u8>>) {
+    fn on_message(&mut self, message: &Message, data: Option<Vec<u8>>) {
         match message {
             Message::Send(s) => {
                 // Frida's SendPayload has 'type' field and 'returns' contains the JSON payload
                 if s.payload.r#type == "send" {
+                    if let Some(events) = trace::parse_batch(&s.payload.returns) {
+                        for event in &events {
+                            for line in self.trace.render(event) {
+                                println!("{}", line);
+                            }
+                        }
+                        return;
+                    }
+                    if let Some(batch) = stalk::parse_batch(&s.payload.returns, data.as_deref()) {
+                        for line in self.stalk.render(batch) {
+                            println!("{}", line);
+                        }
+                        return;
+                    }
                     if let Some(formatted) = Self::format_hook_event(&s.payload.returns) {
                         println!("{}", formatted);
                         return;
@@ -0,0 +1,196 @@
+// src/gum/hookstats.rs
+//
+// Per-hook call counters and timings kept by the agent (`hook_stats` RPC),
+// rendered by `hook stats`.
+
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreadHits {
+    pub id: u64,
+    pub calls: u64,
+}
+
+/// Times are microseconds between onEnter and onLeave; `timed` counts the
+/// calls that returned, so recursion or a call still running is not averaged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookStats {
+    pub id: String,
+    pub address: String,
+    pub calls: u64,
+    #[serde(default)]
+    pub threads: Vec<ThreadHits>,
+    #[serde(default)]
+    pub timed: u64,
+    #[serde(default)]
+    pub total_time: f64,
+    #[serde(default)]
+    pub max_time: f64,
+}
+
+/// Columns `hook stats --sort` accepts
+pub const SORT_KEYS: &[&str] = &["calls", "threads", "total", "avg", "max", "id"];
+
+impl HookStats {
+    pub fn avg_time(&self) -> f64 {
+        if self.timed == 0 {
+            0.0
+        } else {
+            self.total_time / self.timed as f64
+        }
+    }
+}
+
+/// `12.3us`, `4.56ms` or `1.23s`
+pub fn format_duration(us: f64) -> String {
+    if us < 1_000.0 {
+        format!("{:.1}us", us)
+    } else if us < 1_000_000.0 {
+        format!("{:.2}ms", us / 1_000.0)
+    } else {
+        format!("{:.2}s", us / 1_000_000.0)
+    }
+}
+
+/// Numeric order of hook IDs, so `hook_10` sorts after `hook_9`
+fn id_order(s: &HookStats) -> (u64, String) {
+    let n = s.id.trim_start_matches("hook_").parse().unwrap_or(u64::MAX);
+    (n, s.id.clone())
+}
+
+/// Sort by a column of `SORT_KEYS`; numbers descending, IDs ascending
+pub fn sort_stats(stats: &mut [HookStats], key: &str) -> Result<(), String> {
+    let column: fn(&HookStats) -> f64 = match key {
+        "calls" => |s| s.calls as f64,
+        "threads" => |s| s.threads.len() as f64,
+        "total" => |s| s.total_time,
+        "avg" => |s| s.avg_time(),
+        "max" => |s| s.max_time,
+        "id" => {
+            stats.sort_by_key(id_order);
+            return Ok(());
+        }
+        _ => {
+            return Err(format!(
+                "Unknown sort key '{}' (expected {})",
+                key,
+                SORT_KEYS.join(", ")
+            ))
+        }
+    };
+    stats.sort_by(|a, b| column(b).total_cmp(&column(a)));
+    Ok(())
+}
+
+/// One row per hook
+pub fn render_table(stats: &[HookStats]) -> String {
+    let mut output = format!(
+        "  {:<10} {:<18} {:>10} {:>7} {:>10} {:>10} {:>10}",
+        "ID", "Address", "Calls", "Threads", "Total", "Avg", "Max"
+    )
+    .dark_grey()
+    .to_string();
+    for s in stats {
+        output.push_str(&format!(
+            "\n  {} {} {:>10} {:>7} {:>10} {:>10} {:>10}",
+            format!("{:<10}", s.id).cyan(),
+            format!("{:<18}", s.address).yellow(),
+            s.calls,
+            s.threads.len(),
+            format_duration(s.total_time),
+            format_duration(s.avg_time()),
+            format_duration(s.max_time)
+        ));
+    }
+    output
+}
+
+/// Totals for a single hook with its per-thread breakdown
+pub fn render_detail(s: &HookStats) -> String {
+    let mut output = format!(
+        "{} @ {}\n  Calls: {} ({} returned)\n  Time:  total {}, avg {}, max {}",
+        s.id.clone().cyan(),
+        s.address.clone().yellow(),
+        s.calls,
+        s.timed,
+        format_duration(s.total_time),
+        format_duration(s.avg_time()),
+        format_duration(s.max_time)
+    );
+    if !s.threads.is_empty() {
+        let mut threads = s.threads.clone();
+        threads.sort_by(|a, b| b.calls.cmp(&a.calls).then(a.id.cmp(&b.id)));
+        output.push_str("\n  Threads:");
+        for t in threads {
+            output.push_str(&format!(
+                "\n    {} {:>10}",
+                format!("{:>8}", t.id).yellow(),
+                t.calls
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::format::plain;
+    use serde_json::json;
+
+    fn stats() -> Vec<HookStats> {
+        serde_json::from_value(json!([
+            {"id": "hook_9", "address": "0x1000", "calls": 5, "timed": 5,
+             "totalTime": 50.0, "maxTime": 20.0,
+             "threads": [{"id": 1, "calls": 2}, {"id": 2, "calls": 3}]},
+            {"id": "hook_10", "address": "0x2000", "calls": 100, "timed": 99,
+             "totalTime": 495.0, "maxTime": 15.0, "threads": [{"id": 1, "calls": 100}]},
+            {"id": "hook_2", "address": "0x3000", "calls": 0}
+        ]))
+        .unwrap()
+    }
+
+    fn ids(stats: &[HookStats]) -> Vec<&str> {
+        stats.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_stats() {
+        let mut s = stats();
+        sort_stats(&mut s, "calls").unwrap();
+        assert_eq!(ids(&s), ["hook_10", "hook_9", "hook_2"]);
+        sort_stats(&mut s, "avg").unwrap();
+        assert_eq!(ids(&s), ["hook_9", "hook_10", "hook_2"]);
+        sort_stats(&mut s, "threads").unwrap();
+        assert_eq!(ids(&s), ["hook_9", "hook_10", "hook_2"]);
+        sort_stats(&mut s, "id").unwrap();
+        assert_eq!(ids(&s), ["hook_2", "hook_9", "hook_10"]);
+        assert!(sort_stats(&mut s, "name").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.0), "0.0us");
+        assert_eq!(format_duration(999.94), "999.9us");
+        assert_eq!(format_duration(4_560.0), "4.56ms");
+        assert_eq!(format_duration(1_230_000.0), "1.23s");
+    }
+
+    #[test]
+    fn test_render() {
+        let s = stats();
+        let table = plain(render_table(&s));
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].starts_with("  hook_10    0x2000"));
+        assert!(lines[2].ends_with("495.0us      5.0us     15.0us"));
+
+        let detail = plain(render_detail(&s[0]));
+        assert_eq!(
+            detail,
+            "hook_9 @ 0x1000\n  Calls: 5 (5 returned)\n  Time:  total 50.0us, avg 10.0us, max 20.0us\n  Threads:\n           2          3\n           1          2"
+        );
+    }
+}
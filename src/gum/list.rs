@@ -1,5 +1,7 @@
 // src/gum/list.rs
-use super::vzdata::{string_to_u64, VzBase, VzDataType, VzFunction, VzModule, VzRange, VzVariable};
+use super::vzdata::{
+    new_base, string_to_u64, VzDataType, VzFunction, VzModule, VzRange, VzVariable,
+};
 use crate::gum::filter::parse_filter_string_to_json;
 use frida::Script;
 use serde_json::{json, Value};
@@ -40,10 +42,7 @@ pub fn list_modules(script: &mut Script, filter: Option<&str>) -> Result<Vec<VzM
                 .ok_or_else(|| "Expected u64 size of module".to_string())?;
 
             Ok(VzModule {
-                base: VzBase {
-                    data_type: VzDataType::Module,
-                    is_saved: false,
-                },
+                base: new_base(VzDataType::Module),
                 name,
                 address: string_to_u64(&address),
                 size: size as usize,
@@ -91,10 +90,7 @@ pub fn list_ranges(
                 .ok_or_else(|| "Expected string protection of range".to_string())?
                 .to_string();
             Ok(VzRange {
-                base: VzBase {
-                    data_type: VzDataType::Range,
-                    is_saved: false,
-                },
+                base: new_base(VzDataType::Range),
                 address: string_to_u64(&address),
                 size: size as usize,
                 protection,
@@ -142,10 +138,7 @@ pub fn list_functions(
                 .ok_or_else(|| "Expected string module of function".to_string())?
                 .to_string();
             Ok(VzFunction {
-                base: VzBase {
-                    data_type: VzDataType::Function,
-                    is_saved: false,
-                },
+                base: new_base(VzDataType::Function),
                 name,
                 address: string_to_u64(&address),
                 module,
@@ -193,10 +186,7 @@ pub fn list_variables(
                 .ok_or_else(|| "Expected string module of variable".to_string())?
                 .to_string();
             Ok(VzVariable {
-                base: VzBase {
-                    data_type: VzDataType::Variable,
-                    is_saved: false,
-                },
+                base: new_base(VzDataType::Variable),
                 name,
                 address: string_to_u64(&address),
                 module,
@@ -1,8 +1,9 @@
-use super::vzdata::{VzData, VzValueType};
+use super::vzdata::{string_to_u64, VzData, VzValueType};
 use crate::util::format::{get_header_padding, lengthed};
 use crossterm::style::Stylize;
 use frida::Script;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
 macro_rules! impl_reader {
     ($name:ident, $ret:ty, $export:expr, $conv:ident) => {
@@ -29,27 +30,6 @@ macro_rules! impl_reader {
     };
 }
 
-macro_rules! impl_writer {
-    ($name:ident, $export:expr, $typ:ty) => {
-        pub fn $name(script: &mut Script, addr: u64, value: $typ) -> Result<(), String> {
-            if !check_write_protection(script, addr)? {
-                let protection = get_memory_protection(script, addr)?;
-                return Err(format!(
-                    "Cannot write to address {:#x}: insufficient write permissions (protection: {})",
-                    addr,
-                    protection.unwrap_or("unknown".to_string())
-                ));
-            }
-
-            script
-                .exports
-                .call($export, Some(json!([addr, value])))
-                .map_err(|e| e.to_string())?;
-            Ok(())
-        }
-    };
-}
-
 impl_reader!(readbyte, i8, "reader_byte", as_i64);
 impl_reader!(readubyte, u8, "reader_ubyte", as_u64);
 impl_reader!(readshort, i16, "reader_short", as_i64);
@@ -62,6 +42,19 @@ impl_reader!(readfloat, f32, "reader_float", as_f64);
 impl_reader!(readdouble, f64, "reader_double", as_f64);
 
 pub fn readstring(script: &mut Script, addr: u64, len: Option<usize>) -> Result<String, String> {
+    read_text(script, "reader_string", addr, len)
+}
+
+pub fn readutf16(script: &mut Script, addr: u64, len: Option<usize>) -> Result<String, String> {
+    read_text(script, "reader_utf16", addr, len)
+}
+
+fn read_text(
+    script: &mut Script,
+    export: &str,
+    addr: u64,
+    len: Option<usize>,
+) -> Result<String, String> {
     if !check_read_protection(script, addr)? {
         let protection = get_memory_protection(script, addr)?;
         return Err(format!(
@@ -73,7 +66,7 @@ pub fn readstring(script: &mut Script, addr: u64, len: Option<usize>) -> Result<
 
     let data = script
         .exports
-        .call("reader_string", Some(json!([addr, len])))
+        .call(export, Some(json!([addr, len])))
         .map_err(|e| e.to_string())?;
     let binding = data.ok_or_else(|| "No data returned".to_string())?;
     let value = binding
@@ -103,49 +96,25 @@ pub fn readbytes(script: &mut Script, addr: u64, len: usize) -> Result<Vec<u8>,
     Ok(arr.iter().map(|v| v.as_u64().unwrap_or(0) as u8).collect())
 }
 
-impl_writer!(writebyte, "writer_byte", i8);
-impl_writer!(writeubyte, "writer_ubyte", u8);
-impl_writer!(writeshort, "writer_short", i16);
-impl_writer!(writeushort, "writer_ushort", u16);
-impl_writer!(writeint, "writer_int", i32);
-impl_writer!(writeuint, "writer_uint", u32);
-impl_writer!(writelong, "writer_long", i64);
-impl_writer!(writeulong, "writer_ulong", u64);
-impl_writer!(writefloat, "writer_float", f32);
-impl_writer!(writedouble, "writer_double", f64);
-
-pub fn writestring(script: &mut Script, addr: u64, value: &str) -> Result<(), String> {
-    if !check_write_protection(script, addr)? {
-        let protection = get_memory_protection(script, addr)?;
-        return Err(format!(
-            "Cannot write to address {:#x}: insufficient write permissions (protection: {})",
-            addr,
-            protection.unwrap_or("unknown".to_string())
-        ));
-    }
-
-    script
-        .exports
-        .call("writer_string", Some(json!([addr, value])))
-        .map_err(|e| e.to_string())?;
-    Ok(())
-}
-
-pub fn writebytes(script: &mut Script, addr: u64, value: &[u8]) -> Result<(), String> {
-    if !check_write_protection(script, addr)? {
+pub fn readpointer(script: &mut Script, addr: u64) -> Result<u64, String> {
+    if !check_read_protection(script, addr)? {
         let protection = get_memory_protection(script, addr)?;
         return Err(format!(
-            "Cannot write to address {:#x}: insufficient write permissions (protection: {})",
+            "Cannot read from address {:#x}: insufficient read permissions (protection: {})",
             addr,
             protection.unwrap_or("unknown".to_string())
         ));
     }
 
-    script
+    let data = script
         .exports
-        .call("writer_bytes", Some(json!([addr, value])))
+        .call("reader_pointer", Some(json!([addr])))
         .map_err(|e| e.to_string())?;
-    Ok(())
+    let binding = data.ok_or_else(|| "No data returned".to_string())?;
+    let value = binding
+        .as_str()
+        .ok_or_else(|| "Invalid pointer".to_string())?;
+    Ok(string_to_u64(value))
 }
 
 pub fn check_read_protection(script: &mut Script, addr: u64) -> Result<bool, String> {
@@ -201,6 +170,7 @@ pub fn get_address_from_data(data: &VzData) -> Option<u64> {
         VzData::ScanResult(s) => Some(s.address),
         VzData::Import(i) => i.address,
         VzData::Symbol(s) => Some(s.address),
+        VzData::HookEvent(e) => Some(e.address),
         _ => None,
     }
 }
@@ -219,6 +189,7 @@ pub fn parse_value_type(s: &str) -> Result<VzValueType, String> {
         "d" | "double" | "float64" => Ok(VzValueType::Double),
         "bl" | "bool" | "boolean" => Ok(VzValueType::Bool),
         "str" | "string" | "utf8" => Ok(VzValueType::String),
+        "ws" | "wstr" | "wstring" | "utf16" => Ok(VzValueType::Utf16),
         "bs" | "arr" | "bytes" | "array" => Ok(VzValueType::Bytes),
         "p" | "pointer" => Ok(VzValueType::Pointer),
         "" => Ok(VzValueType::Byte), // Default to Byte if empty
@@ -354,6 +325,10 @@ pub fn read_memory_by_type(
             let val = readstring(script, addr, length)?;
             Ok(format!("\"{}\"", val))
         }
+        VzValueType::Utf16 => {
+            let val = readutf16(script, addr, length)?;
+            Ok(format!("\"{}\"", val))
+        }
         VzValueType::Array | VzValueType::Bytes => {
             let len = length.unwrap_or(16);
             let val = readbytes(script, addr, len)?;
@@ -386,55 +361,82 @@ pub fn write_memory_by_type(
     value_str: &str,
     value_type: &VzValueType,
 ) -> Result<(), String> {
-    match value_type {
+    let (export, value) = typed_writer(value_str, value_type)?;
+    if !check_write_protection(script, addr)? {
+        let protection = get_memory_protection(script, addr)?;
+        return Err(format!(
+            "Cannot write to address {:#x}: insufficient write permissions (protection: {})",
+            addr,
+            protection.unwrap_or("unknown".to_string())
+        ));
+    }
+
+    script
+        .exports
+        .call(export, Some(json!([addr, value])))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The agent's `writer_*` export for `value_type` and `value_str` parsed as
+/// its argument. `freeze` hands both to the agent to rewrite on a timer.
+pub fn typed_writer(
+    value_str: &str,
+    value_type: &VzValueType,
+) -> Result<(&'static str, Value), String> {
+    let unquoted = value_str
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value_str);
+    let writer = match value_type {
         VzValueType::Byte | VzValueType::Int8 => {
             let val = value_str.parse::<i8>().map_err(|_| "Invalid byte value")?;
-            writebyte(script, addr, val)
+            ("writer_byte", json!(val))
         }
         VzValueType::UByte | VzValueType::UInt8 => {
             let val = value_str.parse::<u8>().map_err(|_| "Invalid ubyte value")?;
-            writeubyte(script, addr, val)
+            ("writer_ubyte", json!(val))
         }
         VzValueType::Short | VzValueType::Int16 => {
             let val = value_str
                 .parse::<i16>()
                 .map_err(|_| "Invalid short value")?;
-            writeshort(script, addr, val)
+            ("writer_short", json!(val))
         }
         VzValueType::UShort | VzValueType::UInt16 => {
             let val = value_str
                 .parse::<u16>()
                 .map_err(|_| "Invalid ushort value")?;
-            writeushort(script, addr, val)
+            ("writer_ushort", json!(val))
         }
         VzValueType::Int | VzValueType::Int32 => {
             let val = value_str.parse::<i32>().map_err(|_| "Invalid int value")?;
-            writeint(script, addr, val)
+            ("writer_int", json!(val))
         }
         VzValueType::UInt | VzValueType::UInt32 => {
             let val = value_str.parse::<u32>().map_err(|_| "Invalid uint value")?;
-            writeuint(script, addr, val)
+            ("writer_uint", json!(val))
         }
         VzValueType::Long | VzValueType::Int64 => {
             let val = value_str.parse::<i64>().map_err(|_| "Invalid long value")?;
-            writelong(script, addr, val)
+            ("writer_long", json!(val))
         }
         VzValueType::ULong | VzValueType::UInt64 => {
             let val = crate::util::format::parse_hex_or_decimal(value_str)
                 .map_err(|_| "Invalid ulong value")?;
-            writeulong(script, addr, val)
+            ("writer_ulong", json!(val))
         }
         VzValueType::Float | VzValueType::Float32 => {
             let val = value_str
                 .parse::<f32>()
                 .map_err(|_| "Invalid float value")?;
-            writefloat(script, addr, val)
+            ("writer_float", json!(val))
         }
         VzValueType::Double | VzValueType::Float64 => {
             let val = value_str
                 .parse::<f64>()
                 .map_err(|_| "Invalid double value")?;
-            writedouble(script, addr, val)
+            ("writer_double", json!(val))
         }
         VzValueType::Bool | VzValueType::Boolean => {
             let val = match value_str.to_lowercase().as_str() {
@@ -442,45 +444,49 @@ pub fn write_memory_by_type(
                 "false" | "0" => 0i8,
                 _ => return Err("Invalid boolean value, use true/false or 1/0".to_string()),
             };
-            writebyte(script, addr, val)
-        }
-        VzValueType::String | VzValueType::Utf8 => {
-            let clean_value = if value_str.starts_with('"') && value_str.ends_with('"') {
-                &value_str[1..value_str.len() - 1]
-            } else {
-                value_str
-            };
-            writestring(script, addr, clean_value)
+            ("writer_byte", json!(val))
         }
+        VzValueType::String | VzValueType::Utf8 => ("writer_string", json!(unquoted)),
+        VzValueType::Utf16 => ("writer_utf16", json!(unquoted)),
         VzValueType::Array | VzValueType::Bytes => {
-            let bytes = if value_str.starts_with('[') && value_str.ends_with(']') {
-                let inner = &value_str[1..value_str.len() - 1];
-                inner
-                    .split_whitespace()
-                    .map(|s| u8::from_str_radix(s, 16).map_err(|_| "Invalid hex byte"))
-                    .collect::<Result<Vec<u8>, _>>()?
-            } else {
-                value_str
-                    .split_whitespace()
-                    .map(|s| u8::from_str_radix(s, 16).map_err(|_| "Invalid hex byte"))
-                    .collect::<Result<Vec<u8>, _>>()?
-            };
-            writebytes(script, addr, &bytes)
+            let inner = value_str
+                .strip_prefix('[')
+                .and_then(|v| v.strip_suffix(']'))
+                .unwrap_or(value_str);
+            let bytes = inner
+                .split_whitespace()
+                .map(|s| u8::from_str_radix(s, 16).map_err(|_| "Invalid hex byte"))
+                .collect::<Result<Vec<u8>, _>>()?;
+            ("writer_bytes", json!(bytes))
         }
         VzValueType::Pointer => {
             let val = crate::util::format::parse_hex_or_decimal(value_str)
                 .map_err(|_| "Invalid pointer value")?;
-            writeulong(script, addr, val)
+            ("writer_ulong", json!(val))
         }
-        VzValueType::Void => Err("Cannot write void type".to_string()),
+        VzValueType::Void => return Err("Cannot write void type".to_string()),
+    };
+    Ok(writer)
+}
+
+/// Bytes a `typed_writer` value covers, NUL terminator included
+pub fn written_size(value: &Value, value_type: &VzValueType) -> usize {
+    match (value, value_type) {
+        (Value::String(text), VzValueType::Utf16) => (text.encode_utf16().count() + 1) * 2,
+        (Value::String(text), _) => text.len() + 1,
+        (Value::Array(bytes), _) => bytes.len().max(1),
+        _ => get_type_size(value_type),
     }
 }
 
+/// Hex/typed dump of `length` bytes at `addr`. Rows containing an address in
+/// `labels` end with that label and its offset into the row.
 pub fn view_memory(
     script: &mut Script,
     addr: u64,
     value_type: &VzValueType,
     length: usize,
+    labels: &BTreeMap<u64, String>,
 ) -> Result<String, String> {
     let bytes = readbytes(script, addr, length)?;
     if bytes.is_empty() {
@@ -507,6 +513,7 @@ pub fn view_memory(
             | VzValueType::UInt8
             | VzValueType::String
             | VzValueType::Utf8
+            | VzValueType::Utf16
             | VzValueType::Array
             | VzValueType::Bytes
     );
@@ -617,6 +624,14 @@ pub fn view_memory(
             output.push(' ');
         }
 
+        for (label_addr, label) in labels.range(current_addr..current_addr + chunk.len() as u64) {
+            output.push_str(&format!(
+                " {}{}",
+                format!("+{:x}", label_addr - current_addr).dark_grey(),
+                format!("<{}>", label).magenta()
+            ));
+        }
+
         output.push('\n');
     }
 
@@ -638,6 +653,7 @@ fn get_type_size(value_type: &VzValueType) -> usize {
         VzValueType::Bool | VzValueType::Boolean => 1,
         VzValueType::Pointer => 8,
         VzValueType::String | VzValueType::Utf8 => 1,
+        VzValueType::Utf16 => 2,
         VzValueType::Array | VzValueType::Bytes => 1,
         VzValueType::Void => 1,
     }
@@ -868,9 +884,53 @@ fn decode_value_to_string_from_bytes(value_type: &VzValueType, slice: &[u8], lit
             format!("{:#018x}", v)
         }
         // For these types, view uses hex-bytes mode; fallback to single byte display string
-        VzValueType::String | VzValueType::Utf8 | VzValueType::Array | VzValueType::Bytes => {
+        VzValueType::String
+        | VzValueType::Utf8
+        | VzValueType::Utf16
+        | VzValueType::Array
+        | VzValueType::Bytes => {
             format!("{:02x}", slice[0])
         }
         VzValueType::Void => "".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_writer() {
+        let writer = |value, value_type| typed_writer(value, &value_type);
+        assert_eq!(
+            writer("-5", VzValueType::Int),
+            Ok(("writer_int", json!(-5)))
+        );
+        assert_eq!(
+            writer("0x10", VzValueType::Pointer),
+            Ok(("writer_ulong", json!(16)))
+        );
+        assert_eq!(
+            writer("true", VzValueType::Bool),
+            Ok(("writer_byte", json!(1)))
+        );
+        assert_eq!(
+            writer("\"hi\"", VzValueType::String),
+            Ok(("writer_string", json!("hi")))
+        );
+        assert_eq!(
+            writer("[de ad]", VzValueType::Bytes),
+            Ok(("writer_bytes", json!([0xde, 0xad])))
+        );
+        assert!(writer("300", VzValueType::Byte).is_err());
+        assert!(writer("1", VzValueType::Void).is_err());
+    }
+
+    #[test]
+    fn test_written_size() {
+        assert_eq!(written_size(&json!(1.5), &VzValueType::Double), 8);
+        assert_eq!(written_size(&json!("hi"), &VzValueType::String), 3);
+        assert_eq!(written_size(&json!("hi"), &VzValueType::Utf16), 6);
+        assert_eq!(written_size(&json!([1, 2, 3]), &VzValueType::Bytes), 3);
+    }
+}
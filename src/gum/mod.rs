@@ -2,26 +2,38 @@
 mod handler;
 mod session;
 
+pub mod bookmark;
 pub mod commander;
 pub mod commands;
+pub mod completer;
+pub mod coverage;
+pub mod event;
+pub mod eventlog;
+pub mod expr;
 pub mod filter;
+pub mod hookstats;
 pub mod list;
 pub mod memory;
 pub mod navigator;
+pub mod signature;
+pub mod stalk;
 pub mod store;
 pub mod store_tests;
+pub mod trace;
+pub mod vars;
 pub mod vzdata;
+pub mod watch;
 
 pub use store::Store;
 pub use vzdata::VzData;
 
 use std::process::exit;
 
-use crate::core::cli::TargetArgs;
+use crate::core::cli::AttachArgs;
 use crossterm::style::Stylize;
 use frida::{Device, ScriptOption};
 use handler::Handler;
-use session::session_manager;
+use session::{batch_manager, load_batch, session_manager};
 
 fn attach_pid<'a>(
     device: &'a Device,
@@ -31,7 +43,13 @@ fn attach_pid<'a>(
     Ok((session, pid))
 }
 
-pub fn attach(device: &mut Device, args: &TargetArgs) {
+pub fn attach(device: &mut Device, options: &AttachArgs) -> i32 {
+    let args = &options.target;
+    let commands = load_batch(&options.batch).unwrap_or_else(|e| {
+        crate::util::logger::error(&e);
+        exit(1);
+    });
+
     let (session, pid) = if let Some(_pid) = args.attach_pid {
         let pid: u32 = device
             .enumerate_processes()
@@ -152,7 +170,7 @@ pub fn attach(device: &mut Device, args: &TargetArgs) {
 
     if session.is_detached() {
         println!("{}", "Session detached...".yellow().bold());
-        return;
+        return 1;
     }
 
     let script_content = include_str!("../agent.js").to_string();
@@ -163,7 +181,7 @@ pub fn attach(device: &mut Device, args: &TargetArgs) {
             exit(1);
         });
 
-    let handler = script.handle_message(Handler);
+    let handler = script.handle_message(Handler::default());
     if let Err(e) = handler {
         println!("{} {}", "Failed to set message handler:".red(), e);
         exit(1);
@@ -181,7 +199,13 @@ pub fn attach(device: &mut Device, args: &TargetArgs) {
         });
     }
 
-    session_manager(&session, &mut script, pid);
+    let status = match commands {
+        Some(ref commands) => batch_manager(&session, &mut script, commands, options.output),
+        None => {
+            session_manager(&session, &mut script, pid, options.output);
+            0
+        }
+    };
 
     if !session.is_detached() {
         if let Err(e) = script.unload() {
@@ -193,4 +217,5 @@ pub fn attach(device: &mut Device, args: &TargetArgs) {
             println!("{}", "Session detached.".yellow().bold());
         }
     }
+    status
 }
@@ -1,5 +1,5 @@
 // src/gum/navigator.rs
-use super::vzdata::{VzBase, VzData, VzDataType, VzPointer, VzValueType};
+use super::vzdata::{new_base, VzData, VzDataType, VzPointer, VzValueType};
 use crossterm::style::Stylize;
 use std::fmt;
 
@@ -101,6 +101,13 @@ impl fmt::Display for Navigator {
                     format!("{}:", sym.base.data_type.to_string()).blue(),
                     sym.name,
                 ),
+                VzData::HookEvent(e) => write!(
+                    f,
+                    "{}{}{}",
+                    format!("{}:", e.base.data_type.to_string()).blue(),
+                    e.id.clone().yellow(),
+                    format!("@{:#x}", e.address).yellow(),
+                ),
             },
             None => write!(f, "{}", "vlitz".blue()),
         }
@@ -120,6 +127,22 @@ impl Navigator {
     pub fn get_data(&self) -> Option<&VzData> {
         self.data.as_ref()
     }
+    /// Prompt text, with the label of the current address appended
+    pub fn prompt(&self, label: Option<&str>) -> String {
+        match label {
+            Some(label) => format!("{}{}>", self, format!("<{}>", label).magenta()),
+            None => format!("{}>", self),
+        }
+    }
+    // Annotations describe the selected address, not wherever we move to
+    fn clear_annotation(&mut self) {
+        if let Some(data) = self.data.as_mut() {
+            let base = data.base_mut();
+            base.label = None;
+            base.comment = None;
+            base.tags.clear();
+        }
+    }
     pub fn add(&mut self, offset: u64) {
         if let Some(data) = self.data.as_mut() {
             match data {
@@ -143,6 +166,7 @@ impl Navigator {
                 _ => {}
             }
         }
+        self.clear_annotation();
     }
     pub fn sub(&mut self, offset: u64) {
         if let Some(data) = self.data.as_mut() {
@@ -167,6 +191,7 @@ impl Navigator {
                 _ => {}
             }
         }
+        self.clear_annotation();
     }
     pub fn goto(&mut self, address: u64) {
         if let Some(data) = self.data.as_mut() {
@@ -190,12 +215,10 @@ impl Navigator {
                 }
                 _ => {}
             }
+            self.clear_annotation();
         } else {
             self.data = Some(VzData::Pointer(VzPointer {
-                base: VzBase {
-                    data_type: VzDataType::Pointer,
-                    is_saved: false,
-                },
+                base: new_base(VzDataType::Pointer),
                 address,
                 size: 8,
                 value_type: VzValueType::Pointer,
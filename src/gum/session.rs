@@ -1,37 +1,148 @@
 // src/gum/session.rs
 
-use super::commander::Commander;
+use super::commander::{BatchOutcome, Commander};
+use super::completer::VlitzHelper;
+use crate::core::cli::{BatchArgs, OutputFormat};
 use crossterm::{cursor, style::Stylize, terminal, ExecutableCommand};
 use frida::{Script, Session};
 use regex::Regex;
+use rustyline::{error::ReadlineError, history::DefaultHistory, CompletionType, Config, Editor};
 use std::{
-    io::{stdin, stdout, Write},
+    fs,
+    io::stdout,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
 };
 
-fn parse_command(input: &str) -> Vec<String> {
-    let re = Regex::new(r#"("[^"]*")|('[^']*')|(\S+)"#).expect("Failed to compile command regex");
+const HISTORY_FILE: &str = ".vlitz_history";
+const HISTORY_SIZE: usize = 1000;
+
+/// Per-user history file in the home directory
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// A word of a command line. Quoted words are taken literally: they are not
+/// expanded as `$variables` or resolved as addresses where text is accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Word {
+    pub text: String,
+    pub quoted: bool,
+}
+
+/// Split a command line into words. A quoted run joined to other text, as in
+/// `"libc-2.31.so"+0x10`, stays part of that word with its quotes, for the
+/// address expression parser to see.
+pub(crate) fn parse_command(input: &str) -> Vec<Word> {
+    let re = Regex::new(r#"(?:"[^"]*"|'[^']*'|[^\s"']+)+|\S+"#)
+        .expect("Failed to compile command regex");
 
     re.find_iter(input)
         .map(|m| {
             let s = m.as_str();
-            // Strip surrounding quotes if present
-            if (s.starts_with('"') && s.ends_with('"'))
-                || (s.starts_with('\'') && s.ends_with('\''))
-            {
-                s[1..s.len() - 1].to_string()
-            } else {
-                s.to_string()
+            // Strip surrounding quotes if the whole word is one quoted run
+            let quote = s.chars().next().filter(|c| *c == '"' || *c == '\'');
+            match quote {
+                Some(q) if s.len() > 1 && s.ends_with(q) && !s[1..s.len() - 1].contains(q) => {
+                    Word {
+                        text: s[1..s.len() - 1].to_string(),
+                        quoted: true,
+                    }
+                }
+                _ => Word {
+                    text: s.to_string(),
+                    quoted: false,
+                },
             }
         })
         .collect()
 }
 
-pub fn session_manager(session: &Session, script: &mut Script<'_>, pid: u32) {
+/// Split script text into individual commands. Each line may hold several
+/// commands separated by `;`; blank lines and `#` comments are skipped.
+pub(crate) fn split_commands(text: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+        for ch in line.chars() {
+            match (ch, quote) {
+                ('"' | '\'', None) => quote = Some(ch),
+                (c, Some(q)) if c == q => quote = None,
+                (';', None) => {
+                    commands.push(std::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(ch);
+        }
+        commands.push(current);
+    }
+    commands
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/// Collect the commands requested by `--script` and `--eval`, script first.
+/// Returns `None` when neither flag was given.
+pub fn load_batch(args: &BatchArgs) -> Result<Option<Vec<String>>, String> {
+    if !args.is_batch() {
+        return Ok(None);
+    }
+    let mut commands = Vec::new();
+    if let Some(ref path) = args.script {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read script {}: {}", path, e))?;
+        commands.extend(split_commands(&text));
+    }
+    if let Some(ref eval) = args.eval {
+        commands.extend(split_commands(eval));
+    }
+    Ok(Some(commands))
+}
+
+/// Run commands without a prompt and return the process exit status.
+pub fn batch_manager(
+    session: &Session,
+    script: &mut Script<'_>,
+    commands: &[String],
+    output: OutputFormat,
+) -> i32 {
+    let mut commander = Commander::new(script);
+    commander.set_output(output);
+    let status = match commander.run_commands(commands) {
+        BatchOutcome::Completed | BatchOutcome::Exited => 0,
+        BatchOutcome::Failed(index) => {
+            crate::util::logger::error(&format!(
+                "Batch aborted at command {}: {}",
+                index + 1,
+                commands[index]
+            ));
+            1
+        }
+    };
+    if session.is_detached() {
+        println!("{}", "Session detached. Exiting...".red());
+        return 1;
+    }
+    status
+}
+
+pub fn session_manager(session: &Session, script: &mut Script<'_>, pid: u32, output: OutputFormat) {
     let mut commander = Commander::new(script);
+    commander.set_output(output);
     let version = env!("CARGO_PKG_VERSION");
     let title = format!("vlitz v{}", version);
     if let Err(e) = stdout().execute(terminal::SetTitle(title)) {
@@ -65,26 +176,44 @@ pub fn session_manager(session: &Session, script: &mut Script<'_>, pid: u32) {
         crate::util::logger::error(&format!("Error setting Ctrl-C handler: {}", e));
         std::process::exit(1);
     });
+
+    let config = Config::builder()
+        .history_ignore_space(true)
+        .completion_type(CompletionType::List)
+        .max_history_size(HISTORY_SIZE)
+        .and_then(|b| b.history_ignore_dups(true))
+        .map(|b| b.build())
+        .unwrap_or_default();
+    let mut editor: Editor<VlitzHelper, DefaultHistory> = match Editor::with_config(config) {
+        Ok(editor) => editor,
+        Err(e) => {
+            crate::util::logger::error(&format!("Failed to initialize line editor: {}", e));
+            return;
+        }
+    };
+    editor.set_helper(Some(VlitzHelper::new(commander.completion_data())));
+    let history = history_path();
+    if let Some(ref path) = history {
+        // A missing history file is expected on first run
+        let _ = editor.load_history(path);
+    }
+
     loop {
         if !running.load(Ordering::SeqCst) {
             println!("\n{}", "Ctrl + C detected. Exiting...".yellow());
             break;
         }
-        let write_str = format!("{}>", commander.navigator);
-        if let Err(e) = stdout().write(write_str.as_bytes()) {
-            crate::util::logger::error(&format!("Write error: {}", e));
-        }
-        if let Err(e) = stdout().flush() {
-            crate::util::logger::error(&format!("Flush error: {}", e));
-        }
-        let mut input = String::new();
-        let bytes_read = stdin().read_line(&mut input);
-        match bytes_read {
-            Ok(0) => {
+        let prompt = commander.prompt();
+        let input = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                println!("{}", "Ctrl + C detected. Exiting...".yellow());
+                break;
+            }
+            Err(ReadlineError::Eof) => {
                 println!("\n{}", "Ctrl + D detected. Exiting...".yellow());
                 break;
             }
-            Ok(_) => (), // Successfully read some bytes
             Err(e) => {
                 println!("Error reading input: {}", e);
                 break;
@@ -98,20 +227,23 @@ pub fn session_manager(session: &Session, script: &mut Script<'_>, pid: u32) {
         if input.is_empty() {
             continue;
         }
+        if let Err(e) = editor.add_history_entry(input) {
+            crate::util::logger::error(&format!("Failed to add history entry: {}", e));
+        }
         let mut args = parse_command(input);
         let command = args.remove(0);
-        match command.as_str() {
-            _ => {
-                if !commander.execute_command(
-                    command.as_str(),
-                    args.iter()
-                        .map(|s| s.as_str())
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                ) {
-                    break;
-                }
-            }
+        let keep_running = commander.execute_command(&command.text, &args);
+        if !keep_running {
+            break;
+        }
+        if let Some(helper) = editor.helper_mut() {
+            helper.data = commander.completion_data();
+        }
+    }
+
+    if let Some(ref path) = history {
+        if let Err(e) = editor.save_history(path) {
+            crate::util::logger::error(&format!("Failed to save history: {}", e));
         }
     }
 }
@@ -120,6 +252,10 @@ pub fn session_manager(session: &Session, script: &mut Script<'_>, pid: u32) {
 mod tests {
     use super::*;
 
+    fn texts(input: &str) -> Vec<String> {
+        parse_command(input).into_iter().map(|w| w.text).collect()
+    }
+
     #[test]
     fn test_parse_empty_command() {
         let result = parse_command("");
@@ -128,37 +264,76 @@ mod tests {
 
     #[test]
     fn test_parse_simple_command() {
-        let result = parse_command("help");
+        let result = texts("help");
         assert_eq!(result, vec!["help"]);
     }
 
     #[test]
     fn test_parse_command_with_args() {
-        let result = parse_command("read 0x1000 byte 16");
+        let result = texts("read 0x1000 byte 16");
         assert_eq!(result, vec!["read", "0x1000", "byte", "16"]);
     }
 
     #[test]
     fn test_parse_double_quoted_string() {
-        let result = parse_command(r#"echo "hello world""#);
+        let result = texts(r#"echo "hello world""#);
         assert_eq!(result, vec!["echo", "hello world"]);
     }
 
     #[test]
     fn test_parse_single_quoted_string() {
-        let result = parse_command(r#"echo 'test'"#);
+        let result = texts(r#"echo 'test'"#);
         assert_eq!(result, vec!["echo", "test"]);
     }
 
     #[test]
     fn test_parse_mixed_quotes() {
-        let result = parse_command(r#"command "arg1" 'arg2' "arg3""#);
+        let result = texts(r#"command "arg1" 'arg2' "arg3""#);
         assert_eq!(result, vec!["command", "arg1", "arg2", "arg3"]);
     }
 
+    #[test]
+    fn test_parse_keeps_joined_quotes() {
+        let words = parse_command(r#"read "libc-2.31.so"+0x10 "a b" 'c'!open"#);
+        let result: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(
+            result,
+            vec!["read", r#""libc-2.31.so"+0x10"#, "a b", "'c'!open"]
+        );
+        assert!(!words[1].quoted);
+        assert!(words[2].quoted);
+    }
+
+    #[test]
+    fn test_parse_marks_quoted_words() {
+        let quoted: Vec<bool> = parse_command(r#"call puts "main" $buf"#)
+            .iter()
+            .map(|w| w.quoted)
+            .collect();
+        assert_eq!(quoted, vec![false, false, true, false]);
+    }
+
     #[test]
     fn test_parse_with_special_chars() {
-        let result = parse_command("test-arg_special@value");
+        let result = texts("test-arg_special@value");
         assert_eq!(result, vec!["test-arg_special@value"]);
     }
+
+    #[test]
+    fn test_split_commands_semicolons() {
+        let result = split_commands("list modules; select lib:0 ;hook list");
+        assert_eq!(result, vec!["list modules", "select lib:0", "hook list"]);
+    }
+
+    #[test]
+    fn test_split_commands_skips_comments_and_blanks() {
+        let result = split_commands("# setup\n\nlist modules\n   # indented comment\nexit\n");
+        assert_eq!(result, vec!["list modules", "exit"]);
+    }
+
+    #[test]
+    fn test_split_commands_quoted_semicolon() {
+        let result = split_commands(r#"scan "a;b" utf8; field list"#);
+        assert_eq!(result, vec![r#"scan "a;b" utf8"#, "field list"]);
+    }
 }
@@ -0,0 +1,588 @@
+// src/gum/signature.rs
+//
+// C function signatures for `hook add --sig`, e.g.
+// `int open(const char* path, int flags)`. Each parameter becomes an ArgSpec
+// the agent uses to decode the argument, named after VzValueType.
+
+use super::memory::parse_value_type;
+use super::vzdata::VzValueType;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Signatures bundled with vlitz, looked up by `hook add`
+const BUILTIN_SIGNATURES: &str = include_str!("libc.sigs");
+
+/// Per-user signature file in the home directory, in the same format
+const USER_SIGNATURES_FILE: &str = ".vlitz_signatures";
+
+/// Decoding of one argument or return value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArgSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub value_type: VzValueType,
+    /// The slot holds a pointer to a `value_type`, e.g. `int*`.
+    /// Strings and `Bytes` are always read through their pointer.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub indirect: bool,
+    /// Byte count dumped for `Bytes`, from a `[N]` suffix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    /// `[ret]` suffix: on return the buffer holds as many bytes or characters
+    /// as the function returned, e.g. readlink's unterminated `buf`
+    #[serde(rename = "sizeFromRet", skip_serializing_if = "std::ops::Not::not")]
+    pub size_from_ret: bool,
+    /// Out-parameter: decoded again when the function returns
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub out: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Signature {
+    pub name: String,
+    /// `None` for `void` functions
+    pub ret: Option<ArgSpec>,
+    pub args: Vec<ArgSpec>,
+}
+
+impl ArgSpec {
+    /// NativeFunction/NativeCallback type of the slot. Floating-point values
+    /// have their own registers; everything else is passed as a pointer.
+    pub fn native_type(&self) -> &'static str {
+        match self.value_type {
+            VzValueType::Float | VzValueType::Float32 if !self.indirect => "float",
+            VzValueType::Double | VzValueType::Float64 if !self.indirect => "double",
+            _ => "pointer",
+        }
+    }
+}
+
+impl Signature {
+    pub fn has_out_args(&self) -> bool {
+        self.args.iter().any(|a| a.out)
+    }
+}
+
+impl fmt::Display for ArgSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.out {
+            write!(f, "out ")?;
+        }
+        write!(f, "{}", self.value_type)?;
+        if self.indirect {
+            write!(f, "*")?;
+        }
+        write!(f, " {}", self.name)?;
+        if let Some(size) = self.size {
+            write!(f, "[{}]", size)?;
+        }
+        if self.size_from_ret {
+            write!(f, "[ret]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Prototype spelled with VzValueType names, e.g. `Int open(String path, Int flags)`
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.ret {
+            Some(ret) if ret.indirect => write!(f, "{}* ", ret.value_type)?,
+            Some(ret) => write!(f, "{} ", ret.value_type)?,
+            None => write!(f, "Void ")?,
+        }
+        let args: Vec<String> = self.args.iter().map(|a| a.to_string()).collect();
+        write!(f, "{}({})", self.name, args.join(", "))
+    }
+}
+
+/// Words that qualify a type without naming it
+const QUALIFIERS: &[&str] = &["const", "volatile", "restrict", "struct", "union", "enum"];
+
+/// Words that can only be part of a type, never a parameter name
+const TYPE_WORDS: &[&str] = &[
+    "const", "volatile", "restrict", "struct", "union", "enum", "signed", "unsigned", "char",
+    "short", "int", "long", "float", "double", "void", "bool", "_Bool",
+];
+
+/// Scalar type of a base C type name (qualifiers removed)
+fn scalar_type(base: &str) -> Option<VzValueType> {
+    Some(match base {
+        "char" | "signed char" | "int8_t" | "CHAR" => VzValueType::Byte,
+        "unsigned char" | "uint8_t" | "BYTE" | "UCHAR" => VzValueType::UByte,
+        "short" | "short int" | "signed short" | "int16_t" | "SHORT" => VzValueType::Short,
+        "unsigned short" | "unsigned short int" | "uint16_t" | "WORD" | "USHORT" | "wchar_t"
+        | "char16_t" | "WCHAR" => VzValueType::UShort,
+        "int" | "signed" | "signed int" | "int32_t" | "INT" | "LONG" | "BOOL" | "pid_t" => {
+            VzValueType::Int
+        }
+        "unsigned" | "unsigned int" | "uint32_t" | "UINT" | "DWORD" | "ULONG" | "uid_t"
+        | "gid_t" | "mode_t" | "socklen_t" => VzValueType::UInt,
+        "long" | "long int" | "long long" | "long long int" | "signed long" | "int64_t"
+        | "ssize_t" | "off_t" | "intptr_t" | "ptrdiff_t" | "LONGLONG" => VzValueType::Long,
+        "unsigned long" | "unsigned long int" | "unsigned long long" | "uint64_t" | "size_t"
+        | "uintptr_t" | "QWORD" | "ULONGLONG" | "SIZE_T" => VzValueType::ULong,
+        "float" | "FLOAT" => VzValueType::Float,
+        "double" => VzValueType::Double,
+        "bool" | "_Bool" | "BOOLEAN" => VzValueType::Bool,
+        _ => return None,
+    })
+}
+
+/// Windows pointer typedefs: (name, pointee type, levels of indirection)
+fn pointer_typedef(base: &str) -> Option<(&'static str, usize)> {
+    Some(match base {
+        "LPSTR" | "LPCSTR" | "PSTR" | "PCSTR" => ("char", 1),
+        "LPWSTR" | "LPCWSTR" | "PWSTR" | "PCWSTR" => ("wchar_t", 1),
+        "LPVOID" | "LPCVOID" | "PVOID" | "HANDLE" | "HMODULE" => ("void", 1),
+        _ => return None,
+    })
+}
+
+/// Build the spec for a type written as `base` with `levels` of `*`
+fn type_spec(base: &str, levels: usize, size: Option<usize>) -> Result<ArgSpec, String> {
+    let (base, levels) = match pointer_typedef(base) {
+        Some((pointee, extra)) => (pointee, levels + extra),
+        None => (base, levels),
+    };
+    let spec = |value_type, indirect| ArgSpec {
+        name: String::new(),
+        value_type,
+        indirect,
+        size: None,
+        size_from_ret: false,
+        out: false,
+    };
+    if let Some(size) = size {
+        if levels == 0 && parse_value_type(base) != Ok(VzValueType::Bytes) {
+            return Err(format!("Only pointers can have a size: {}[{}]", base, size));
+        }
+        return Ok(ArgSpec {
+            size: Some(size),
+            ..spec(VzValueType::Bytes, false)
+        });
+    }
+    Ok(match (levels, scalar_type(base)) {
+        (1, _) if matches!(base, "char" | "CHAR") => spec(VzValueType::String, false),
+        (1, _) if matches!(base, "wchar_t" | "char16_t" | "WCHAR") => {
+            spec(VzValueType::Utf16, false)
+        }
+        (1, Some(t)) => spec(t, true),
+        (0, Some(t)) => spec(t, false),
+        (0, None) if base == "void" => spec(VzValueType::Void, false),
+        // Value type names work too, e.g. `string path` or `utf16 name`
+        (0, None) => match parse_value_type(base) {
+            Ok(t) => spec(t, false),
+            // Unknown typedefs and structs passed in a register: show raw
+            Err(_) => spec(VzValueType::Pointer, false),
+        },
+        (1, None) => spec(VzValueType::Pointer, false),
+        // `void** res`: the slot points at a pointer
+        _ => spec(VzValueType::Pointer, true),
+    })
+}
+
+/// Parse one declaration (`const char* path`, `int`, `out int* n`, `void* buf[64]`,
+/// `out char* buf[ret]`) into a spec named after its declarator, or
+/// `default_name` when unnamed
+fn parse_decl(decl: &str, default_name: &str) -> Result<ArgSpec, String> {
+    let mut decl = decl.trim();
+    let mut size = None;
+    let mut size_from_ret = false;
+    if let Some(open) = decl.rfind('[') {
+        let inner = decl[open + 1..]
+            .strip_suffix(']')
+            .ok_or_else(|| format!("Unterminated '[' in: {}", decl))?
+            .trim();
+        if inner == "ret" {
+            size_from_ret = true;
+        } else {
+            size = Some(
+                crate::util::format::parse_hex_or_decimal_usize(inner)
+                    .map_err(|_| format!("Invalid size in: {}", decl))?,
+            );
+        }
+        decl = decl[..open].trim_end();
+    }
+
+    let spaced = decl.replace(['*', '&'], " * ");
+    let mut words: Vec<&str> = spaced.split_whitespace().collect();
+    let mut out = false;
+    words.retain(|w| match *w {
+        "out" | "inout" | "_Out_" | "_Out_opt_" | "_Inout_" | "_Inout_opt_" => {
+            out = true;
+            false
+        }
+        "in" | "_In_" | "_In_opt_" => false,
+        _ => true,
+    });
+
+    // The last word names the parameter only if a type word is left before
+    // it, so `const size_t` is an unnamed `size_t`
+    let is_type_word = |w: &str| w != "*" && !QUALIFIERS.contains(&w);
+    let name = match words.last() {
+        Some(last)
+            if words.iter().filter(|w| is_type_word(w)).count() > 1
+                && is_type_word(last)
+                && !TYPE_WORDS.contains(last) =>
+        {
+            words.pop().map(String::from)
+        }
+        _ => None,
+    };
+    let levels = words.iter().filter(|w| **w == "*").count();
+    let base = words
+        .iter()
+        .filter(|w| is_type_word(w))
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if base.is_empty() {
+        return Err(format!("Missing type in: {}", decl));
+    }
+
+    let mut spec = type_spec(&base, levels, size)?;
+    if size_from_ret {
+        spec = match spec.value_type {
+            VzValueType::String | VzValueType::Utf16 | VzValueType::Bytes => spec,
+            // `void* buf[ret]` or `int* v[ret]`: dump the returned byte count
+            ref t if *t == VzValueType::Pointer || spec.indirect => ArgSpec {
+                value_type: VzValueType::Bytes,
+                indirect: false,
+                ..spec
+            },
+            _ => return Err(format!("Only buffers can be sized by ret: {}", decl)),
+        };
+        spec.size_from_ret = true;
+    }
+    spec.name = name.unwrap_or_else(|| default_name.to_string());
+    spec.out = out;
+    Ok(spec)
+}
+
+/// Parse a C prototype such as `int open(const char* path, int flags)`.
+/// A trailing `...` is accepted; variadic arguments are not decoded.
+pub fn parse_signature(input: &str) -> Result<Signature, String> {
+    let input = input.trim().trim_end_matches(';');
+    let open = input
+        .find('(')
+        .ok_or_else(|| format!("Expected '(' in signature: {}", input))?;
+    let close = input
+        .rfind(')')
+        .filter(|&c| c > open)
+        .ok_or_else(|| format!("Expected ')' in signature: {}", input))?;
+
+    let head = input[..open].trim();
+    let name_start = head
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let name = &head[name_start..];
+    if name.is_empty() || head[..name_start].trim().is_empty() {
+        return Err(format!("Expected return type and name in: {}", head));
+    }
+    let ret = parse_decl(&head[..name_start], "ret")?;
+    let ret = match ret.value_type {
+        VzValueType::Void if !ret.indirect => None,
+        _ => Some(ret),
+    };
+
+    let params = input[open + 1..close].trim();
+    let mut args = Vec::new();
+    if !params.is_empty() && params != "void" {
+        for (i, param) in params.split(',').enumerate() {
+            if param.trim() == "..." {
+                break;
+            }
+            let spec = parse_decl(param, &format!("arg{}", i))?;
+            if spec.value_type == VzValueType::Void {
+                return Err(format!("Parameter cannot be void: {}", param.trim()));
+            }
+            args.push(spec);
+        }
+    }
+
+    Ok(Signature {
+        name: name.to_string(),
+        ret,
+        args,
+    })
+}
+
+/// Signatures known by function name, from the bundled table and user files
+pub struct SignatureDb {
+    signatures: HashMap<String, Signature>,
+}
+
+impl SignatureDb {
+    pub fn new() -> Self {
+        let mut db = SignatureDb {
+            signatures: HashMap::new(),
+        };
+        db.load_str(BUILTIN_SIGNATURES)
+            .expect("Bundled signatures must parse");
+        db
+    }
+
+    /// Add declarations from signature file text: C prototypes terminated by
+    /// `;`, with `#` and `//` comment lines. Later entries replace earlier
+    /// ones of the same name. Returns the number of signatures read.
+    pub fn load_str(&mut self, text: &str) -> Result<usize, String> {
+        let mut decl = String::new();
+        let mut start_line = 0;
+        let mut count = 0;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            if decl.is_empty() {
+                start_line = i + 1;
+            }
+            decl.push(' ');
+            decl.push_str(line);
+            while let Some(end) = decl.find(';') {
+                let sig = parse_signature(&decl[..end])
+                    .map_err(|e| format!("line {}: {}", start_line, e))?;
+                self.signatures.insert(sig.name.clone(), sig);
+                count += 1;
+                decl = decl[end + 1..].to_string();
+                start_line = i + 1;
+            }
+            if decl.trim().is_empty() {
+                decl.clear();
+            }
+        }
+        if !decl.trim().is_empty() {
+            return Err(format!("line {}: missing ';'", start_line));
+        }
+        Ok(count)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<usize, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        self.load_str(&text)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// `~/.vlitz_signatures`, if the home directory is known
+    pub fn user_file() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(USER_SIGNATURES_FILE))
+    }
+
+    /// Signature for a symbol name. Mach-O symbols carry a leading `_`.
+    pub fn get(&self, name: &str) -> Option<&Signature> {
+        self.signatures
+            .get(name)
+            .or_else(|| self.signatures.get(name.strip_prefix('_')?))
+    }
+
+    /// All signatures, sorted by name
+    pub fn iter(&self) -> Vec<&Signature> {
+        let mut sigs: Vec<_> = self.signatures.values().collect();
+        sigs.sort_by(|a, b| a.name.cmp(&b.name));
+        sigs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg(name: &str, value_type: VzValueType) -> ArgSpec {
+        ArgSpec {
+            name: name.to_string(),
+            value_type,
+            indirect: false,
+            size: None,
+            size_from_ret: false,
+            out: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_open() {
+        let sig = parse_signature("int open(const char* path, int flags)").unwrap();
+        assert_eq!(sig.name, "open");
+        assert_eq!(sig.ret, Some(arg("ret", VzValueType::Int)));
+        assert_eq!(
+            sig.args,
+            vec![
+                arg("path", VzValueType::String),
+                arg("flags", VzValueType::Int)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_widths_and_unnamed() {
+        let sig = parse_signature(
+            "void f(unsigned char, short s, uint32_t u, long long l, size_t n, double d, float)",
+        )
+        .unwrap();
+        assert_eq!(sig.ret, None);
+        let types: Vec<_> = sig.args.iter().map(|a| a.value_type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                VzValueType::UByte,
+                VzValueType::Short,
+                VzValueType::UInt,
+                VzValueType::Long,
+                VzValueType::ULong,
+                VzValueType::Double,
+                VzValueType::Float
+            ]
+        );
+        assert_eq!(sig.args[0].name, "arg0");
+        assert_eq!(sig.args[6].name, "arg6");
+    }
+
+    #[test]
+    fn test_parse_pointers_and_buffers() {
+        let sig = parse_signature(
+            "ssize_t read(int fd, void* buf[0x40], size_t count, const wchar_t *name, struct stat *st, char **argv)",
+        )
+        .unwrap();
+        assert_eq!(sig.ret, Some(arg("ret", VzValueType::Long)));
+        assert_eq!(sig.args[1].value_type, VzValueType::Bytes);
+        assert_eq!(sig.args[1].size, Some(0x40));
+        assert_eq!(sig.args[3], arg("name", VzValueType::Utf16));
+        assert_eq!(sig.args[4], arg("st", VzValueType::Pointer));
+        assert_eq!(
+            sig.args[5],
+            ArgSpec {
+                indirect: true,
+                ..arg("argv", VzValueType::Pointer)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_unnamed_qualified() {
+        let sig = parse_signature("int f(const size_t, struct stat, const char*)").unwrap();
+        assert_eq!(sig.args[0], arg("arg0", VzValueType::ULong));
+        assert_eq!(sig.args[1], arg("arg1", VzValueType::Pointer));
+        assert_eq!(sig.args[2], arg("arg2", VzValueType::String));
+    }
+
+    #[test]
+    fn test_parse_out_params() {
+        let sig =
+            parse_signature("BOOL GetSize(HANDLE h, _Out_ DWORD* size, out struct stat* st[144])")
+                .unwrap();
+        assert_eq!(sig.args[0], arg("h", VzValueType::Pointer));
+        assert_eq!(
+            sig.args[1],
+            ArgSpec {
+                indirect: true,
+                out: true,
+                ..arg("size", VzValueType::UInt)
+            }
+        );
+        assert!(sig.args[2].out);
+        assert_eq!(sig.args[2].size, Some(144));
+        assert!(sig.has_out_args());
+
+        let sig =
+            parse_signature("int posix_memalign(out void** memptr, size_t align, size_t size)")
+                .unwrap();
+        assert_eq!(
+            sig.args[0],
+            ArgSpec {
+                indirect: true,
+                out: true,
+                ..arg("memptr", VzValueType::Pointer)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_size_from_ret() {
+        let sig = parse_signature("ssize_t f(out char* s[ret], out void* buf[ret])").unwrap();
+        assert_eq!(sig.args[0].value_type, VzValueType::String);
+        assert!(sig.args[0].size_from_ret);
+        assert_eq!(sig.args[1].value_type, VzValueType::Bytes);
+        assert_eq!(sig.args[1].size, None);
+        assert_eq!(sig.args[1].to_string(), "out Bytes buf[ret]");
+        assert!(parse_signature("int f(int n[ret])").is_err());
+    }
+
+    #[test]
+    fn test_parse_value_type_names_and_variadic() {
+        let sig = parse_signature("int printf(string fmt, ...)").unwrap();
+        assert_eq!(sig.args, vec![arg("fmt", VzValueType::String)]);
+        let sig = parse_signature("void* malloc(size_t size);").unwrap();
+        assert_eq!(sig.ret, Some(arg("ret", VzValueType::Pointer)));
+        assert!(parse_signature("int getpid(void)").unwrap().args.is_empty());
+    }
+
+    #[test]
+    fn test_native_types() {
+        let sig =
+            parse_signature("double scale(float f, double* d, const char* s, int n)").unwrap();
+        assert_eq!(sig.ret.unwrap().native_type(), "double");
+        let types: Vec<&str> = sig.args.iter().map(ArgSpec::native_type).collect();
+        assert_eq!(types, ["float", "pointer", "pointer", "pointer"]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_signature("open").is_err());
+        assert!(parse_signature("open(int fd)").is_err());
+        assert!(parse_signature("int f(int x[4])").is_err());
+        assert!(parse_signature("int f(void x)").is_err());
+        assert!(parse_signature("int f(char* p[zz])").is_err());
+    }
+
+    #[test]
+    fn test_builtin_db() {
+        let db = SignatureDb::new();
+        for name in [
+            "open", "read", "mmap", "connect", "recv", "dlopen", "getenv",
+        ] {
+            assert!(db.get(name).is_some(), "missing {}", name);
+        }
+        assert_eq!(db.get("_malloc").unwrap().name, "malloc");
+        assert_eq!(db.get("free").unwrap().ret, None);
+        // The mode of open is optional
+        assert_eq!(db.get("open").unwrap().args.len(), 2);
+        assert!(db.get("readlink").unwrap().has_out_args());
+        for name in ["read", "pread", "recv", "recvfrom", "fread"] {
+            let sig = db.get(name).unwrap();
+            assert!(
+                sig.args.iter().any(|a| a.out && a.size_from_ret),
+                "{}",
+                name
+            );
+        }
+        assert!(db.get("no_such_function").is_none());
+        assert_eq!(
+            db.get("readlink").unwrap().to_string(),
+            "Long readlink(String path, out String buf[ret], uLong bufsiz)"
+        );
+    }
+
+    #[test]
+    fn test_db_load_str() {
+        let mut db = SignatureDb::new();
+        let text = "# in-house\nint vz_open(const char* name,\n    int mode);\n// override\nvoid free(void* p); int vz_close(int h);\n";
+        assert_eq!(db.load_str(text), Ok(3));
+        assert_eq!(db.get("vz_open").unwrap().args.len(), 2);
+        assert_eq!(db.get("free").unwrap().args[0].name, "p");
+        assert!(db.get("vz_close").is_some());
+
+        assert_eq!(
+            db.load_str("int ok(int a);\n\nint bad(int x[4]);"),
+            Err("line 3: Only pointers can have a size: int[4]".to_string())
+        );
+        assert_eq!(
+            db.load_str("int f(int a)\n"),
+            Err("line 1: missing ';'".to_string())
+        );
+    }
+}
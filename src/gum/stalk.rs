@@ -0,0 +1,271 @@
+// src/gum/stalk.rs
+//
+// `stalk` follows a thread with Stalker. The agent sends each drained batch
+// as a `stalk:events` message whose binary data holds fixed-size records;
+// the JSON part names the thread and symbolizes addresses the host has not
+// seen yet.
+
+use super::event::{Frame, HOOK_EVENT_VERSION};
+use super::vzdata::string_to_u64;
+use crossterm::style::Stylize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Bytes per record: kind (u32), depth (i32), two addresses (u64), all
+/// little-endian
+pub const RECORD_SIZE: usize = 24;
+
+/// What a record's two addresses are depends on its kind: call site and
+/// callee, `ret` instruction and return address, instruction and nothing,
+/// or block start and end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StalkKind {
+    Call,
+    Ret,
+    Exec,
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalkEvent {
+    pub kind: StalkKind,
+    pub depth: i32,
+    pub location: u64,
+    pub target: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StalkBatch {
+    pub thread_id: u64,
+    pub symbols: BTreeMap<u64, Frame>,
+    pub events: Vec<StalkEvent>,
+}
+
+impl StalkKind {
+    fn from_code(code: u32) -> Option<StalkKind> {
+        match code {
+            0 => Some(StalkKind::Call),
+            1 => Some(StalkKind::Ret),
+            2 => Some(StalkKind::Exec),
+            3 => Some(StalkKind::Block),
+            _ => None,
+        }
+    }
+}
+
+/// Decode records; a trailing partial record and unknown kinds are skipped
+pub fn parse_records(data: &[u8]) -> Vec<StalkEvent> {
+    data.chunks_exact(RECORD_SIZE)
+        .filter_map(|record| {
+            let word = |at: usize| u32::from_le_bytes(record[at..at + 4].try_into().unwrap());
+            let addr = |at: usize| u64::from_le_bytes(record[at..at + 8].try_into().unwrap());
+            Some(StalkEvent {
+                kind: StalkKind::from_code(word(0))?,
+                depth: word(4) as i32,
+                location: addr(8),
+                target: addr(16),
+            })
+        })
+        .collect()
+}
+
+/// Decode a `stalk:events` message; `None` for other messages and newer
+/// schemas
+pub fn parse_batch(payload: &Value, data: Option<&[u8]>) -> Option<StalkBatch> {
+    if payload.get("type")?.as_str()? != "stalk:events" {
+        return None;
+    }
+    if payload.get("v")?.as_u64()? > HOOK_EVENT_VERSION {
+        return None;
+    }
+    let symbols = payload
+        .get("symbols")
+        .and_then(|v| v.as_object())
+        .map(|symbols| {
+            symbols
+                .values()
+                .filter_map(|frame| serde_json::from_value::<Frame>(frame.clone()).ok())
+                .map(|frame| (string_to_u64(&frame.address), frame))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(StalkBatch {
+        thread_id: payload.get("threadId")?.as_u64()?,
+        symbols,
+        events: data.map(parse_records).unwrap_or_default(),
+    })
+}
+
+/// Formats stalked events. The agent symbolizes each address once, so the
+/// renderer keeps every symbol it is sent.
+#[derive(Default)]
+pub struct StalkRenderer {
+    last_thread: Option<u64>,
+    symbols: HashMap<u64, Frame>,
+}
+
+impl StalkRenderer {
+    pub fn render(&mut self, batch: StalkBatch) -> Vec<String> {
+        self.symbols.extend(batch.symbols);
+        let mut lines = Vec::new();
+        for event in &batch.events {
+            if self.last_thread != Some(batch.thread_id) {
+                self.last_thread = Some(batch.thread_id);
+                lines.push(
+                    format!("/* TID {} */", batch.thread_id)
+                        .dark_grey()
+                        .to_string(),
+                );
+            }
+            lines.push(self.render_event(event));
+        }
+        lines
+    }
+
+    fn render_event(&self, event: &StalkEvent) -> String {
+        let indent = "   | ".repeat(event.depth.max(0) as usize);
+        match event.kind {
+            StalkKind::Call => format!(
+                "{}-> {} {}",
+                indent,
+                self.location(event.target).cyan(),
+                format!("(from {})", self.location(event.location)).dark_grey()
+            ),
+            StalkKind::Ret => format!(
+                "{}<- {} {}",
+                indent,
+                self.location(event.location).cyan(),
+                format!("(to {})", self.location(event.target)).dark_grey()
+            ),
+            StalkKind::Exec => format!(
+                "{} {}",
+                format!("{:#x}", event.location).yellow(),
+                self.location(event.location).dark_grey()
+            ),
+            StalkKind::Block => format!(
+                "{} {} {}",
+                format!("{:#x}", event.location).yellow(),
+                self.location(event.location).cyan(),
+                format!("({} bytes)", event.target.saturating_sub(event.location)).dark_grey()
+            ),
+        }
+    }
+
+    /// `module!symbol +offset` where the agent could tell, else the address
+    fn location(&self, address: u64) -> String {
+        match self.symbols.get(&address) {
+            Some(frame) if frame.module.is_some() || frame.symbol.is_some() => frame.location(),
+            _ => format!("{:#x}", address),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::format::plain;
+    use serde_json::json;
+
+    fn record(kind: u32, depth: i32, location: u64, target: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(kind.to_le_bytes());
+        bytes.extend(depth.to_le_bytes());
+        bytes.extend(location.to_le_bytes());
+        bytes.extend(target.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let mut data = record(0, 1, 0x1000, 0x2000);
+        data.extend(record(9, 0, 0, 0));
+        data.extend(record(1, -1, 0x2010, 0x1005));
+        data.extend([0; 5]);
+        assert_eq!(
+            parse_records(&data),
+            [
+                StalkEvent {
+                    kind: StalkKind::Call,
+                    depth: 1,
+                    location: 0x1000,
+                    target: 0x2000
+                },
+                StalkEvent {
+                    kind: StalkKind::Ret,
+                    depth: -1,
+                    location: 0x2010,
+                    target: 0x1005
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let payload = json!({
+            "type": "stalk:events",
+            "v": 1,
+            "threadId": 7,
+            "symbols": {
+                "0x2000": {"address": "0x2000", "module": "libc.so.6", "symbol": "malloc", "offset": 0x2000}
+            }
+        });
+        let data = record(0, 0, 0x1000, 0x2000);
+        let batch = parse_batch(&payload, Some(&data)).unwrap();
+        assert_eq!(batch.thread_id, 7);
+        assert_eq!(batch.events.len(), 1);
+        assert_eq!(batch.symbols[&0x2000].symbol.as_deref(), Some("malloc"));
+
+        let mut newer = payload.clone();
+        newer["v"] = json!(HOOK_EVENT_VERSION + 1);
+        assert_eq!(parse_batch(&newer, Some(&data)), None);
+        assert_eq!(
+            parse_batch(&json!({"type": "trace:events", "v": 1}), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render_calls() {
+        let frame = |address: &str, symbol: Option<&str>, offset| Frame {
+            address: address.to_string(),
+            module: Some("app".to_string()),
+            symbol: symbol.map(String::from),
+            offset: Some(offset),
+        };
+        let mut renderer = StalkRenderer::default();
+        let batch = |events| StalkBatch {
+            thread_id: 7,
+            symbols: BTreeMap::from([
+                (0x1000, frame("0x1000", Some("main"), 0x1000)),
+                (0x2000, frame("0x2000", Some("parse"), 0x2000)),
+                (0x2040, frame("0x2040", Some("parse"), 0x2040)),
+            ]),
+            events,
+        };
+        let event = |kind, depth, location, target| StalkEvent {
+            kind,
+            depth,
+            location,
+            target,
+        };
+        let lines: Vec<String> = renderer
+            .render(batch(vec![
+                event(StalkKind::Call, 0, 0x1000, 0x2000),
+                event(StalkKind::Ret, 1, 0x2040, 0x1005),
+            ]))
+            .iter()
+            .chain(&renderer.render(batch(vec![event(StalkKind::Block, 0, 0x2000, 0x2010)])))
+            .map(plain)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "/* TID 7 */",
+                "-> app!parse +0x2000 (from app!main +0x1000)",
+                "   | <- app!parse +0x2040 (to 0x1005)",
+                "0x2000 app!parse +0x2000 (16 bytes)",
+            ]
+        );
+    }
+}
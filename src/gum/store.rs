@@ -1,10 +1,11 @@
 // src/gum/store.rs
 
 use super::{
-    filter::{FilterOperator, FilterSegment, FilterValue, LogicalOperator},
-    vzdata::VzData,
+    filter::{self, evaluate_segments, FilterOperator, FilterSegment, FilterValue},
+    vzdata::{VzData, VzHookEvent},
 };
 use crossterm::style::Stylize;
+use serde_json::{json, Value};
 use std::fmt::Debug; // Required for format!("{:?}") on VzDataType
 use std::{collections::BTreeSet, fmt};
 
@@ -283,6 +284,7 @@ impl Store {
                 VzData::Range(r) => Some(r.address),
                 VzData::Function(f) => Some(f.address),
                 VzData::Variable(v) => Some(v.address),
+                VzData::HookEvent(e) => Some(e.address),
                 _ => None,
             }
         }
@@ -303,6 +305,7 @@ impl Store {
         fn get_id(item: &VzData) -> Option<u64> {
             match item {
                 VzData::Thread(t) => Some(t.id as u64),
+                VzData::HookEvent(e) => Some(e.timestamp),
                 _ => None,
             }
         }
@@ -319,7 +322,7 @@ impl Store {
                     (Some(_), None) => Ordering::Less,
                     (None, Some(_)) => Ordering::Greater,
                     (None, None) => {
-                        // Fallback to id (e.g., threads) or address
+                        // Fallback to id (e.g., threads, event times) or address
                         let ida = get_id(a).or_else(|| get_addr(a));
                         let idb = get_id(b).or_else(|| get_addr(b));
                         ida.unwrap_or(0).cmp(&idb.unwrap_or(0))
@@ -335,32 +338,19 @@ impl Store {
             return; // No filter, do nothing
         }
 
-        // Evaluate left-to-right with explicit AND/OR operators
-        self.data.retain(|item: &VzData| {
-            let mut acc: Option<bool> = None;
-            let mut current_op = LogicalOperator::And;
-            for segment in &filter_segments {
-                match segment {
-                    FilterSegment::Condition(cond) => {
-                        let res = Self::evaluate_condition_for_item(item, cond);
-                        acc = Some(match acc {
-                            None => res,
-                            Some(prev) => match current_op {
-                                LogicalOperator::And => prev && res,
-                                LogicalOperator::Or => prev || res,
-                            },
-                        });
-                    }
-                    FilterSegment::Logical(op) => {
-                        current_op = op.clone();
-                    }
-                }
-            }
-            acc.unwrap_or(true)
-        });
+        self.data
+            .retain(|item: &VzData| Self::matches(item, &filter_segments));
         self.adjust_cursor();
     }
 
+    /// Whether `item` passes `segments`, evaluated left-to-right with explicit
+    /// AND/OR operators
+    pub fn matches(item: &VzData, segments: &[FilterSegment]) -> bool {
+        evaluate_segments(segments, |cond| {
+            Self::evaluate_condition_for_item(item, cond)
+        })
+    }
+
     fn evaluate_condition_for_item(
         vz_data_item: &VzData,
         condition: &super::filter::FilterCondition,
@@ -381,6 +371,11 @@ impl Store {
         vz_data_item: &VzData,
         key: &str,
     ) -> Option<FilterValue> {
+        if let VzData::HookEvent(e) = vz_data_item {
+            if let Some(value) = Self::hook_event_field(e, key) {
+                return Some(value);
+            }
+        }
         match key.to_lowercase().as_str() {
             "name" => match vz_data_item {
                 VzData::Module(m) => Some(FilterValue::String(m.name.clone())),
@@ -457,6 +452,10 @@ impl Store {
                 VzData::Variable(v) => Some(FilterValue::String(v.module.clone())),
                 _ => None,
             },
+            "label" => vz_data_item.base().label.clone().map(FilterValue::String),
+            "comment" | "note" => vz_data_item.base().comment.clone().map(FilterValue::String),
+            // Tags compare as one comma-separated string, so `tag:heap` matches any tag
+            "tag" | "tags" => Some(FilterValue::String(vz_data_item.base().tags.join(","))),
             "class" | "class_name" => match vz_data_item {
                 VzData::JavaMethod(jm) => Some(FilterValue::String(jm.class.clone())),
                 VzData::ObjCMethod(om) => Some(FilterValue::String(om.class.clone())),
@@ -466,6 +465,52 @@ impl Store {
         }
     }
 
+    /// Hook event keys; `type` is the event kind (`enter`/`leave`) here
+    fn hook_event_field(event: &VzHookEvent, key: &str) -> Option<FilterValue> {
+        let key = key.to_lowercase();
+        if let Some(index) = key.strip_prefix("arg") {
+            let arg = event.args.get(index.parse::<usize>().ok()?)?;
+            return Some(Self::event_scalar(&arg.value, arg.decoded.as_deref()));
+        }
+        match key.as_str() {
+            "id" => Some(FilterValue::String(event.id.clone())),
+            "type" | "kind" => Some(FilterValue::String(event.kind.name().to_string())),
+            "tid" | "thread" => Some(FilterValue::Number(event.tid as f64)),
+            "depth" => Some(FilterValue::Number(event.depth as f64)),
+            "address" => Some(FilterValue::Number(event.address as f64)),
+            "timestamp" | "time" => Some(FilterValue::Number(event.timestamp as f64)),
+            "retval" => event
+                .retval
+                .as_ref()
+                .map(|r| Self::event_scalar(&r.value, r.decoded.as_deref())),
+            "caller" => event
+                .backtrace
+                .first()
+                .map(|f| FilterValue::String(f.location())),
+            "backtrace" | "bt" => Some(FilterValue::String(
+                event
+                    .backtrace
+                    .iter()
+                    .map(|f| f.location())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Filter value of a register-sized value: the decoded value when a
+    /// signature gave one (strings unquoted), else the raw value as a number
+    fn event_scalar(value: &str, decoded: Option<&str>) -> FilterValue {
+        match decoded {
+            Some(d) => match serde_json::from_str::<String>(d) {
+                Ok(s) => FilterValue::String(s),
+                Err(_) => filter::parse_value(d),
+            },
+            None => filter::parse_value(value),
+        }
+    }
+
     fn compare_filter_values(
         item_val: &FilterValue,
         op: &super::filter::FilterOperator,
@@ -526,6 +571,42 @@ impl Store {
         }
     }
 
+    /// One JSON object per entry on the given page (current page when `None`),
+    /// tagged with its store index
+    pub fn to_json(&self, page: Option<usize>) -> Vec<Value> {
+        let (current_page, total_pages) = self.get_page_info();
+        let page = page.unwrap_or(current_page).clamp(1, total_pages.max(1));
+        let start = (page - 1) * self.page_size;
+        match self.get_data_by_page(page) {
+            Ok(data) => data
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| Self::entry_to_json(start + i, item))
+                .collect(),
+            Err(e) => {
+                crate::util::logger::error(&format!("Page error: {}", e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Every entry in the store as JSON, regardless of paging
+    pub fn all_to_json(&self) -> Vec<Value> {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(i, item)| Self::entry_to_json(i, item))
+            .collect()
+    }
+
+    fn entry_to_json(index: usize, item: &VzData) -> Value {
+        let mut value = serde_json::to_value(item).unwrap_or(Value::Null);
+        if let Value::Object(ref mut obj) = value {
+            obj.insert("index".to_string(), json!(index));
+        }
+        value
+    }
+
     pub fn to_string(&self, page: Option<usize>) -> String {
         let cursor = if self.data.len() > 0 {
             self.get_cursor()
@@ -554,9 +635,10 @@ impl Store {
         for (i, item) in data.iter().enumerate() {
             let global_idx = self.get_cursor() + i;
             body.push_str(&format!(
-                "\n[{}] {}",
+                "\n[{}] {}{}",
                 format!("{:^width$}", global_idx, width = max_idx_len).blue(),
-                item
+                item,
+                item.base().annotation()
             ));
         }
         format!("{}{}", header, body)
@@ -340,4 +340,120 @@ mod tests {
         assert_eq!(store.data.len(), 0);
         assert_eq!(store.get_page_info(), (1, 1));
     }
+
+    #[test]
+    fn test_to_json_entries() {
+        let mut store = Store::new("Test".into());
+
+        // Add 60 items, spilling onto a second page
+        for i in 0..60 {
+            store.add_datas(vec![VzData::Module(crate::gum::vzdata::VzModule {
+                base: crate::gum::vzdata::new_base(crate::gum::vzdata::VzDataType::Module),
+                name: format!("module_{}", i),
+                address: 0x7f00_0000 + i as u64 * 0x1000,
+                size: 0x1000,
+            })]);
+        }
+
+        let first = store.to_json(None);
+        assert_eq!(first.len(), 50);
+        assert_eq!(first[0]["type"], "Module");
+        assert_eq!(first[0]["name"], "module_0");
+        assert_eq!(first[0]["address"], "0x7f000000");
+        assert_eq!(first[0]["index"], 0);
+
+        let second = store.to_json(Some(2));
+        assert_eq!(second.len(), 10);
+        assert_eq!(second[0]["index"], 50);
+
+        assert_eq!(store.all_to_json().len(), 60);
+    }
+
+    #[test]
+    fn test_annotations_render_and_filter() {
+        let mut store = Store::new("Test".into());
+        for i in 0..3u64 {
+            store.add_datas(vec![VzData::Module(crate::gum::vzdata::VzModule {
+                base: crate::gum::vzdata::new_base(crate::gum::vzdata::VzDataType::Module),
+                name: format!("module_{}", i),
+                address: 0x1000 * (i + 1),
+                size: 0x1000,
+            })]);
+        }
+        let base = store.data[1].base_mut();
+        base.label = Some("player".into());
+        base.comment = Some("health at +0x40".into());
+        base.tags = vec!["heap".into(), "struct".into()];
+
+        let plain = String::from_utf8(strip_ansi_escapes::strip(store.to_string(None))).unwrap();
+        assert!(plain.contains("<player> #heap #struct ; health at +0x40"));
+
+        // Annotations survive a JSON round trip, and are omitted when unset
+        let json = serde_json::to_value(&store.data[1]).unwrap();
+        assert_eq!(json["label"], "player");
+        assert!(serde_json::to_value(&store.data[0])
+            .unwrap()
+            .get("tags")
+            .is_none());
+        let parsed: VzData = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, store.data[1]);
+
+        store.filter(vec![crate::gum::filter::FilterSegment::Condition(
+            crate::gum::filter::FilterCondition {
+                key: "tag".into(),
+                operator: crate::gum::filter::FilterOperator::Contains,
+                value: crate::gum::filter::FilterValue::String("heap".into()),
+            },
+        )]);
+        assert_eq!(store.data.len(), 1);
+        assert_eq!(store.data[0].base().label.as_deref(), Some("player"));
+    }
+
+    #[test]
+    fn test_hook_events() {
+        use crate::gum::event::HookEvent;
+        use crate::gum::vzdata::VzHookEvent;
+
+        let event = |timestamp: u64, path: &str| {
+            let payload = serde_json::json!({
+                "type": "hook:enter",
+                "v": 1,
+                "id": "hook_2",
+                "address": "0x1000",
+                "threadId": 5,
+                "timestamp": timestamp,
+                "args": [
+                    {"value": "0x5000", "name": "path", "decoded": format!("\"{}\"", path)},
+                    {"value": "0x3"}
+                ],
+                "backtrace": [{"address": "0x2000", "module": "app", "symbol": "main"}]
+            });
+            VzData::HookEvent(VzHookEvent::from(&HookEvent::parse(&payload).unwrap()))
+        };
+        let mut store = Store::new("Test".into());
+        store.add_datas(vec![event(20, "/etc/passwd"), event(10, "/etc/hosts")]);
+
+        // Events sort by time, and survive a JSON round trip (e.g. `lib export`)
+        store.sort(None);
+        let VzData::HookEvent(first) = &store.data[0] else {
+            panic!("Expected a hook event");
+        };
+        assert_eq!(first.timestamp, 10);
+        assert_eq!(first.member("arg0"), Some(0x5000));
+        assert_eq!(first.member("arg1"), Some(3));
+        assert_eq!(first.member("caller"), Some(0x2000));
+        assert_eq!(first.member("retval"), None);
+        assert_eq!(first.member("arg2"), None);
+        let json = serde_json::to_value(&store.data[0]).unwrap();
+        assert_eq!(json["type"], "HookEvent");
+        let parsed: VzData = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, store.data[0]);
+
+        store.filter(crate::gum::filter::parse_filter_string("arg0 : passwd & tid = 5").unwrap());
+        assert_eq!(store.data.len(), 1);
+        let VzData::HookEvent(left) = &store.data[0] else {
+            panic!("Expected a hook event");
+        };
+        assert_eq!(left.timestamp, 20);
+    }
 }
@@ -0,0 +1,163 @@
+// src/gum/trace.rs
+//
+// `trace` hooks many functions at once and prints their calls as an indented
+// tree per thread, frida-trace style. The agent sends calls in batches as
+// `trace:events` messages.
+
+use super::event::{format_timestamp, HOOK_EVENT_VERSION};
+use crossterm::style::Stylize;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Refuse patterns matching more functions than this; each one is a hook
+pub const MAX_TRACE_TARGETS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceKind {
+    Enter,
+    Leave,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEvent {
+    pub kind: TraceKind,
+    pub name: String,
+    pub thread_id: u64,
+    #[serde(default)]
+    pub depth: u64,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub retval: Option<String>,
+}
+
+/// Decode a `trace:events` batch; `None` for other messages and newer schemas
+pub fn parse_batch(payload: &Value) -> Option<Vec<TraceEvent>> {
+    if payload.get("type")?.as_str()? != "trace:events" {
+        return None;
+    }
+    if payload.get("v")?.as_u64()? > HOOK_EVENT_VERSION {
+        return None;
+    }
+    serde_json::from_value(payload.get("events")?.clone()).ok()
+}
+
+/// Anchored regex for a shell-style glob (`*` and `?`)
+pub fn glob_to_regex(glob: &str) -> Regex {
+    let pattern = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+    Regex::new(&format!("^{}$", pattern)).expect("Escaped glob must compile")
+}
+
+/// Formats trace calls, printing a thread header whenever the thread changes
+#[derive(Default)]
+pub struct TraceRenderer {
+    last_thread: Option<u64>,
+}
+
+impl TraceRenderer {
+    pub fn render(&mut self, event: &TraceEvent) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.last_thread != Some(event.thread_id) {
+            self.last_thread = Some(event.thread_id);
+            lines.push(
+                format!("             /* TID {} */", event.thread_id)
+                    .dark_grey()
+                    .to_string(),
+            );
+        }
+        let indent = "   | ".repeat(event.depth as usize);
+        let call = match event.kind {
+            TraceKind::Enter => format!("{}()", event.name.clone().cyan()),
+            TraceKind::Leave => format!(
+                "<- {} = {}",
+                event.name.clone().cyan(),
+                event.retval.as_deref().unwrap_or("?").yellow()
+            ),
+        };
+        lines.push(format!(
+            "{} {}{}",
+            format_timestamp(event.timestamp).dark_grey(),
+            indent,
+            call
+        ));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::format::plain;
+    use serde_json::json;
+
+    #[test]
+    fn test_glob_to_regex() {
+        let re = glob_to_regex("SSL_*");
+        assert!(re.is_match("SSL_read"));
+        assert!(re.is_match("SSL_"));
+        assert!(!re.is_match("xSSL_read"));
+        let re = glob_to_regex("get?id");
+        assert!(re.is_match("getpid"));
+        assert!(!re.is_match("getppid"));
+        assert!(glob_to_regex("a.b").is_match("a.b"));
+        assert!(!glob_to_regex("a.b").is_match("axb"));
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let payload = json!({
+            "type": "trace:events",
+            "v": 1,
+            "events": [
+                {"kind": "enter", "name": "SSL_read", "threadId": 7, "depth": 0, "timestamp": 0},
+                {"kind": "leave", "name": "SSL_read", "threadId": 7, "depth": 0, "timestamp": 1, "retval": "0x10"}
+            ]
+        });
+        let events = parse_batch(&payload).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].kind, TraceKind::Leave);
+        assert_eq!(events[1].retval.as_deref(), Some("0x10"));
+
+        let mut newer = payload.clone();
+        newer["v"] = json!(HOOK_EVENT_VERSION + 1);
+        assert_eq!(parse_batch(&newer), None);
+        assert_eq!(parse_batch(&json!({"type": "hook:enter", "v": 1})), None);
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let event = |kind, name: &str, thread_id, depth| TraceEvent {
+            kind,
+            name: name.to_string(),
+            thread_id,
+            depth,
+            timestamp: 1_000,
+            retval: Some("0x0".to_string()),
+        };
+        let mut renderer = TraceRenderer::default();
+        let lines: Vec<String> = [
+            event(TraceKind::Enter, "SSL_read", 7, 0),
+            event(TraceKind::Enter, "SSL_get_error", 7, 1),
+            event(TraceKind::Enter, "SSL_write", 8, 0),
+            event(TraceKind::Leave, "SSL_read", 7, 0),
+        ]
+        .iter()
+        .flat_map(|e| renderer.render(e))
+        .map(|l| plain(&l))
+        .collect();
+        assert_eq!(
+            lines,
+            [
+                "             /* TID 7 */",
+                "00:00:01.000 SSL_read()",
+                "00:00:01.000    | SSL_get_error()",
+                "             /* TID 8 */",
+                "00:00:01.000 SSL_write()",
+                "             /* TID 7 */",
+                "00:00:01.000 <- SSL_read = 0x0",
+            ]
+        );
+    }
+}
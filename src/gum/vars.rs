@@ -0,0 +1,168 @@
+// src/gum/vars.rs
+use std::collections::{BTreeMap, VecDeque};
+
+/// Number of resolved addresses kept as `$1`..`$9`
+const ADDRESS_HISTORY: usize = 9;
+
+/// Session variables: user-defined `$name`s plus automatic ones.
+///
+/// - `$_` / `$1`..`$9`: addresses the user last looked at, newest first
+/// - `$_val`: value printed by the last `read`
+/// - `$_hook`: ID of the last hook added
+#[derive(Debug, Default)]
+pub struct Variables {
+    user: BTreeMap<String, String>,
+    addresses: VecDeque<u64>,
+    last_value: Option<String>,
+    last_hook: Option<String>,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        let name = name.strip_prefix('$').unwrap_or(name);
+        match name {
+            "_" => self.addresses.front().map(|a| format!("{:#x}", a)),
+            "_val" => self.last_value.clone(),
+            "_hook" => self.last_hook.clone(),
+            n if n.len() == 1 && n.starts_with(|c: char| c.is_ascii_digit()) => {
+                let index = n.parse::<usize>().ok()?.checked_sub(1)?;
+                self.addresses.get(index).map(|a| format!("{:#x}", a))
+            }
+            n => self.user.get(n).cloned(),
+        }
+    }
+
+    /// Define a user variable; names are `[A-Za-z][A-Za-z0-9_]*` and may be
+    /// given with or without the leading `$`
+    pub fn set(&mut self, name: &str, value: String) -> Result<(), String> {
+        let name = name.strip_prefix('$').unwrap_or(name);
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!("Invalid variable name: ${}", name));
+        }
+        if name == "nav" {
+            return Err("$nav is reserved for the navigator address".to_string());
+        }
+        self.user.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    pub fn push_address(&mut self, address: u64) {
+        self.addresses.push_front(address);
+        self.addresses.truncate(ADDRESS_HISTORY);
+    }
+
+    pub fn set_last_value(&mut self, value: String) {
+        self.last_value = Some(value);
+    }
+
+    pub fn set_last_hook(&mut self, id: String) {
+        self.last_hook = Some(id);
+    }
+
+    /// All defined variables as `(name, value)`, automatic ones first
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut names: Vec<String> = vec!["_".into(), "_val".into(), "_hook".into()];
+        names.extend((1..=self.addresses.len()).map(|i| i.to_string()));
+        names.extend(self.user.keys().cloned());
+        names
+            .into_iter()
+            .filter_map(|n| self.get(&n).map(|v| (format!("${}", n), v)))
+            .collect()
+    }
+
+    /// Names usable in completion, with the leading `$`
+    pub fn names(&self) -> Vec<String> {
+        self.entries().into_iter().map(|(n, _)| n).collect()
+    }
+
+    /// Replace every known `$name` in `arg` with its value. Unknown names
+    /// such as `$nav` are left for the expression evaluator.
+    pub fn expand(&self, arg: &str) -> String {
+        let mut out = String::with_capacity(arg.len());
+        let mut rest = arg;
+        while let Some(pos) = rest.find('$') {
+            out.push_str(&rest[..pos]);
+            let after = &rest[pos + 1..];
+            let len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            let name = &after[..len];
+            match self.get(name).filter(|_| !name.is_empty()) {
+                Some(value) => out.push_str(&value),
+                None => {
+                    out.push('$');
+                    out.push_str(name);
+                }
+            }
+            rest = &after[len..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_variable() {
+        let mut vars = Variables::new();
+        vars.set("$base", "0x7f000000".into()).unwrap();
+        assert_eq!(vars.get("base").as_deref(), Some("0x7f000000"));
+        assert_eq!(vars.get("$base").as_deref(), Some("0x7f000000"));
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let mut vars = Variables::new();
+        assert!(vars.set("1abc", "0".into()).is_err());
+        assert!(vars.set("a-b", "0".into()).is_err());
+        assert!(vars.set("_", "0".into()).is_err());
+        assert!(vars.set("$nav", "0".into()).is_err());
+    }
+
+    #[test]
+    fn test_address_history() {
+        let mut vars = Variables::new();
+        assert_eq!(vars.get("_"), None);
+        for addr in 1..=12u64 {
+            vars.push_address(addr * 0x10);
+        }
+        assert_eq!(vars.get("_").as_deref(), Some("0xc0"));
+        assert_eq!(vars.get("1").as_deref(), Some("0xc0"));
+        assert_eq!(vars.get("2").as_deref(), Some("0xb0"));
+        assert_eq!(vars.get("9").as_deref(), Some("0x40"));
+        assert_eq!(vars.get("0"), None);
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut vars = Variables::new();
+        vars.set("buf", "0x1000".into()).unwrap();
+        vars.set_last_hook("hook_3".into());
+        vars.push_address(0x2000);
+        assert_eq!(vars.expand("$buf+0x10"), "0x1000+0x10");
+        assert_eq!(vars.expand("[$_]"), "[0x2000]");
+        assert_eq!(vars.expand("$_hook"), "hook_3");
+        assert_eq!(vars.expand("$nav+4"), "$nav+4");
+        assert_eq!(vars.expand("cost: $"), "cost: $");
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut vars = Variables::new();
+        vars.set("a", "1".into()).unwrap();
+        vars.set_last_value("42".into());
+        assert_eq!(
+            vars.entries(),
+            vec![("$_val".into(), "42".into()), ("$a".into(), "1".into())]
+        );
+    }
+}
@@ -1,8 +1,10 @@
 // src/gum/vzdata.rs
+use super::event::{format_timestamp, EventValue, Frame, HookEvent, HookEventKind};
 use crossterm::style::Stylize;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VzDataType {
     Pointer,
     Module,
@@ -19,6 +21,7 @@ pub enum VzDataType {
     ScanResult,
     Import,
     Symbol,
+    HookEvent,
 }
 
 impl fmt::Display for VzDataType {
@@ -39,17 +42,43 @@ impl fmt::Display for VzDataType {
             VzDataType::ScanResult => write!(f, "ScanResult"),
             VzDataType::Import => write!(f, "Import"),
             VzDataType::Symbol => write!(f, "Symbol"),
+            VzDataType::HookEvent => write!(f, "HookEvent"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzBase {
+    #[serde(rename = "type")]
     pub data_type: VzDataType,
     pub is_saved: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl VzBase {
+    /// Label, tags and comment formatted for display after an entry,
+    /// with a leading space; empty when there is nothing to show
+    pub fn annotation(&self) -> String {
+        let mut out = String::new();
+        if let Some(label) = &self.label {
+            out.push_str(&format!(" {}", format!("<{}>", label).magenta()));
+        }
+        for tag in &self.tags {
+            out.push_str(&format!(" {}", format!("#{}", tag).cyan()));
+        }
+        if let Some(comment) = &self.comment {
+            out.push_str(&format!(" {}", format!("; {}", comment).dark_grey()));
+        }
+        out
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VzValueType {
     Byte,
     Int8,
@@ -75,6 +104,7 @@ pub enum VzValueType {
     Boolean,
     String,
     Utf8,
+    Utf16,
     Array,
     Bytes,
     Pointer,
@@ -96,6 +126,7 @@ impl fmt::Display for VzValueType {
             VzValueType::Double | VzValueType::Float64 => write!(f, "Double"),
             VzValueType::Bool | VzValueType::Boolean => write!(f, "Bool"),
             VzValueType::String | VzValueType::Utf8 => write!(f, "String"),
+            VzValueType::Utf16 => write!(f, "Utf16"),
             VzValueType::Array | VzValueType::Bytes => write!(f, "Bytes"),
             VzValueType::Pointer => write!(f, "Pointer"),
             VzValueType::Void => write!(f, "Void"),
@@ -103,7 +134,8 @@ impl fmt::Display for VzValueType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
 pub enum VzData {
     Pointer(VzPointer),
     Module(VzModule),
@@ -120,6 +152,82 @@ pub enum VzData {
     ScanResult(VzScanResult),
     Import(VzImport),
     Symbol(VzSymbol),
+    HookEvent(VzHookEvent),
+}
+
+// Entries carry their variant in the flattened `type` field, so dispatch on it
+impl<'de> Deserialize<'de> for VzData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let data_type = value
+            .get("type")
+            .cloned()
+            .ok_or_else(|| D::Error::missing_field("type"))?;
+        let data_type: VzDataType = serde_json::from_value(data_type).map_err(D::Error::custom)?;
+        let data = match data_type {
+            VzDataType::Pointer => serde_json::from_value(value).map(VzData::Pointer),
+            VzDataType::Module => serde_json::from_value(value).map(VzData::Module),
+            VzDataType::Range => serde_json::from_value(value).map(VzData::Range),
+            VzDataType::Function => serde_json::from_value(value).map(VzData::Function),
+            VzDataType::Variable => serde_json::from_value(value).map(VzData::Variable),
+            VzDataType::JavaClass => serde_json::from_value(value).map(VzData::JavaClass),
+            VzDataType::JavaMethod => serde_json::from_value(value).map(VzData::JavaMethod),
+            VzDataType::ObjCClass => serde_json::from_value(value).map(VzData::ObjCClass),
+            VzDataType::ObjCMethod => serde_json::from_value(value).map(VzData::ObjCMethod),
+            VzDataType::Thread => serde_json::from_value(value).map(VzData::Thread),
+            VzDataType::Hook => serde_json::from_value(value).map(VzData::Hook),
+            VzDataType::Instruction => serde_json::from_value(value).map(VzData::Instruction),
+            VzDataType::ScanResult => serde_json::from_value(value).map(VzData::ScanResult),
+            VzDataType::Import => serde_json::from_value(value).map(VzData::Import),
+            VzDataType::Symbol => serde_json::from_value(value).map(VzData::Symbol),
+            VzDataType::HookEvent => serde_json::from_value(value).map(VzData::HookEvent),
+        };
+        data.map_err(D::Error::custom)
+    }
+}
+
+impl VzData {
+    pub fn base(&self) -> &VzBase {
+        match self {
+            VzData::Pointer(p) => &p.base,
+            VzData::Module(m) => &m.base,
+            VzData::Range(r) => &r.base,
+            VzData::Function(f) => &f.base,
+            VzData::Variable(v) => &v.base,
+            VzData::JavaClass(c) => &c.base,
+            VzData::JavaMethod(m) => &m.base,
+            VzData::ObjCClass(c) => &c.base,
+            VzData::ObjCMethod(m) => &m.base,
+            VzData::Thread(t) => &t.base,
+            VzData::Hook(h) => &h.base,
+            VzData::Instruction(i) => &i.base,
+            VzData::ScanResult(s) => &s.base,
+            VzData::Import(i) => &i.base,
+            VzData::Symbol(s) => &s.base,
+            VzData::HookEvent(e) => &e.base,
+        }
+    }
+
+    pub fn base_mut(&mut self) -> &mut VzBase {
+        match self {
+            VzData::Pointer(p) => &mut p.base,
+            VzData::Module(m) => &mut m.base,
+            VzData::Range(r) => &mut r.base,
+            VzData::Function(f) => &mut f.base,
+            VzData::Variable(v) => &mut v.base,
+            VzData::JavaClass(c) => &mut c.base,
+            VzData::JavaMethod(m) => &mut m.base,
+            VzData::ObjCClass(c) => &mut c.base,
+            VzData::ObjCMethod(m) => &mut m.base,
+            VzData::Thread(t) => &mut t.base,
+            VzData::Hook(h) => &mut h.base,
+            VzData::Instruction(i) => &mut i.base,
+            VzData::ScanResult(s) => &mut s.base,
+            VzData::Import(i) => &mut i.base,
+            VzData::Symbol(s) => &mut s.base,
+            VzData::HookEvent(e) => &mut e.base,
+        }
+    }
 }
 
 impl fmt::Display for VzData {
@@ -140,13 +248,16 @@ impl fmt::Display for VzData {
             VzData::ScanResult(s) => write!(f, "{}", s),
             VzData::Import(i) => write!(f, "{}", i),
             VzData::Symbol(s) => write!(f, "{}", s),
+            VzData::HookEvent(e) => write!(f, "{}", e),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzPointer {
+    #[serde(flatten)]
     pub base: VzBase,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub size: usize,
     pub value_type: VzValueType,
@@ -165,10 +276,12 @@ impl fmt::Display for VzPointer {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzModule {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub size: usize,
 }
@@ -202,9 +315,11 @@ impl VzModule {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzRange {
+    #[serde(flatten)]
     pub base: VzBase,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub size: usize,
     pub protection: String,
@@ -240,10 +355,12 @@ impl VzRange {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzFunction {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub module: String,
 }
@@ -277,10 +394,12 @@ impl VzFunction {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzVariable {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub module: String,
 }
@@ -314,8 +433,9 @@ impl VzVariable {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzJavaClass {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
 }
@@ -331,8 +451,9 @@ impl fmt::Display for VzJavaClass {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzJavaMethod {
+    #[serde(flatten)]
     pub base: VzBase,
     pub class: String,
     pub name: String,
@@ -354,8 +475,9 @@ impl fmt::Display for VzJavaMethod {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzObjCClass {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
 }
@@ -371,8 +493,9 @@ impl fmt::Display for VzObjCClass {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzObjCMethod {
+    #[serde(flatten)]
     pub base: VzBase,
     pub class: String,
     pub name: String,
@@ -390,8 +513,9 @@ impl fmt::Display for VzObjCMethod {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzThread {
+    #[serde(flatten)]
     pub base: VzBase,
     pub id: u64,
 }
@@ -411,10 +535,12 @@ impl fmt::Display for VzThread {
 // New Types for Hooking, Disassembly, and Scanning
 // ============================================================================
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzHook {
+    #[serde(flatten)]
     pub base: VzBase,
     pub id: String,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub target_name: Option<String>,
     pub module: Option<String>,
@@ -466,9 +592,11 @@ impl VzHook {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzInstruction {
+    #[serde(flatten)]
     pub base: VzBase,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub size: usize,
     pub mnemonic: String,
@@ -507,9 +635,11 @@ impl VzInstruction {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzScanResult {
+    #[serde(flatten)]
     pub base: VzBase,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub size: usize,
     pub value: Option<String>,
@@ -542,13 +672,22 @@ impl VzScanResult {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzImport {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
+    #[serde(
+        serialize_with = "serialize_hex_opt",
+        deserialize_with = "deserialize_hex_opt"
+    )]
     pub address: Option<u64>,
     pub import_type: String,
     pub module: String,
+    #[serde(
+        serialize_with = "serialize_hex_opt",
+        deserialize_with = "deserialize_hex_opt"
+    )]
     pub slot: Option<u64>,
 }
 
@@ -584,10 +723,12 @@ impl VzImport {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VzSymbol {
+    #[serde(flatten)]
     pub base: VzBase,
     pub name: String,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
     pub address: u64,
     pub symbol_type: String,
     pub size: Option<usize>,
@@ -627,6 +768,86 @@ impl VzSymbol {
     }
 }
 
+/// A hook call captured into a store by `hook capture`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VzHookEvent {
+    #[serde(flatten)]
+    pub base: VzBase,
+    pub id: String,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
+    pub address: u64,
+    pub kind: HookEventKind,
+    pub tid: u64,
+    pub depth: u64,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub args: Vec<EventValue>,
+    #[serde(default)]
+    pub retval: Option<EventValue>,
+    #[serde(default)]
+    pub backtrace: Vec<Frame>,
+}
+
+impl From<&HookEvent> for VzHookEvent {
+    fn from(event: &HookEvent) -> Self {
+        VzHookEvent {
+            base: new_base(VzDataType::HookEvent),
+            id: event.id.clone(),
+            address: string_to_u64(&event.address),
+            kind: event.kind,
+            tid: event.thread_id,
+            depth: event.depth,
+            timestamp: event.timestamp,
+            args: event.args.clone(),
+            retval: event.retval.clone(),
+            backtrace: event.backtrace.clone(),
+        }
+    }
+}
+
+impl fmt::Display for VzHookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Decoded values stand in for raw ones unless they span lines (hexdumps)
+        let short = |v: &EventValue| match &v.decoded {
+            Some(d) if !d.contains('\n') => d.clone(),
+            _ => v.value.clone(),
+        };
+        let args = self.args.iter().map(short).collect::<Vec<_>>().join(", ");
+        let retval = self
+            .retval
+            .as_ref()
+            .map(|r| format!(" = {}", short(r)))
+            .unwrap_or_default();
+        write!(
+            f,
+            "{} {} {} {} @ {} {}{}",
+            format!("[{}]", self.base.data_type).blue(),
+            format_timestamp(self.timestamp).dark_grey(),
+            self.id.clone().cyan(),
+            self.kind.name(),
+            format!("{:#x}", self.address).yellow(),
+            format!("(tid: {}) ({})", self.tid, args).dark_grey(),
+            retval.yellow()
+        )
+    }
+}
+
+impl VzHookEvent {
+    /// Raw value of a selector member: `argN`, `retval` or `caller` (the
+    /// return address)
+    pub fn member(&self, name: &str) -> Option<u64> {
+        let value = match name {
+            "retval" => &self.retval.as_ref()?.value,
+            "caller" => &self.backtrace.first()?.address,
+            _ => {
+                let index = name.strip_prefix("arg")?.parse::<usize>().ok()?;
+                &self.args.get(index)?.value
+            }
+        };
+        crate::util::format::parse_hex_or_decimal(value).ok()
+    }
+}
+
 // ============================================================================
 // Utility Functions
 // ============================================================================
@@ -636,10 +857,48 @@ pub fn string_to_u64(s: &str) -> u64 {
     u64::from_str_radix(s, 16).unwrap_or(0)
 }
 
+/// Addresses are emitted as hex strings; tagged pointers exceed what JSON numbers hold exactly
+fn serialize_hex<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#x}", value))
+}
+
+fn serialize_hex_opt<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(v) => serialize_hex(v, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum HexOrNumber {
+        Hex(String),
+        Number(u64),
+    }
+    match HexOrNumber::deserialize(deserializer)? {
+        HexOrNumber::Hex(s) => {
+            crate::util::format::parse_hex_or_decimal(&s).map_err(D::Error::custom)
+        }
+        HexOrNumber::Number(n) => Ok(n),
+    }
+}
+
+fn deserialize_hex_opt<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "deserialize_hex")] u64);
+    Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(v)| v))
+}
+
 /// Create a new VzBase with the specified data type
 pub fn new_base(data_type: VzDataType) -> VzBase {
     VzBase {
         data_type,
         is_saved: false,
+        label: None,
+        comment: None,
+        tags: Vec::new(),
     }
 }
@@ -0,0 +1,134 @@
+// src/gum/watch.rs
+//
+// `watch` reports accesses to a range of memory through Frida's
+// MemoryAccessMonitor. The agent sends a `watch:hit` message for each
+// access to a watched byte.
+
+use super::event::{format_timestamp, Frame, BACKTRACE_FRAMES, HOOK_EVENT_VERSION};
+use crossterm::style::Stylize;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchHit {
+    pub id: String,
+    /// `read`, `write` or `execute`
+    pub operation: String,
+    pub address: String,
+    /// The instruction that made the access
+    pub from: Frame,
+    /// Not reported by older Frida versions
+    #[serde(default)]
+    pub thread_id: Option<u64>,
+    #[serde(default)]
+    pub backtrace: Vec<Frame>,
+    pub timestamp: u64,
+}
+
+/// Operation names for the `--read`, `--write` and `--exec` flags
+pub fn operation(flag: &str) -> Option<&'static str> {
+    match flag {
+        "-r" | "--read" => Some("read"),
+        "-w" | "--write" => Some("write"),
+        "-x" | "--exec" => Some("execute"),
+        _ => None,
+    }
+}
+
+impl WatchHit {
+    /// Decode a `watch:hit` message; `None` for other messages and newer
+    /// schemas
+    pub fn parse(payload: &Value) -> Option<WatchHit> {
+        if payload.get("type")?.as_str()? != "watch:hit" {
+            return None;
+        }
+        if payload.get("v")?.as_u64()? > HOOK_EVENT_VERSION {
+            return None;
+        }
+        serde_json::from_value(payload.clone()).ok()
+    }
+
+    pub fn render(&self) -> String {
+        let thread = self
+            .thread_id
+            .map(|tid| format!(" (tid: {})", tid))
+            .unwrap_or_default();
+        let mut output = format!(
+            "{} {} {} {} @ {} by {} {}{}",
+            format_timestamp(self.timestamp).dark_grey(),
+            "[WATCH]".red(),
+            self.id.clone().cyan(),
+            self.operation,
+            self.address.clone().yellow(),
+            self.from.address.clone().yellow(),
+            self.from.location(),
+            thread
+        );
+        if !self.backtrace.is_empty() {
+            output.push_str("\n  Backtrace:");
+            for (i, frame) in self.backtrace.iter().take(BACKTRACE_FRAMES).enumerate() {
+                output.push_str(&format!(
+                    "\n    #{} {} {}",
+                    i,
+                    frame.address.clone().yellow(),
+                    frame.location().dark_grey()
+                ));
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::format::plain;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_and_render() {
+        let payload = json!({
+            "type": "watch:hit",
+            "v": 1,
+            "id": "watch_0",
+            "operation": "write",
+            "address": "0x5000",
+            "from": {"address": "0x401234", "module": "app", "symbol": "update", "offset": 0x1234},
+            "threadId": 7,
+            "backtrace": [
+                {"address": "0x401234", "module": "app", "symbol": "update", "offset": 0x1234},
+                {"address": "0x400800", "module": "app", "symbol": "main", "offset": 0x800}
+            ],
+            "timestamp": 1_000
+        });
+        let hit = WatchHit::parse(&payload).unwrap();
+        assert_eq!(hit.thread_id, Some(7));
+        assert_eq!(
+            plain(hit.render()),
+            "00:00:01.000 [WATCH] watch_0 write @ 0x5000 by 0x401234 app!update +0x1234 (tid: 7)\n  \
+             Backtrace:\n    \
+             #0 0x401234 app!update +0x1234\n    \
+             #1 0x400800 app!main +0x800"
+        );
+
+        let mut bare = payload.clone();
+        bare["threadId"] = Value::Null;
+        bare["backtrace"] = json!([]);
+        assert_eq!(
+            plain(WatchHit::parse(&bare).unwrap().render()),
+            "00:00:01.000 [WATCH] watch_0 write @ 0x5000 by 0x401234 app!update +0x1234"
+        );
+
+        let mut newer = payload.clone();
+        newer["v"] = json!(HOOK_EVENT_VERSION + 1);
+        assert_eq!(WatchHit::parse(&newer), None);
+    }
+
+    #[test]
+    fn test_operation_flags() {
+        assert_eq!(operation("--write"), Some("write"));
+        assert_eq!(operation("-x"), Some("execute"));
+        assert_eq!(operation("--prot"), None);
+    }
+}
@@ -80,3 +80,9 @@ pub fn lengthed(s: &str, size: usize) -> String {
         format!("{:<size$}", s, size = size + (s.len() - stripped_str.len()))
     }
 }
+
+/// Text with the ANSI color codes removed, for comparing rendered output in tests
+#[cfg(test)]
+pub(crate) fn plain(s: impl AsRef<[u8]>) -> String {
+    String::from_utf8(strip_ansi_escapes::strip(s)).unwrap()
+}